@@ -0,0 +1,73 @@
+//! Folds an optional bundled JSON preset, named by the `DAB_CONFIG`
+//! environment variable, into the `DAB_*` build-time variables read by
+//! `src/lib/config.rs`. Variables set explicitly take precedence over the
+//! preset.
+//!
+//! Also bundles the neural network named by `DAB_NETWORK`, if any, as
+//! `network.bin` in `OUT_DIR`, for the AI's evaluation.
+
+use std::{env, fs, path::Path};
+
+const KEYS: [(&str, &str); 15] = [
+    ("board_width", "DAB_BOARD_WIDTH"),
+    ("board_height", "DAB_BOARD_HEIGHT"),
+    ("board_mask", "DAB_BOARD_MASK"),
+    ("board_values", "DAB_BOARD_VALUES"),
+    ("theme_red", "DAB_THEME_RED"),
+    ("theme_blue", "DAB_THEME_BLUE"),
+    ("theme_board", "DAB_THEME_BOARD"),
+    ("theme_board_size", "DAB_THEME_BOARD_SIZE"),
+    ("gamepad", "DAB_GAMEPAD"),
+    ("sound", "DAB_SOUND"),
+    ("share", "DAB_SHARE"),
+    ("engine", "DAB_ENGINE"),
+    ("ai_box_value", "DAB_AI_BOX_VALUE"),
+    ("ai_chain_value", "DAB_AI_CHAIN_VALUE"),
+    ("ai_parity_bonus", "DAB_AI_PARITY_BONUS"),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=DAB_CONFIG");
+    println!("cargo:rerun-if-env-changed=DAB_NETWORK");
+    bundle_network();
+    for (_, var) in KEYS {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    let path = match env::var("DAB_CONFIG") {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    println!("cargo:rerun-if-changed={}", path);
+    let text = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("failed to read DAB_CONFIG {}: {}", path, err));
+    let preset: serde_json::Value = serde_json::from_str(&text)
+        .unwrap_or_else(|err| panic!("failed to parse DAB_CONFIG {}: {}", path, err));
+    for (key, var) in KEYS {
+        if env::var(var).is_ok() {
+            continue;
+        }
+        let value = match preset.get(key) {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+            None => continue,
+        };
+        println!("cargo:rustc-env={}={}", var, value);
+    }
+}
+
+/// Copies the `DAB_NETWORK` file to `OUT_DIR`, or writes an empty file there
+/// if the variable is not set.
+fn bundle_network() {
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR is set by cargo")).join("network.bin");
+    let bytes = match env::var("DAB_NETWORK") {
+        Ok(path) => {
+            println!("cargo:rerun-if-changed={}", path);
+            fs::read(&path)
+                .unwrap_or_else(|err| panic!("failed to read DAB_NETWORK {}: {}", path, err))
+        }
+        Err(_) => vec![],
+    };
+    fs::write(&out, bytes)
+        .unwrap_or_else(|err| panic!("failed to write {}: {}", out.display(), err));
+}
@@ -0,0 +1,21 @@
+#!/bin/sh
+# Builds the release WASM bundle and fails when it grows past its budget.
+#
+# Usage: scripts/check-wasm-size.sh [cargo build flags...]
+# The budget in bytes can be overridden with WASM_SIZE_BUDGET.
+set -eu
+
+budget="${WASM_SIZE_BUDGET:-1500000}"
+cd "$(dirname "$0")/.."
+cargo build --release --target wasm32-unknown-unknown --bin dots-and-boxes "$@"
+wasm="target/wasm32-unknown-unknown/release/dots-and-boxes.wasm"
+if command -v wasm-opt >/dev/null 2>&1; then
+    wasm-opt -Oz "$wasm" -o "$wasm.opt"
+    wasm="$wasm.opt"
+fi
+size=$(wc -c < "$wasm")
+echo "$wasm: $size bytes (budget $budget)"
+if [ "$size" -gt "$budget" ]; then
+    echo "WASM bundle exceeds its size budget" >&2
+    exit 1
+fi
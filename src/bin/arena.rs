@@ -0,0 +1,7 @@
+//! Plays two AI configurations against each other, natively, and prints
+//! their results. Run `cargo run --release --bin arena -- --help` for its
+//! options.
+
+fn main() {
+    dots_and_boxes::run_arena();
+}
@@ -0,0 +1,7 @@
+//! Headless engine speaking a UCI-style text protocol on stdin and stdout,
+//! for driving the AI from other programs. See `run_engine` for the
+//! commands.
+
+fn main() {
+    dots_and_boxes::run_engine();
+}
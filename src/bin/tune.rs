@@ -0,0 +1,6 @@
+//! Tunes the weights of the minimax AI's heuristic by self-play, natively.
+//! Run `cargo run --release --bin tune -- --help` for its options.
+
+fn main() {
+    dots_and_boxes::run_tune();
+}
@@ -0,0 +1,8 @@
+//! Web Worker running the AI's searches off the main thread, loaded by
+//! `AIWorker` in the library. Builds without the `ai` feature have nothing
+//! to search, so their worker does nothing.
+
+fn main() {
+    #[cfg(feature = "ai")]
+    dots_and_boxes::run_ai_worker();
+}
@@ -1,18 +1,247 @@
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+#[cfg(target_arch = "wasm32")]
 use web_sys::console;
 
-use crate::lib::minmax::*;
-use crate::lib::{BarDirection, BarId, BarVec, BoardState, CellState, Player};
+use crate::book::OpeningBook;
+use crate::config::parse;
+use crate::external::AIExternal;
+use crate::minmax::*;
+use crate::network::Network;
+use crate::stats;
+use crate::{
+    box_balance, score_balance, BarId, BarVec, BoardState, CellState, Difficulty, Engine, Player,
+};
+use crate::{Evaluation, SearchStats};
+use crate::{AI_SEARCH_DEPTH, AI_TIME_BUDGET_MS};
 
-pub type AIOptions = MinMaxOptions;
+pub struct AIOptions {
+    /// How `AIMinMax` searches. `AIMcts` only uses the time budget.
+    pub search: MinMaxOptions,
+    /// Random playouts the heuristic adds to the chain evaluation.
+    pub playouts: u32,
+    /// Plays more playouts as the board fills up, up to
+    /// `MAX_PLAYOUT_SCALE` times `playouts`, so each evaluation takes
+    /// about as long as one on the empty board. Their results are averaged,
+    /// so the heuristic keeps its scale.
+    pub adaptive_playouts: bool,
+    /// Seeds the AI's random number generator, so a game can be replayed
+    /// move for move, e.g. to debug a blunder. Without one, the seed is
+    /// random. Searches cut short by a time budget can still differ.
+    pub seed: Option<u64>,
+    /// Scores positions with this network instead of the chain evaluation
+    /// and playouts, on boards of the size it was trained for. Used by
+    /// `AIMinMax` only.
+    pub network: Option<Arc<Network>>,
+    /// Weights of the chain evaluation in `AIMinMax`'s heuristic.
+    pub weights: HeuristicWeights,
+    /// How `AIMinMax` likes to play, beyond what its search finds best.
+    pub style: Style,
+    /// Solves the game exactly once fewer than `ENDGAME_FREE_BARS` bars are
+    /// free, instead of searching it with the heuristic. Used by `AIMinMax`
+    /// only.
+    pub endgame_solver: bool,
+}
+
+impl Default for AIOptions {
+    fn default() -> Self {
+        Self {
+            search: MinMaxOptions::default(),
+            playouts: HEURISTIC_PLAYOUTS,
+            adaptive_playouts: true,
+            seed: None,
+            network: None,
+            weights: HeuristicWeights::default(),
+            style: Style::default(),
+            endgame_solver: true,
+        }
+    }
+}
+
+impl From<Difficulty> for AIOptions {
+    fn from(difficulty: Difficulty) -> Self {
+        let (depth, playouts, mistake_probability) = match difficulty {
+            Difficulty::Easy => (1, 0, 0.3),
+            Difficulty::Medium => (2, 1, 0.1),
+            Difficulty::Hard => (AI_SEARCH_DEPTH, HEURISTIC_PLAYOUTS, 0.0),
+        };
+        let network = match difficulty {
+            Difficulty::Hard => bundled_network(),
+            Difficulty::Easy | Difficulty::Medium => None,
+        };
+        Self {
+            search: MinMaxOptions {
+                depth,
+                // Vary between equally good moves so rematches play out
+                // differently.
+                tie_epsilon: Some(0),
+                mistake_probability,
+                time_budget_ms: Some(AI_TIME_BUDGET_MS),
+            },
+            playouts,
+            adaptive_playouts: true,
+            seed: None,
+            network,
+            weights: HeuristicWeights::from_build_env(),
+            style: Style::default(),
+            // Perfect play in the endgame would make up for the mistakes of
+            // the easier levels.
+            endgame_solver: difficulty == Difficulty::Hard,
+        }
+    }
+}
+
+/// Playing style of `AIMinMax`, as biases on the scores of its moves, so
+/// one engine can play like different opponents. All are in boxes, and the
+/// default of zero plays by the scores alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    /// Bonus for taking a box, even when declining it would keep control.
+    /// Negative values decline more readily.
+    pub aggression: f64,
+    /// Bonus for giving boxes away while at least half of the bars are
+    /// free. Negative values avoid early sacrifices.
+    pub early_sacrifice: f64,
+    /// Picks a random move instead of the best one, each move being e times
+    /// less likely for every this many boxes it scores below the best.
+    /// Replaces `MinMaxOptions::tie_epsilon` and `mistake_probability` when
+    /// positive.
+    pub temperature: f64,
+}
+
+/// Heuristic points the chain evaluation gives for the expected split of
+/// the boxes, see `chain_evaluation`. The defaults count every box three
+/// times, which the `tune` binary refines by self-play.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeuristicWeights {
+    /// Per box already taken or capturable right away.
+    pub box_value: i32,
+    /// Per box of the long chains and loops the player in control is
+    /// expected to keep, net of the boxes it gives back.
+    pub chain_value: i32,
+    /// For being in control of the long chains and loops at all.
+    pub parity_bonus: i32,
+}
+
+impl Default for HeuristicWeights {
+    fn default() -> Self {
+        Self {
+            box_value: 3,
+            chain_value: 3,
+            parity_bonus: 0,
+        }
+    }
+}
+
+impl HeuristicWeights {
+    /// The defaults, overridden by the `DAB_AI_*` build-time variables or
+    /// the `ai_*` keys of a `DAB_CONFIG` preset, as written by `tune`.
+    pub fn from_build_env() -> Self {
+        let default = Self::default();
+        Self {
+            box_value: parse("DAB_AI_BOX_VALUE", option_env!("DAB_AI_BOX_VALUE"))
+                .unwrap_or(default.box_value),
+            chain_value: parse("DAB_AI_CHAIN_VALUE", option_env!("DAB_AI_CHAIN_VALUE"))
+                .unwrap_or(default.chain_value),
+            parity_bonus: parse("DAB_AI_PARITY_BONUS", option_env!("DAB_AI_PARITY_BONUS"))
+                .unwrap_or(default.parity_bonus),
+        }
+    }
+}
+
+/// The network bundled with `DAB_NETWORK` at build time, if any. A
+/// malformed one is reported once and ignored.
+fn bundled_network() -> Option<Arc<Network>> {
+    static BUNDLED: OnceLock<Option<Arc<Network>>> = OnceLock::new();
+    let network = BUNDLED.get_or_init(|| {
+        let bytes = include_bytes!(concat!(env!("OUT_DIR"), "/network.bin"));
+        if bytes.is_empty() {
+            return None;
+        }
+        let network = Network::from_bytes(bytes).map(Arc::new);
+        if network.is_none() {
+            let error = "bundled neural network is malformed, ignoring it";
+            // The console only exists in the browser, not in the native tools.
+            #[cfg(target_arch = "wasm32")]
+            console::error_1(&error.into());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", error);
+        }
+        network
+    });
+    network.clone()
+}
+
+/// Random playouts the heuristic adds to the chain evaluation. With the
+/// default weights, the heuristic counts each box five times.
+const HEURISTIC_PLAYOUTS: u32 = 2;
+/// Most playouts `AIOptions::adaptive_playouts` plays, as a multiple of
+/// `AIOptions::playouts`.
+const MAX_PLAYOUT_SCALE: u32 = 4;
+
+/// Positions with fewer free bars than this are solved exactly by
+/// `EndgameSolver` instead of estimated by the heuristic.
+const ENDGAME_FREE_BARS: u32 = 14;
+/// The endgame table is cleared when it grows beyond this many positions.
+const ENDGAME_TABLE_CAPACITY: usize = 1 << 20;
+
+/// How many boxes worse than the best move a move must score for
+/// `review_move` to point it out.
+const REVIEW_MARGIN_BOXES: i32 = 2;
 
+/// An AI player. Games hold them as trait objects, so the engine can be
+/// switched at runtime, see `new_ai`.
 pub trait AI {
-    fn new(board_state: &BoardState, ai_player: Player) -> Self;
+    fn new(board_state: &BoardState, ai_player: Player) -> Self
+    where
+        Self: Sized;
     fn set_options(&mut self, options: AIOptions);
     fn next_move(&mut self, board_state: &BoardState) -> Option<BarId>;
+    /// Like `next_move`, and passes the best move found so far to
+    /// `on_progress` while searching, for showing what the AI considers.
+    /// The default reports nothing.
+    fn next_move_with_progress(
+        &mut self,
+        board_state: &BoardState,
+        _on_progress: &mut dyn FnMut(BarId),
+    ) -> Option<BarId> {
+        self.next_move(board_state)
+    }
+    /// Checks `bar` in `board_state` before the side to move plays it, be
+    /// it the AI's side or its opponent. Returns a clearly better move if
+    /// there was one.
+    fn review_move(&mut self, board_state: &BoardState, bar: BarId) -> Option<BarId>;
+    fn last_search_stats(&self) -> Option<&SearchStats>;
+    /// Assessment of `board_state` from Red's perspective. The default is
+    /// `static_evaluation`.
+    fn evaluate(&mut self, board_state: &BoardState) -> Evaluation {
+        static_evaluation(board_state)
+    }
+    /// Chances of `player` winning from `board_state`, counting a draw as
+    /// half a win. The default maps `evaluate` with
+    /// `Evaluation::win_probability`.
+    fn win_probability(&mut self, board_state: &BoardState, player: Player) -> f64 {
+        self.evaluate(board_state)
+            .win_probability(board_state, player)
+    }
+}
+
+/// A new AI of `engine`, playing `ai_player` in games starting from
+/// `board_state`.
+pub fn new_ai(engine: Engine, board_state: &BoardState, ai_player: Player) -> Box<dyn AI> {
+    match engine {
+        Engine::MinMax => Box::new(<AIMinMax as AI>::new(board_state, ai_player)),
+        Engine::Mcts => Box::new(AIMcts::new(board_state, ai_player)),
+        Engine::Random => Box::new(AIRandom::new(board_state, ai_player)),
+        Engine::Greedy => Box::new(AIGreedy::new(board_state, ai_player)),
+        Engine::External => Box::new(AIExternal::new(board_state, ai_player)),
+    }
 }
 
+pub(crate) use intern::AIState;
+
 mod intern {
     use super::*;
 
@@ -21,21 +250,14 @@ mod intern {
     }
 
     impl PossibleMovesIterator<AIState, BarId> for PossibleMovesIter {
-        fn new<'a>(_state: &'a AIState) -> Self {
+        fn new(_state: &AIState) -> Self {
             Self { cur_index: 0 }
         }
 
-        fn next<'a>(&mut self, state: &'a AIState) -> Option<BarId> {
-            let first_free_from_index = |start_index: u32, vec: &BarVec| {
-                (start_index..vec.length).find_map(|index| {
-                    let bar_id = vec.index_to_id(index);
-                    let cell_state = vec.get(bar_id.col, bar_id.row);
-                    if cell_state != CellState::Free {
-                        None
-                    } else {
-                        Some((bar_id, index))
-                    }
-                })
+        fn next(&mut self, state: &AIState) -> Option<BarId> {
+            let first_free_from_index = |start_index: u32, bars: &BarVec| {
+                let index = bars.first_free(start_index)?;
+                Some((bars.index_to_id(index), index))
             };
 
             let board_state: &BoardState = state;
@@ -63,63 +285,134 @@ mod intern {
         }
     }
 
+    #[derive(Clone)]
     pub struct AIState {
         board_state: BoardState,
         mutation_stack: Vec<BarId>,
+        /// The free bars in the order `random_playout` plays them, kept so
+        /// playouts reuse its allocation.
+        playout_moves: Vec<BarId>,
     }
 
     impl MinMaxState for AIState {
         type Move = BarId;
         type PossibleMovesIterator = PossibleMovesIter;
 
+        /// Symmetric positions have the same value, so they share their
+        /// entries in the transposition table.
+        fn position_hash(&self) -> u64 {
+            self.board_state.canonical_hash()
+        }
+
+        /// Like the opening book, see `book.rs`.
+        fn canonical_move(&self, mv: Self::Move) -> Self::Move {
+            let board = &self.board_state;
+            board.transform_bar(board.canonical_symmetry(), mv)
+        }
+
+        fn move_from_canonical(&self, mv: Self::Move) -> Self::Move {
+            let board = &self.board_state;
+            board.transform_bar(board.canonical_symmetry().inverse(), mv)
+        }
+
         fn _apply_move(&mut self, mv: Self::Move) -> bool {
             self.mutation_stack.push(mv);
-            self.board_state.do_move(mv)
+            let applied = self.board_state.do_move(mv);
+            #[cfg(feature = "invariants")]
+            self.check_invariants(|| format!("applying {}", mv));
+            applied
         }
 
         fn _undo_moves(&mut self, nr_moves: u32) -> bool {
-            (0..nr_moves).all(|_| {
+            let undone = (0..nr_moves).all(|_| {
                 let mv = self
                     .mutation_stack
                     .pop()
                     .expect("mutation stack empty during undo");
-                let neighbors = self.board_state.bar_neighbors(mv);
-                let point_scored = neighbors.iter().any(|tup| {
-                    let (col, row) = *tup;
-                    let cell_state = self.board_state.cell_get(col, row);
-                    assert!(
-                        cell_state != CellState::Player(self.board_state.cur_turn.other()),
-                        "wrong cell state, cell_state: {:?}, cur_turn: {:?}",
-                        cell_state,
-                        self.board_state.cur_turn,
+                let undone = self.board_state.unplay(mv).is_some();
+                #[cfg(feature = "invariants")]
+                if !undone {
+                    self.report_broken_invariants(
+                        &format!("undoing {}", mv),
+                        &["the bar is not drawn".to_string()],
                     );
-                    cell_state != CellState::Free
-                });
-                let this_turn = if point_scored {
-                    self.board_state.cur_turn
-                } else {
-                    self.board_state.cur_turn.other()
-                };
-                let vec = match mv.direction {
-                    BarDirection::Vertical => &mut self.board_state.vstates,
-                    BarDirection::Horizontal => &mut self.board_state.hstates,
-                };
-                let state = vec.get(mv.col, mv.row);
-                if state != CellState::Player(this_turn) {
-                    // panic!(
-                    //     "state: {:?}, cur_player: {:?}, this turn: {:?}",
-                    //     state, self.board_state.cur_turn, this_turn
-                    // );
-                    return false;
                 }
+                undone
+            });
+            #[cfg(feature = "invariants")]
+            self.check_invariants(|| format!("undoing {} move(s)", nr_moves));
+            undone
+        }
+    }
 
-                vec.set(mv.col, mv.row, CellState::Free);
-                for cell in neighbors.into_iter() {
-                    self.board_state.cell_set(cell.0, cell.1, CellState::Free);
+    #[cfg(feature = "invariants")]
+    impl AIState {
+        /// Panics with a report if the position is inconsistent: a box
+        /// owned without all its bars or free with them, a box owned by a
+        /// player who drew none of its bars, a side to move that does not
+        /// follow from the last move, or hashes that differ from the ones
+        /// computed from scratch. `operation` names what was just done.
+        fn check_invariants(&self, operation: impl FnOnce() -> String) {
+            let board = &self.board_state;
+            let mut broken = vec![];
+            for (col, row, owner) in board.cells() {
+                let bars = board.cell_bars(col, row).map(|bar| board.bar_get(bar));
+                if board.cell_is_full(col, row) != matches!(owner, CellState::Player(_)) {
+                    broken.push(format!(
+                        "box ({},{}) is {} with {} free bars",
+                        col,
+                        row,
+                        owner,
+                        board.open_edges(col, row)
+                    ));
+                } else if matches!(owner, CellState::Player(_)) && !bars.contains(&owner) {
+                    broken.push(format!(
+                        "box ({},{}) is owned by {} who drew none of its bars",
+                        col, row, owner
+                    ));
                 }
-                self.board_state.cur_turn = this_turn;
-                true
-            })
+            }
+            if let Some(&last) = self.mutation_stack.last() {
+                match board.bar_get(last) {
+                    CellState::Player(mover) => {
+                        let scored = board
+                            .bar_neighbors(last)
+                            .any(|(col, row)| board.cell_get(col, row) != CellState::Free);
+                        let keeps_turn = scored && !board.rules.alternate_turns;
+                        let expected = if keeps_turn { mover } else { mover.other() };
+                        if board.cur_turn != expected {
+                            broken.push(format!(
+                                "{} is to move after {} {} {}",
+                                board.cur_turn,
+                                mover,
+                                if scored { "scored with" } else { "drew" },
+                                last
+                            ));
+                        }
+                    }
+                    CellState::Free => broken.push(format!("the last move {} is not drawn", last)),
+                    CellState::Neutral => broken.push(format!("the last move {} is neutral", last)),
+                }
+            }
+            if board.hashes != board.full_hashes() {
+                broken.push("the hashes differ from the ones computed from scratch".to_string());
+            }
+            if !broken.is_empty() {
+                self.report_broken_invariants(&operation(), &broken);
+            }
+        }
+
+        fn report_broken_invariants(&self, operation: &str, broken: &[String]) {
+            let moves = (self.mutation_stack.iter())
+                .map(|bar| bar.to_string())
+                .collect::<Vec<_>>();
+            panic!(
+                "AI state broken after {}:\n  {}\nposition: {}\nmoves played: {}",
+                operation,
+                broken.join("\n  "),
+                self.board_state.to_fen(),
+                moves.join(" ")
+            );
         }
     }
 
@@ -128,6 +421,7 @@ mod intern {
             Self {
                 board_state,
                 mutation_stack: vec![],
+                playout_moves: vec![],
             }
         }
     }
@@ -140,71 +434,383 @@ mod intern {
         }
     }
 
-    pub fn random_free_move<R: Rng>(state: &AIState, rng: &mut R) -> Option<BarId> {
-        let total_bars = state.vstates.length + state.hstates.length;
-        for _ in 0..3 {
-            let chosen_index = rng.gen_range(0..total_bars);
-            let (vec, index) = if chosen_index < state.vstates.length {
-                (&state.vstates, chosen_index)
-            } else {
-                (&state.hstates, chosen_index - state.vstates.length)
-            };
-            let bar_id = vec.index_to_id(index);
-            let state = vec.get(bar_id.col, bar_id.row);
-            if state == CellState::Free {
-                return Some(bar_id);
+    /// Finishes the game with uniformly random moves and returns Red's
+    /// lead under the board's rules, see `score_balance`. `state` is
+    /// restored afterwards. Playing the free
+    /// bars in a shuffled order is a uniformly random playout, without
+    /// searching for a free bar after every move.
+    pub fn random_playout<R: Rng>(state: &mut AIState, rng: &mut R) -> i32 {
+        let mut free_moves = std::mem::take(&mut state.playout_moves);
+        free_moves.clear();
+        free_moves.extend(state.possible_moves());
+        free_moves.shuffle(rng);
+        let mut cur_state = state.checkpoint();
+        for mv in &free_moves {
+            cur_state.apply(*mv);
+        }
+        let margin = cur_state
+            .cells()
+            .map(|(col, row, cell_state)| match cell_state {
+                CellState::Free => panic!("found free cell in completed board"),
+                CellState::Neutral => 0,
+                CellState::Player(Player::Red) => cur_state.cell_value(col, row) as i32,
+                CellState::Player(Player::Blue) => -(cur_state.cell_value(col, row) as i32),
+            })
+            .sum::<i32>()
+            + cur_state.head_start_balance();
+        let margin = margin * cur_state.rules.box_value();
+        drop(cur_state);
+        state.playout_moves = free_moves;
+        margin
+    }
+
+    /// Red's points minus Blue's, plus the expected split of the free boxes,
+    /// in heuristic points given by `weights`:
+    ///
+    /// - Chains the side to move can start capturing go to it.
+    /// - Long chains (three boxes or more) and loops go to the player in
+    ///   control, minus the two boxes per chain and four per loop it gives
+    ///   back to keep control, except for the last one.
+    /// - Short chains and junction boxes are assumed to be shared evenly.
+    ///
+    /// The player in control is found with the long chain rule, see
+    /// `ChainParity`. In misère play the whole estimate is turned around,
+    /// which only roughly fits: control is worth less when boxes hurt.
+    /// Without extra turns, chains are not taken whole, and the estimate
+    /// is rougher still. Chains count the values of their boxes, but the
+    /// boxes given back to keep control count a point each.
+    pub fn chain_evaluation(state: &BoardState, weights: &HeuristicWeights) -> i32 {
+        let sign = |player| match player {
+            Player::Red => 1,
+            Player::Blue => -1,
+        };
+        let chains = state.chains();
+        let mut balance = box_balance(state);
+        let mut long_boxes = 0;
+        for chain in &chains {
+            let len = (chain.cells.iter())
+                .map(|cell| state.cell_value(cell.col, cell.row) as i32)
+                .sum::<i32>();
+            if chain.is_capturable() {
+                balance += sign(state.cur_turn) * len;
+            } else if chain.is_long() {
+                long_boxes += len;
+            }
+        }
+        let parity = state.chain_parity_of(&chains);
+        let box_value = state.rules.box_value();
+        let controller = match parity.controller {
+            Some(controller) => controller,
+            None => return box_value * weights.box_value * balance,
+        };
+        let (long_chains, loops) = (parity.long_chains as i32, parity.loops as i32);
+        let given_back = if long_chains > 0 {
+            2 * (long_chains - 1) + 4 * loops
+        } else {
+            4 * (loops - 1)
+        };
+        let control = weights.chain_value * (long_boxes - 2 * given_back) + weights.parity_bonus;
+        box_value * (weights.box_value * balance + sign(controller) * control)
+    }
+}
+
+/// Seed for the AI's random number generator. Taken from `Math.random` in
+/// the browser, which avoids pulling `getrandom` into the WASM bundle.
+pub(crate) fn entropy_seed() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let high = (js_sys::Math::random() * u32::MAX as f64) as u64;
+        let low = (js_sys::Math::random() * u32::MAX as f64) as u64;
+        (high << 32) | low
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    }
+}
+
+/// Solves the last moves of a game exactly. Positions are keyed by their
+/// free bars, the rules, the shape of the board and the values of its
+/// boxes only: who owns the boxes taken so far does not change how the rest
+/// of the game plays out.
+#[derive(Clone, Default)]
+struct EndgameSolver {
+    /// Value of a position from the point of view of the side to move, by
+    /// the XOR of the keys of its free bars.
+    table: HashMap<u64, i32>,
+}
+
+impl EndgameSolver {
+    /// Points the side to move takes from now on, minus the points its
+    /// opponent takes, with perfect play by both. Turned around in misère
+    /// play, where both try to take as few boxes as they can.
+    fn solve(&mut self, state: &mut intern::AIState) -> i32 {
+        if self.table.len() > ENDGAME_TABLE_CAPACITY {
+            self.table.clear();
+        }
+        let board_key =
+            BoardState::rules_key(state.rules) ^ state.mask_key() ^ state.values_key();
+        let key = (state.possible_moves()).fold(board_key, |key, bar| key ^ state.bar_key(bar));
+        self.solve_from(state, key)
+    }
+
+    fn solve_from(&mut self, state: &mut intern::AIState, key: u64) -> i32 {
+        if let Some(value) = self.table.get(&key) {
+            return *value;
+        }
+        let box_value = state.rules.box_value();
+        let mut moves = state.possible_moves().collect::<Vec<_>>();
+        // A capture that does not draw the third side of another box is
+        // never wrong: it cannot be part of a double-cross, so declining it
+        // gains nothing. Only the captures at the end of a chain leave a
+        // choice, between taking all boxes and handing the last two back.
+        // In misère play declining can pay, and without extra turns a
+        // capture hands the move over like any other.
+        if !state.rules.misere && !state.rules.alternate_turns {
+            if let Some(bar) =
+                (moves.iter()).find(|bar| completes_box(state, **bar) && !gives_box(state, **bar))
+            {
+                moves = vec![*bar];
             }
         }
-        let possible_moves = state.possible_moves().collect::<Vec<_>>();
-        possible_moves.choose(rng).cloned()
+        let mover = state.cur_turn;
+        let mut best = i32::MIN;
+        for bar in moves {
+            let captured = state
+                .bar_neighbors(bar)
+                .filter(|(col, row)| state.open_edges(*col, *row) == 1)
+                .map(|(col, row)| state.cell_value(col, row) as i32)
+                .sum::<i32>();
+            let child_key = key ^ state.bar_key(bar);
+            let mut child = state.checkpoint();
+            child.apply(bar);
+            let keeps_turn = child.cur_turn == mover;
+            let value = self.solve_from(&mut child, child_key);
+            best = best.max(box_value * captured + if keeps_turn { value } else { -value });
+        }
+        let best = if best == i32::MIN { 0 } else { best };
+        self.table.insert(key, best);
+        best
     }
 }
 
+#[derive(Clone)]
 pub struct AIMinMaxInterface {
-    options: AIOptions,
     ai_player: Player,
-    rng: ThreadRng,
+    rng: SmallRng,
+    playouts: u32,
+    adaptive_playouts: bool,
+    endgame: EndgameSolver,
+    network: Option<Arc<Network>>,
+    weights: HeuristicWeights,
+    style: Style,
+    endgame_solver: bool,
 }
 
 impl AIMinMaxInterface {
     fn new(ai_player: Player) -> Self {
-        let rng = rand::thread_rng();
-        let options = Default::default();
+        let rng = SmallRng::seed_from_u64(entropy_seed());
         Self {
-            options,
             ai_player,
             rng,
+            playouts: HEURISTIC_PLAYOUTS,
+            adaptive_playouts: true,
+            endgame: EndgameSolver::default(),
+            network: None,
+            weights: HeuristicWeights::default(),
+            style: Style::default(),
+            endgame_solver: true,
         }
     }
+
+    /// Heuristic points of a box that is certain to be won: its value in
+    /// the chain evaluation plus one per playout.
+    fn box_weight(&self) -> i32 {
+        self.weights.box_value + self.playouts as i32
+    }
+
+    /// Value of `state` with perfect play by both sides, weighted like a
+    /// finished game. Only feasible with fewer than `ENDGAME_FREE_BARS` free
+    /// bars.
+    fn exact_value(&mut self, state: &mut intern::AIState) -> i32 {
+        let sign = match self.ai_player {
+            Player::Red => 1,
+            Player::Blue => -1,
+        };
+        let to_move = if state.cur_turn == Player::Red { 1 } else { -1 };
+        let result = score_balance(state) + to_move * self.endgame.solve(state);
+        sign * self.box_weight() * result
+    }
+
+    /// The moves from `state` with their `exact_value`s.
+    fn solve_moves(&mut self, state: &mut intern::AIState) -> Vec<(BarId, i32)> {
+        let moves = state.possible_moves().collect::<Vec<_>>();
+        (moves.into_iter())
+            .map(|bar| {
+                let mut child = state.checkpoint();
+                child.apply(bar);
+                (bar, self.exact_value(&mut child))
+            })
+            .collect()
+    }
+
+    /// The moves perfect play by both sides follows from `state`, which must
+    /// have fewer than `ENDGAME_FREE_BARS` free bars.
+    fn solved_line(&mut self, state: &mut intern::AIState) -> Vec<BarId> {
+        let mut line = Vec::new();
+        let mut state = state.checkpoint();
+        loop {
+            // Values are from the AI's point of view.
+            let sign = if state.cur_turn == self.ai_player {
+                1
+            } else {
+                -1
+            };
+            let best = (self.solve_moves(&mut state).into_iter())
+                .max_by_key(|(_, value)| sign * value)
+                .map(|(bar, _)| bar);
+            let Some(bar) = best else {
+                return line;
+            };
+            state.apply(bar);
+            line.push(bar);
+        }
+    }
+
+    /// Adds the biases of `Style` to the scores of the moves from `state`.
+    fn apply_style(&self, state: &BoardState, scored_moves: &mut [(BarId, i32)]) {
+        let box_weight = self.box_weight() as f64;
+        let total = state.vstates.length + state.hstates.length;
+        let early = 2 * state.free_bar_count() >= total;
+        for (bar, score) in scored_moves {
+            let bias = if completes_box(state, *bar) {
+                self.style.aggression
+            } else if early && gives_box(state, *bar) {
+                self.style.early_sacrifice
+            } else {
+                0.0
+            };
+            *score += (bias * box_weight).round() as i32;
+        }
+    }
+
+    /// Draws a move with `Style::temperature`, none if it is not positive.
+    fn sample_move(&mut self, scored_moves: &[(BarId, i32)]) -> Option<BarId> {
+        if self.style.temperature <= 0.0 {
+            return None;
+        }
+        let best = scored_moves.iter().map(|(_, score)| *score).max()?;
+        let scale = self.style.temperature * self.box_weight() as f64;
+        let weights = (scored_moves.iter())
+            .map(|(_, score)| ((score - best) as f64 / scale).exp())
+            .collect::<Vec<_>>();
+        let mut remaining = self.rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for ((bar, _), weight) in scored_moves.iter().zip(weights) {
+            remaining -= weight;
+            if remaining < 0.0 {
+                return Some(*bar);
+            }
+        }
+        // Rounding left a sliver of the total, which is the best move's.
+        scored_moves
+            .iter()
+            .max_by_key(|(_, score)| *score)
+            .map(|(bar, _)| *bar)
+    }
 }
 
 impl MinMaxInterface for AIMinMaxInterface {
     type State = intern::AIState;
 
+    /// Mostly the deterministic `chain_evaluation`, with a few random
+    /// playouts mixed in to tell apart positions before chains form. Once
+    /// few bars are left, with `AIOptions::endgame_solver`, the exact result
+    /// of `EndgameSolver`, weighted like a finished game. With
+    /// `AIOptions::network`, its estimate of the final result, weighted the
+    /// same.
     fn heuristic(&mut self, state: &mut Self::State) -> i32 {
-        let nr_tests = 5;
-        let results = (0..nr_tests).map(|_| {
-            let mut cur_state = state.checkpoint();
-            while let Some(mv) = intern::random_free_move(&cur_state, &mut self.rng) {
-                cur_state.apply(mv);
-            }
-            let score: i32 = cur_state
-                .cellstates
-                .iter()
-                .map(|cell_state| match cell_state.clone() {
-                    CellState::Free => panic!("found free cell in completed board"),
-                    CellState::Player(player) => {
-                        if player == self.ai_player {
-                            1
-                        } else {
-                            -1
-                        }
-                    }
-                })
-                .sum();
-            score
+        let sign = match self.ai_player {
+            Player::Red => 1,
+            Player::Blue => -1,
+        };
+        let free = state.free_bar_count();
+        if self.endgame_solver && free < ENDGAME_FREE_BARS {
+            return self.exact_value(state);
+        }
+        if let Some(network) = self
+            .network
+            .as_ref()
+            .filter(|network| network.accepts(state))
+        {
+            let weight = self.box_weight() as f32;
+            return sign * (weight * network.evaluate(state)).round() as i32;
+        }
+        let count = if self.adaptive_playouts {
+            let total = state.vstates.length + state.hstates.length;
+            (self.playouts * total / free).min(self.playouts * MAX_PLAYOUT_SCALE)
+        } else {
+            self.playouts
+        };
+        let playouts = (0..count)
+            .map(|_| intern::random_playout(state, &mut self.rng))
+            .sum::<i32>();
+        // Averaged and scaled back to `self.playouts` playouts.
+        let playouts = if count > 0 {
+            playouts * self.playouts as i32 / count as i32
+        } else {
+            0
+        };
+        sign * (intern::chain_evaluation(state, &self.weights) + playouts)
+    }
+
+    /// The final lead under the board's rules, weighted like in the
+    /// heuristic.
+    fn terminal_value(&mut self, state: &mut Self::State) -> i32 {
+        let sign = match self.ai_player {
+            Player::Red => 1,
+            Player::Blue => -1,
+        };
+        sign * self.box_weight() * score_balance(state)
+    }
+
+    fn is_maximizing(&self, state: &Self::State) -> bool {
+        state.cur_turn == self.ai_player
+    }
+
+    /// Captures, so the search does not stop in the middle of taking a
+    /// chain.
+    fn is_forcing(&self, state: &Self::State, bar: BarId) -> bool {
+        completes_box(state, bar)
+    }
+
+    /// Captures first, then safe moves, then moves giving boxes away.
+    fn order_moves(&mut self, state: &Self::State, moves: &mut [BarId]) {
+        moves.sort_by_cached_key(|bar| {
+            if completes_box(state, *bar) {
+                0
+            } else if !gives_box(state, *bar) {
+                1
+            } else {
+                2
+            }
         });
-        results.sum::<i32>()
+    }
+
+    fn choose_index(&mut self, count: usize) -> usize {
+        self.rng.gen_range(0..count)
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.rng.gen_bool(probability.clamp(0.0, 1.0))
+    }
+
+    /// Seeds from the copied generator, so a seeded game still plays the
+    /// same moves.
+    fn reseed(&mut self, share: u64) {
+        self.rng = SmallRng::seed_from_u64(self.rng.gen::<u64>() ^ share);
     }
 }
 
@@ -218,11 +824,494 @@ impl AI for AIMinMax {
     }
 
     fn set_options(&mut self, options: AIOptions) {
-        self.set_options(options);
+        self.set_options(options.search);
+        let game = self.game_mut();
+        game.playouts = options.playouts;
+        game.adaptive_playouts = options.adaptive_playouts;
+        game.network = options.network;
+        game.weights = options.weights;
+        game.style = options.style;
+        game.endgame_solver = options.endgame_solver;
+        if let Some(seed) = options.seed {
+            game.rng = SmallRng::seed_from_u64(seed);
+        }
     }
 
     fn next_move(&mut self, state: &BoardState) -> Option<BarId> {
+        self.next_move_with_progress(state, &mut |_| {})
+    }
+
+    fn next_move_with_progress(
+        &mut self,
+        state: &BoardState,
+        on_progress: &mut dyn FnMut(BarId),
+    ) -> Option<BarId> {
+        self.set_root_state(state.clone().into());
+        if let Some(bar) = book_move(state, &mut self.game_mut().rng) {
+            // Nothing was searched.
+            self.set_last_stats(SearchStats::default());
+            return Some(bar);
+        }
+        let solved = self.game_mut().endgame_solver && state.free_bar_count() < ENDGAME_FREE_BARS;
+        let start_ms = stats::now_ms();
+        let mut scored_moves = if solved {
+            // Few enough bars are left to solve the rest of the game
+            // instead of searching it.
+            let scored_moves = self.game_mut().solve_moves(&mut state.clone().into());
+            if let Some((bar, _)) = scored_moves.iter().max_by_key(|(_, score)| *score) {
+                on_progress(*bar);
+            }
+            scored_moves
+        } else {
+            self.score_moves_with_progress(on_progress)
+        };
+        let game = self.game_mut();
+        game.apply_style(state, &mut scored_moves);
+        let bar = match game.sample_move(&scored_moves) {
+            Some(bar) => Some(bar),
+            None => self.pick_move(scored_moves),
+        };
+        // The line starts with the move played, which is not always the
+        // best one.
+        let mut stats = if solved {
+            SearchStats {
+                duration_ms: stats::now_ms() - start_ms,
+                solved: true,
+                ..Default::default()
+            }
+        } else {
+            self.last_stats().cloned().unwrap_or_default()
+        };
+        stats.principal_variation = match bar {
+            Some(bar) if solved => {
+                let mut state = intern::AIState::from(state.clone());
+                let mut after = state.checkpoint();
+                after.apply(bar);
+                let mut line = vec![bar];
+                line.extend(self.game_mut().solved_line(&mut after));
+                line
+            }
+            Some(bar) => self.variation(bar),
+            None => vec![],
+        };
+        self.set_last_stats(stats);
+        bar
+    }
+
+    /// The margin is in boxes, so it means the same whatever the playouts
+    /// of the difficulty.
+    fn review_move(&mut self, state: &BoardState, bar: BarId) -> Option<BarId> {
+        self.set_root_state(state.clone().into());
+        let mut scored_moves = self.score_moves();
+        if !scored_moves.iter().any(|(mv, _)| *mv == bar) {
+            // The time budget ran out before `bar` was scored.
+            let score = self.score_move(bar);
+            scored_moves.push((bar, score));
+        }
+        let game = self.game_mut();
+        // Scores are from the AI's point of view.
+        let sign = if state.cur_turn == game.ai_player { 1 } else { -1 };
+        let played = sign * scored_moves.iter().find(|(mv, _)| *mv == bar)?.1;
+        let (best_move, best) = (scored_moves.into_iter())
+            .map(|(mv, score)| (mv, sign * score))
+            .max_by_key(|(_, score)| *score)?;
+        (best - played >= REVIEW_MARGIN_BOXES * game.box_weight()).then_some(best_move)
+    }
+
+    /// The score of the best move for the side to move, converted to boxes.
+    /// Exact once few enough bars are free to solve the rest of the game.
+    fn evaluate(&mut self, state: &BoardState) -> Evaluation {
+        if (state.free_bar_count()) < ENDGAME_FREE_BARS {
+            return static_evaluation(state);
+        }
         self.set_root_state(state.clone().into());
-        self.best_move()
+        let scores = self.score_moves().into_iter().map(|(_, score)| score);
+        let game = self.game_mut();
+        // Scores are from the AI's point of view.
+        let (best, sign) = match game.ai_player {
+            Player::Red if state.cur_turn == Player::Red => (scores.max(), 1),
+            Player::Red => (scores.min(), 1),
+            Player::Blue if state.cur_turn == Player::Blue => (scores.max(), -1),
+            Player::Blue => (scores.min(), -1),
+        };
+        let Some(best) = best else {
+            return static_evaluation(state);
+        };
+        Evaluation {
+            margin: (f64::from(sign * best) / f64::from(game.box_weight())).round() as i32,
+            exact: false,
+        }
+    }
+
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        self.last_stats()
+    }
+}
+
+/// Upper bound on the playouts of one Monte Carlo search, for searches
+/// without a time budget.
+const MCTS_MAX_ITERATIONS: u32 = 20_000;
+/// Exploration constant of the UCT formula.
+const MCTS_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// How much lower the opponent's win rate after their move must be than
+/// after the best move, for `review_move` to point it out.
+const MCTS_REVIEW_MARGIN: f64 = 0.25;
+
+/// Node of the Monte Carlo search tree, reached by `mover` playing `mv`.
+/// `reward` is the sum of the playout results from `mover`'s point of
+/// view: 1 for a win, 0.5 for a draw.
+struct MctsNode {
+    mv: Option<BarId>,
+    mover: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Moves without a child yet, in random order. Only filled in once the
+    /// node is expanded, as most leaves never are.
+    untried: Option<Vec<BarId>>,
+    visits: u32,
+    reward: f64,
+}
+
+impl MctsNode {
+    fn new(mv: Option<BarId>, mover: Player, parent: Option<usize>) -> Self {
+        Self {
+            mv,
+            mover,
+            parent,
+            children: vec![],
+            untried: None,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn mean_reward(&self) -> f64 {
+        if self.visits == 0 {
+            0.0
+        } else {
+            self.reward / self.visits as f64
+        }
+    }
+}
+
+/// Monte Carlo tree search with the UCT selection rule, using the same
+/// random playouts as `AIMinMax`'s heuristic. It spends its effort on the
+/// promising lines instead of a fixed depth, which scales better to large
+/// boards. Uses the time budget from `AIOptions`, the other options only
+/// apply to `AIMinMax`.
+pub struct AIMcts {
+    rng: SmallRng,
+    options: AIOptions,
+    last_stats: Option<SearchStats>,
+}
+
+impl AIMcts {
+    /// Searches from `board_state` and returns each move with its number of
+    /// visits and win rate for the side to move.
+    fn search(&mut self, board_state: &BoardState) -> Vec<(BarId, u32, f64)> {
+        let start_ms = stats::now_ms();
+        let deadline_ms = self
+            .options
+            .search
+            .time_budget_ms
+            .map(|budget_ms| start_ms + budget_ms);
+        let mut root_state: intern::AIState = board_state.clone().into();
+        let mut nodes = vec![MctsNode::new(None, root_state.cur_turn.other(), None)];
+        let mut iterations = 0;
+        let mut max_depth = 0;
+        while iterations < MCTS_MAX_ITERATIONS
+            && !deadline_ms.is_some_and(|deadline_ms| stats::now_ms() >= deadline_ms)
+        {
+            iterations += 1;
+            let mut state = root_state.checkpoint();
+            let mut node = 0;
+            let mut depth = 0;
+            loop {
+                let untried = match &mut nodes[node].untried {
+                    Some(untried) => untried,
+                    untried @ None => {
+                        let mut moves = state.possible_moves().collect::<Vec<_>>();
+                        moves.shuffle(&mut self.rng);
+                        untried.insert(moves)
+                    }
+                };
+                // Expansion: add one untried move as a new leaf.
+                if let Some(mv) = untried.pop() {
+                    let mover = state.cur_turn;
+                    state.apply(mv);
+                    nodes.push(MctsNode::new(Some(mv), mover, Some(node)));
+                    let child = nodes.len() - 1;
+                    nodes[node].children.push(child);
+                    node = child;
+                    depth += 1;
+                    break;
+                }
+                // Selection: descend into the child with the best UCT value.
+                match self.select_child(&nodes, node) {
+                    Some(child) => {
+                        state.apply(nodes[child].mv.expect("child without move"));
+                        node = child;
+                        depth += 1;
+                    }
+                    None => break,
+                }
+            }
+            max_depth = max_depth.max(depth);
+            let margin = intern::random_playout(&mut state, &mut self.rng);
+            let mut cur = Some(node);
+            while let Some(id) = cur {
+                let node = &mut nodes[id];
+                node.visits += 1;
+                node.reward += playout_reward(margin, node.mover);
+                cur = node.parent;
+            }
+        }
+        self.last_stats = Some(SearchStats {
+            nodes: nodes.len() as u64,
+            table_hits: 0,
+            depth: max_depth,
+            max_depth,
+            duration_ms: stats::now_ms() - start_ms,
+            solved: false,
+            principal_variation: most_visited_line(&nodes),
+        });
+        nodes[0]
+            .children
+            .iter()
+            .map(|child| {
+                let child = &nodes[*child];
+                let mv = child.mv.expect("child without move");
+                (mv, child.visits, child.mean_reward())
+            })
+            .collect()
+    }
+
+    fn select_child(&self, nodes: &[MctsNode], parent: usize) -> Option<usize> {
+        let log_visits = (nodes[parent].visits.max(1) as f64).ln();
+        let uct = |child: usize| {
+            let child = &nodes[child];
+            let visits = child.visits.max(1) as f64;
+            child.mean_reward() + MCTS_EXPLORATION * (log_visits / visits).sqrt()
+        };
+        nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .max_by(|a, b| uct(*a).total_cmp(&uct(*b)))
+    }
+}
+
+/// The moves to the most visited child of each node, from the root on.
+fn most_visited_line(nodes: &[MctsNode]) -> Vec<BarId> {
+    let mut line = Vec::new();
+    let mut node = 0;
+    while let Some(child) = (nodes[node].children.iter())
+        .copied()
+        .max_by_key(|child| nodes[*child].visits)
+    {
+        line.push(nodes[child].mv.expect("child without move"));
+        node = child;
+    }
+    line
+}
+
+fn playout_reward(margin: i32, player: Player) -> f64 {
+    let margin = match player {
+        Player::Red => margin,
+        Player::Blue => -margin,
+    };
+    match margin.cmp(&0) {
+        std::cmp::Ordering::Greater => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Less => 0.0,
+    }
+}
+
+impl AI for AIMcts {
+    fn new(_board_state: &BoardState, _ai_player: Player) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(entropy_seed()),
+            options: AIOptions::default(),
+            last_stats: None,
+        }
+    }
+
+    fn set_options(&mut self, options: AIOptions) {
+        if let Some(seed) = options.seed {
+            self.rng = SmallRng::seed_from_u64(seed);
+        }
+        self.options = options;
+    }
+
+    fn next_move(&mut self, board_state: &BoardState) -> Option<BarId> {
+        if let Some(bar) = book_move(board_state, &mut self.rng) {
+            self.last_stats = Some(SearchStats::default());
+            return Some(bar);
+        }
+        self.search(board_state)
+            .into_iter()
+            .max_by_key(|(_, visits, _)| *visits)
+            .map(|(mv, _, _)| mv)
+    }
+
+    fn review_move(&mut self, board_state: &BoardState, bar: BarId) -> Option<BarId> {
+        let moves = self.search(board_state);
+        let (_, _, played) = *moves.iter().find(|(mv, _, _)| *mv == bar)?;
+        let (best_move, _, best) = moves.into_iter().max_by_key(|(_, visits, _)| *visits)?;
+        (best - played >= MCTS_REVIEW_MARGIN).then_some(best_move)
+    }
+
+    /// The playouts' win rate after the most visited move, which tracks the
+    /// results of games between the AIs closely enough to be used as is.
+    fn win_probability(&mut self, board_state: &BoardState, player: Player) -> f64 {
+        let best = (self.search(board_state).into_iter())
+            .max_by_key(|(_, visits, _)| *visits)
+            .map(|(_, _, win_rate)| win_rate);
+        match best {
+            Some(win_rate) if player == board_state.cur_turn => win_rate,
+            Some(win_rate) => 1.0 - win_rate,
+            None => static_evaluation(board_state).win_probability(board_state, player),
+        }
+    }
+
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        self.last_stats.as_ref()
+    }
+}
+
+/// One of the opening book's replies in `board`, if it is in the book.
+fn book_move<R: Rng>(board: &BoardState, rng: &mut R) -> Option<BarId> {
+    OpeningBook::get().replies(board).choose(rng).copied()
+}
+
+/// Red's expected margin in `board` without searching: exact once fewer
+/// than `ENDGAME_FREE_BARS` bars are free, else the chain evaluation with
+/// the default weights.
+pub(crate) fn static_evaluation(board: &BoardState) -> Evaluation {
+    if (board.free_bar_count()) < ENDGAME_FREE_BARS {
+        let to_move = if board.cur_turn == Player::Red { 1 } else { -1 };
+        let future = EndgameSolver::default().solve(&mut board.clone().into());
+        return Evaluation {
+            margin: score_balance(board) + to_move * future,
+            exact: true,
+        };
+    }
+    let weights = HeuristicWeights::default();
+    let margin = intern::chain_evaluation(board, &weights) as f64 / weights.box_value as f64;
+    Evaluation {
+        margin: margin.round() as i32,
+        exact: false,
+    }
+}
+
+/// Free bars of `board`.
+pub(crate) fn free_bars(board: &BoardState) -> Vec<BarId> {
+    (board.bars())
+        .filter(|(_, state)| *state == CellState::Free)
+        .map(|(bar, _)| bar)
+        .collect()
+}
+
+/// Whether drawing `bar` completes a box.
+fn completes_box(board: &BoardState, bar: BarId) -> bool {
+    board
+        .bar_neighbors(bar)
+        .any(|(col, row)| board.open_edges(col, row) == 1)
+}
+
+/// Whether drawing `bar` draws the third side of a box, which the
+/// opponent can then take.
+fn gives_box(board: &BoardState, bar: BarId) -> bool {
+    board
+        .bar_neighbors(bar)
+        .any(|(col, row)| board.open_edges(col, row) == 2)
+}
+
+/// Plays uniformly random moves. A baseline for measuring the other AIs.
+pub struct AIRandom {
+    rng: SmallRng,
+}
+
+impl AI for AIRandom {
+    fn new(_board_state: &BoardState, _ai_player: Player) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(entropy_seed()),
+        }
+    }
+
+    fn set_options(&mut self, options: AIOptions) {
+        if let Some(seed) = options.seed {
+            self.rng = SmallRng::seed_from_u64(seed);
+        }
+    }
+
+    fn next_move(&mut self, board_state: &BoardState) -> Option<BarId> {
+        free_bars(board_state).choose(&mut self.rng).copied()
+    }
+
+    fn review_move(&mut self, _board_state: &BoardState, _bar: BarId) -> Option<BarId> {
+        None
+    }
+
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        None
+    }
+}
+
+/// Takes a box whenever it can, otherwise avoids drawing the third side of
+/// a box, and only then gives boxes away. Looks no further than one move.
+pub struct AIGreedy {
+    rng: SmallRng,
+}
+
+impl AIGreedy {
+    /// The captures, or else the safe moves, or else all moves.
+    fn candidates(board_state: &BoardState) -> Vec<BarId> {
+        let bars = free_bars(board_state);
+        let captures = (bars.iter().copied())
+            .filter(|bar| completes_box(board_state, *bar))
+            .collect::<Vec<_>>();
+        if !captures.is_empty() {
+            return captures;
+        }
+        let safe = (bars.iter().copied())
+            .filter(|bar| !gives_box(board_state, *bar))
+            .collect::<Vec<_>>();
+        if !safe.is_empty() {
+            return safe;
+        }
+        bars
+    }
+}
+
+impl AI for AIGreedy {
+    fn new(_board_state: &BoardState, _ai_player: Player) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(entropy_seed()),
+        }
+    }
+
+    fn set_options(&mut self, options: AIOptions) {
+        if let Some(seed) = options.seed {
+            self.rng = SmallRng::seed_from_u64(seed);
+        }
+    }
+
+    fn next_move(&mut self, board_state: &BoardState) -> Option<BarId> {
+        Self::candidates(board_state).choose(&mut self.rng).copied()
+    }
+
+    /// Points out a missed capture, or a box given away while a safe move
+    /// was left.
+    fn review_move(&mut self, board_state: &BoardState, bar: BarId) -> Option<BarId> {
+        let candidates = Self::candidates(board_state);
+        if candidates.contains(&bar) {
+            return None;
+        }
+        candidates.choose(&mut self.rng).copied()
+    }
+
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        None
     }
 }
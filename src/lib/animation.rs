@@ -0,0 +1,115 @@
+use gloo::render::{request_animation_frame, AnimationFrame};
+use yew::Callback;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseOut,
+}
+
+impl Easing {
+    fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+/// Interpolates a value between `from` and `to` over `duration_ms`. The
+/// clock starts on the first animation frame after the tween is started.
+#[derive(Clone, Debug)]
+pub struct Tween {
+    from: f64,
+    to: f64,
+    duration_ms: f64,
+    easing: Easing,
+    start: Option<f64>,
+}
+
+impl Tween {
+    pub fn new(from: f64, to: f64, duration_ms: f64, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            duration_ms,
+            easing,
+            start: None,
+        }
+    }
+
+    fn progress(&self, now: f64) -> f64 {
+        match self.start {
+            Some(start) if self.duration_ms > 0.0 => ((now - start) / self.duration_ms).min(1.0),
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    fn value(&self, now: f64) -> f64 {
+        let t = self.easing.apply(self.progress(now));
+        self.from + (self.to - self.from) * t
+    }
+}
+
+/// Drives a set of named tweens with `requestAnimationFrame`. The owning
+/// component forwards the frame timestamps it receives through `on_frame`
+/// to `tick`, and reads the current values in its view. Frames are only
+/// requested while at least one tween is running.
+pub struct Animator {
+    tweens: Vec<(&'static str, Tween)>,
+    now: f64,
+    frame: Option<AnimationFrame>,
+    on_frame: Callback<f64>,
+}
+
+impl Animator {
+    pub fn new(on_frame: Callback<f64>) -> Self {
+        Self {
+            tweens: vec![],
+            now: 0.0,
+            frame: None,
+            on_frame,
+        }
+    }
+
+    pub fn start(&mut self, name: &'static str, tween: Tween) {
+        self.tweens.retain(|(other, _)| *other != name);
+        self.tweens.push((name, tween));
+        self.request_frame();
+    }
+
+    /// Advances all tweens to `timestamp`, dropping the ones that have
+    /// finished. Returns whether anything changed and needs re-rendering.
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.frame = None;
+        self.now = timestamp;
+        let changed = !self.tweens.is_empty();
+        for (_, tween) in self.tweens.iter_mut() {
+            tween.start.get_or_insert(timestamp);
+        }
+        self.tweens
+            .retain(|(_, tween)| tween.progress(timestamp) < 1.0);
+        if !self.tweens.is_empty() {
+            self.request_frame();
+        }
+        changed
+    }
+
+    /// Current value of the named tween, or `None` once it has finished.
+    pub fn value(&self, name: &str) -> Option<f64> {
+        self.tweens
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, tween)| tween.value(self.now))
+    }
+
+    fn request_frame(&mut self) {
+        if self.frame.is_none() {
+            let on_frame = self.on_frame.clone();
+            self.frame = Some(request_animation_frame(move |timestamp| {
+                on_frame.emit(timestamp)
+            }));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{env, process};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::ai::{self, AIOptions, AI};
+use crate::{box_balance, parse_size, seat, BoardState, Difficulty, Engine, Player};
+
+const USAGE: &str = "usage: arena ENGINE[:DIFFICULTY] ENGINE[:DIFFICULTY] [--games N] \
+                     [--sizes WxH,...] [--seed N]\n\
+                     e.g. arena minmax:Hard mcts:Medium --games 50 --sizes 3x3,4x4";
+
+/// Two-sided 95% quantile of the normal distribution, for the confidence
+/// intervals of the scores.
+const Z_95: f64 = 1.96;
+
+/// One side of the matches.
+#[derive(Clone, Copy)]
+struct Contender {
+    engine: Engine,
+    difficulty: Difficulty,
+}
+
+impl Contender {
+    /// Parses `<engine>[:<difficulty>]`, e.g. `minmax:Hard`. The difficulty
+    /// defaults to the hardest.
+    fn parse(value: &str) -> Option<Self> {
+        let (engine, difficulty) = match value.split_once(':') {
+            Some((engine, difficulty)) => (engine, difficulty.parse().ok()?),
+            None => (value, Difficulty::default()),
+        };
+        // Bots written in JavaScript only play in the browser.
+        Some(Self {
+            engine: engine
+                .parse()
+                .ok()
+                .filter(|engine| *engine != Engine::External)?,
+            difficulty,
+        })
+    }
+
+    /// An AI playing `player` in games starting from `board`.
+    fn new_ai(&self, board: &BoardState, player: Player, seed: u64) -> Box<dyn AI> {
+        let mut ai = ai::new_ai(self.engine, board, player);
+        let mut options = AIOptions::from(self.difficulty);
+        options.seed = Some(seed);
+        ai.set_options(options);
+        ai
+    }
+}
+
+impl std::fmt::Display for Contender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.engine, self.difficulty)
+    }
+}
+
+struct Settings {
+    contenders: [Contender; 2],
+    /// Games per board size, each contender playing first in half of them.
+    games: u32,
+    sizes: Vec<(u32, u32)>,
+    seed: u64,
+}
+
+impl Settings {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Option<Self> {
+        let first = Contender::parse(&args.next()?)?;
+        let second = Contender::parse(&args.next()?)?;
+        let mut settings = Self {
+            contenders: [first, second],
+            games: 20,
+            sizes: vec![(3, 3), (4, 4), (5, 5)],
+            seed: 0,
+        };
+        while let Some(flag) = args.next() {
+            let value = args.next()?;
+            match flag.as_str() {
+                "--games" => settings.games = value.parse().ok().filter(|games| *games > 0)?,
+                "--sizes" => {
+                    settings.sizes = value.split(',').map(parse_size).collect::<Option<_>>()?
+                }
+                "--seed" => settings.seed = value.parse().ok()?,
+                _ => return None,
+            }
+        }
+        Some(settings)
+    }
+}
+
+/// Games won, drawn and lost by the first contender.
+#[derive(Clone, Copy, Default)]
+struct Tally {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl Tally {
+    fn add(&mut self, margin: i32) {
+        match margin {
+            1.. => self.wins += 1,
+            0 => self.draws += 1,
+            _ => self.losses += 1,
+        }
+    }
+
+    fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of the points won, counting a draw as half a win, and the
+    /// half-width of its 95% confidence interval.
+    fn score(&self) -> (f64, f64) {
+        let games = self.games() as f64;
+        let score = (self.wins as f64 + self.draws as f64 / 2.0) / games;
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games;
+        (score, Z_95 * (variance / games).sqrt())
+    }
+
+    fn row(&self, label: &str) -> String {
+        let (score, margin) = self.score();
+        format!(
+            "{:<8}{:>7}{:>7}{:>7}{:>8}{:>9.1}% ± {:.1}%",
+            label,
+            self.games(),
+            self.wins,
+            self.draws,
+            self.losses,
+            100.0 * score,
+            100.0 * margin
+        )
+    }
+}
+
+/// Entry point of the `arena` binary: plays two AI configurations against
+/// each other on each board size, alternating who plays first, and prints
+/// the first one's wins, draws and losses with its score.
+pub fn run_arena() {
+    let settings = Settings::from_args(env::args().skip(1)).unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    let [first, second] = settings.contenders;
+    let mut rng = SmallRng::seed_from_u64(settings.seed);
+    println!("{} against {}", first, second);
+    println!(
+        "{:<8}{:>7}{:>7}{:>7}{:>8}{:>10}",
+        "size", "games", "wins", "draws", "losses", "score"
+    );
+    let mut total = Tally::default();
+    for (width, height) in settings.sizes {
+        let mut tally = Tally::default();
+        for game in 0..settings.games {
+            let board = BoardState::new(width, height);
+            let seed = rng.gen();
+            let margin = if game % 2 == 0 {
+                play_game(board, [first, second], seed)
+            } else {
+                -play_game(board, [second, first], seed)
+            };
+            tally.add(margin);
+            total.add(margin);
+        }
+        println!("{}", tally.row(&format!("{}x{}", width, height)));
+    }
+    println!("{}", total.row("total"));
+}
+
+/// Plays `board` out between Red and Blue played by `contenders` in that
+/// order, and returns Red's boxes minus Blue's.
+fn play_game(mut board: BoardState, contenders: [Contender; 2], seed: u64) -> i32 {
+    let mut ais = [Player::Red, Player::Blue]
+        .map(|player| contenders[seat(player)].new_ai(&board, player, seed ^ seat(player) as u64));
+    while let Some(bar) = ais[seat(board.cur_turn)].next_move(&board) {
+        board.do_move(bar);
+    }
+    box_balance(&board)
+}
@@ -0,0 +1,182 @@
+use gloo::events::EventListener;
+use js_sys::ArrayBuffer;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{console, AudioBuffer, AudioContext, AudioContextState, Response};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoundCategory {
+    Effects,
+    Interface,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoundId {
+    BarPlaced,
+    BoxCompleted,
+    ButtonClick,
+}
+
+impl SoundId {
+    const ALL: [SoundId; 3] = [
+        SoundId::BarPlaced,
+        SoundId::BoxCompleted,
+        SoundId::ButtonClick,
+    ];
+
+    fn url(&self) -> &'static str {
+        match self {
+            SoundId::BarPlaced => "assets/sounds/bar.wav",
+            SoundId::BoxCompleted => "assets/sounds/box.wav",
+            SoundId::ButtonClick => "assets/sounds/click.wav",
+        }
+    }
+
+    fn category(&self) -> SoundCategory {
+        match self {
+            SoundId::BarPlaced | SoundId::BoxCompleted => SoundCategory::Effects,
+            SoundId::ButtonClick => SoundCategory::Interface,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioSettings {
+    pub muted: bool,
+    pub effects_volume: f32,
+    pub interface_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            effects_volume: 0.8,
+            interface_volume: 0.5,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn volume(&self, category: SoundCategory) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        match category {
+            SoundCategory::Effects => self.effects_volume,
+            SoundCategory::Interface => self.interface_volume,
+        }
+    }
+
+    pub fn set_volume(&mut self, category: SoundCategory, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match category {
+            SoundCategory::Effects => self.effects_volume = volume,
+            SoundCategory::Interface => self.interface_volume = volume,
+        }
+    }
+}
+
+/// Owns the page's `AudioContext` and the decoded sounds. All sounds are
+/// fetched and decoded up front; sounds that have not finished loading yet
+/// are silently skipped.
+pub struct AudioManager {
+    context: Option<AudioContext>,
+    buffers: Rc<RefCell<Vec<(SoundId, AudioBuffer)>>>,
+    settings: AudioSettings,
+    _gesture_listener: Option<EventListener>,
+}
+
+impl AudioManager {
+    pub fn new() -> Self {
+        let context = AudioContext::new().ok();
+        let buffers = Rc::new(RefCell::new(vec![]));
+        if let Some(context) = &context {
+            for sound in SoundId::ALL {
+                let context = context.clone();
+                let buffers = buffers.clone();
+                spawn_local(async move {
+                    match load_buffer(&context, sound.url()).await {
+                        Ok(buffer) => buffers.borrow_mut().push((sound, buffer)),
+                        Err(err) => console::error_2(
+                            &format!("failed to load sound {:?}:", sound).into(),
+                            &err,
+                        ),
+                    }
+                });
+            }
+        }
+        // Browsers keep a new AudioContext suspended until the user has
+        // interacted with the page.
+        let gesture_listener = context.clone().and_then(|context| {
+            web_sys::window().map(|window| {
+                EventListener::once(&window, "pointerdown", move |_| resume(&context))
+            })
+        });
+        Self {
+            context,
+            buffers,
+            settings: AudioSettings::default(),
+            _gesture_listener: gesture_listener,
+        }
+    }
+
+    pub fn settings(&self) -> &AudioSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: AudioSettings) {
+        self.settings = settings;
+    }
+
+    pub fn play(&self, sound: SoundId) {
+        let volume = self.settings.volume(sound.category());
+        let context = match &self.context {
+            Some(context) if volume > 0.0 => context,
+            _ => return,
+        };
+        let buffer = self
+            .buffers
+            .borrow()
+            .iter()
+            .find(|(id, _)| *id == sound)
+            .map(|(_, buffer)| buffer.clone());
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        resume(context);
+        if let Err(err) = play_buffer(context, &buffer, volume) {
+            console::error_2(&format!("failed to play sound {:?}:", sound).into(), &err);
+        }
+    }
+}
+
+fn resume(context: &AudioContext) {
+    if context.state() == AudioContextState::Suspended {
+        let _ = context.resume();
+    }
+}
+
+fn play_buffer(context: &AudioContext, buffer: &AudioBuffer, volume: f32) -> Result<(), JsValue> {
+    let source = context.create_buffer_source()?;
+    source.set_buffer(Some(buffer));
+    let gain = context.create_gain()?;
+    gain.gain().set_value(volume);
+    source.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(&context.destination())?;
+    source.start()
+}
+
+async fn load_buffer(context: &AudioContext, url: &str) -> Result<AudioBuffer, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    let data: ArrayBuffer = JsFuture::from(response.array_buffer()?).await?.dyn_into()?;
+    JsFuture::from(context.decode_audio_data(&data)?)
+        .await?
+        .dyn_into()
+}
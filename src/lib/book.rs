@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::ai::free_bars;
+use crate::{BarDirection, BarId, BoardState};
+
+/// Opening positions of small boards, one per line: the FEN of the position
+/// followed by every move that keeps the best result with perfect play, as
+/// `H <col> <row>` or `V <col> <row>`, or `*` if every move does. Generated
+/// by solving the boards exactly, which takes far too long to do during a
+/// game.
+const BOOK: &str = include_str!("opening_book.txt");
+
+/// Known good replies to early positions, so the AI does not spend its
+/// search budget on moves that are already known.
+pub(crate) struct OpeningBook {
+    /// Replies by the board size and canonical hash of the position, in
+    /// the orientation of the canonical position. `None` if every move is as
+    /// good.
+    replies: HashMap<(u32, u32, u64), Option<Vec<BarId>>>,
+}
+
+impl OpeningBook {
+    /// The book, parsed the first time it is needed.
+    pub(crate) fn get() -> &'static Self {
+        static LOADED: OnceLock<OpeningBook> = OnceLock::new();
+        LOADED.get_or_init(|| Self::parse(BOOK))
+    }
+
+    /// Skips malformed lines rather than failing, the book only saves
+    /// search time.
+    fn parse(book: &str) -> Self {
+        let replies = book
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let board = BoardState::from_fen(parts.next()?).ok()?;
+                let parts = parts.collect::<Vec<_>>();
+                let bars = match parts[..] {
+                    ["*"] => None,
+                    _ => Some(parts.chunks(3).map(parse_bar).collect::<Option<Vec<_>>>()?),
+                };
+                let symmetry = board.canonical_symmetry();
+                let bars = bars.map(|bars| {
+                    (bars.into_iter())
+                        .filter(|bar| board.validate(*bar).is_ok())
+                        .map(|bar| board.transform_bar(symmetry, bar))
+                        .collect()
+                });
+                Some((Self::key(&board), bars))
+            })
+            .collect();
+        Self { replies }
+    }
+
+    /// Good replies in `board`, none if it is not in the book. Positions
+    /// symmetric to one in the book get the symmetric replies.
+    pub(crate) fn replies(&self, board: &BoardState) -> Vec<BarId> {
+        let to_board = board.canonical_symmetry().inverse();
+        match self.replies.get(&Self::key(board)) {
+            Some(Some(bars)) => (bars.iter())
+                .map(|bar| board.transform_bar(to_board, *bar))
+                // Guard against hash collisions with positions outside the
+                // book.
+                .filter(|bar| board.validate(*bar).is_ok())
+                .collect(),
+            Some(None) => free_bars(board),
+            None => vec![],
+        }
+    }
+
+    fn key(board: &BoardState) -> (u32, u32, u64) {
+        (board.width, board.height, board.canonical_hash())
+    }
+}
+
+fn parse_bar(parts: &[&str]) -> Option<BarId> {
+    let [direction, col, row] = parts else {
+        return None;
+    };
+    let direction = match *direction {
+        "H" => BarDirection::Horizontal,
+        "V" => BarDirection::Vertical,
+        _ => return None,
+    };
+    Some(BarId {
+        direction,
+        col: col.parse().ok()?,
+        row: row.parse().ok()?,
+    })
+}
@@ -0,0 +1,218 @@
+use crate::{BarId, BoardState, CellState, Player};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChainKind {
+    /// Path of boxes, open at both ends to the border or a junction box.
+    Chain,
+    /// Closed cycle of boxes; opening it gives away every box.
+    Loop,
+}
+
+/// Free boxes that fall one after another once a bar inside is drawn: every
+/// box has at most two free bars, and neighbors share one of them. Boxes are
+/// listed in path order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chain {
+    pub kind: ChainKind,
+    pub cells: Vec<ChainCell>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChainCell {
+    pub col: u32,
+    pub row: u32,
+    /// Free bars around the box, 1 if it can be taken right away.
+    pub open_edges: u32,
+}
+
+impl Chain {
+    /// Whether the player to move can start taking the chain's boxes.
+    pub fn is_capturable(&self) -> bool {
+        self.cells.iter().any(|cell| cell.open_edges == 1)
+    }
+
+    /// Whether opening the chain gives the opponent the choice to keep
+    /// control: a loop, or a chain of three boxes or more, not opened yet.
+    pub fn is_long(&self) -> bool {
+        !self.is_capturable() && (self.kind == ChainKind::Loop || self.cells.len() >= 3)
+    }
+}
+
+/// The fight for control under the long chain rule: once only long chains
+/// and loops are left, the player to move has to open one, and the player
+/// in control takes all but two boxes of each chain and four of each loop,
+/// then declines the rest with a double-cross to keep control.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChainParity {
+    pub long_chains: u32,
+    pub loops: u32,
+    /// Moves the player in control has to decline with a double-cross,
+    /// one per chain but the last and two per loop.
+    pub double_crosses: u32,
+    /// The player who will take the last long chain if neither player
+    /// changes the number of long chains, `None` if there are none.
+    pub controller: Option<Player>,
+}
+
+/// What a move does wrong in terms of chains, for explaining why another
+/// move was better, see `BoardState::chain_mistake`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChainMistake {
+    /// Draws a bar inside this long chain or loop, which lets the opponent
+    /// take its boxes.
+    OpensLongChain(Chain),
+    /// Leaves the opponent in control of the long chains, see
+    /// `ChainParity::controller`.
+    GivesUpControl,
+}
+
+impl BoardState {
+    /// Splits the free boxes with at most two free bars into chains and
+    /// loops. Boxes with three or four free bars are junctions between
+    /// chains and belong to none.
+    pub fn chains(&self) -> Vec<Chain> {
+        let cell_width = self.width - 1;
+        let in_chain = |col: u32, row: u32| {
+            self.cell_get(col, row) == CellState::Free && self.open_edges(col, row) <= 2
+        };
+        let mut visited = vec![false; self.cellstates.len()];
+        let mut res = vec![];
+        for (index, (col, row, _)) in self.cells().enumerate() {
+            let cell = (col, row);
+            if visited[index] || !in_chain(col, row) {
+                continue;
+            }
+            // Walk to one end of the chain first, or all the way around a
+            // loop, so the boxes can be collected in path order.
+            let mut start = cell;
+            let mut prev = None;
+            let mut kind = ChainKind::Chain;
+            loop {
+                let next = self
+                    .open_neighbors(start.0, start.1)
+                    .into_iter()
+                    .find(|next| Some(*next) != prev && in_chain(next.0, next.1));
+                match next {
+                    Some(next) if next == cell => {
+                        kind = ChainKind::Loop;
+                        break;
+                    }
+                    Some(next) => {
+                        prev = Some(start);
+                        start = next;
+                    }
+                    None => break,
+                }
+            }
+            let mut cells = vec![start];
+            visited[(start.1 * cell_width + start.0) as usize] = true;
+            let mut cur = start;
+            while let Some(next) = self.open_neighbors(cur.0, cur.1).into_iter().find(|next| {
+                !visited[(next.1 * cell_width + next.0) as usize] && in_chain(next.0, next.1)
+            }) {
+                visited[(next.1 * cell_width + next.0) as usize] = true;
+                cells.push(next);
+                cur = next;
+            }
+            let cells = (cells.into_iter())
+                .map(|(col, row)| ChainCell {
+                    col,
+                    row,
+                    open_edges: self.open_edges(col, row),
+                })
+                .collect();
+            res.push(Chain { kind, cells });
+        }
+        res
+    }
+
+    /// Who controls the long chains, counted on the current chains.
+    pub fn chain_parity(&self) -> ChainParity {
+        self.chain_parity_of(&self.chains())
+    }
+
+    /// The moves that do not complete a box alternate turns, the ones that
+    /// do complete the free boxes, minus one for each double-cross: the
+    /// opponent plays one on every declined chain and two on every declined
+    /// loop, and the last bar of a loop taken whole completes two boxes as
+    /// well. The player who makes the last move that ends a turn opens the
+    /// last chain, and the other one controls.
+    pub(crate) fn chain_parity_of(&self, chains: &[Chain]) -> ChainParity {
+        let long = chains.iter().filter(|chain| chain.is_long());
+        let loops = long
+            .clone()
+            .filter(|chain| chain.kind == ChainKind::Loop)
+            .count() as u32;
+        let long_chains = long.count() as u32 - loops;
+        if long_chains + loops == 0 {
+            return ChainParity {
+                long_chains,
+                loops,
+                double_crosses: 0,
+                controller: None,
+            };
+        }
+        let double_crosses = if long_chains > 0 {
+            long_chains - 1 + 2 * loops
+        } else {
+            2 * (loops - 1) + 1
+        };
+        let free_bars = self.free_bar_count() as i32;
+        let free_boxes = (self.cells())
+            .filter(|(_, _, state)| *state == CellState::Free)
+            .count() as i32;
+        let turn_ends = free_bars - free_boxes + double_crosses as i32;
+        let controller = if turn_ends % 2 == 0 {
+            self.cur_turn
+        } else {
+            self.cur_turn.other()
+        };
+        ChainParity {
+            long_chains,
+            loops,
+            double_crosses,
+            controller: Some(controller),
+        }
+    }
+
+    /// Whether playing `bar` opens a long chain or loop, or else hands the
+    /// control of the long chains to the opponent. `None` if it does
+    /// neither, completes a box or is not free.
+    pub fn chain_mistake(&self, bar: BarId) -> Option<ChainMistake> {
+        let player = self.cur_turn;
+        let mut after = self.clone();
+        if !after.do_move(bar) || after.cellstates != self.cellstates {
+            return None;
+        }
+        let opened = (self.chains().into_iter())
+            .filter(|chain| chain.is_long())
+            .find(|chain| {
+                (chain.cells.iter()).any(|cell| self.cell_bars(cell.col, cell.row).contains(&bar))
+            });
+        if let Some(chain) = opened {
+            return Some(ChainMistake::OpensLongChain(chain));
+        }
+        let had_control = self.chain_parity().controller == Some(player);
+        let has_control = after.chain_parity().controller == Some(player);
+        (had_control && !has_control).then_some(ChainMistake::GivesUpControl)
+    }
+
+    /// Number of free bars around a box.
+    pub fn open_edges(&self, col: u32, row: u32) -> u32 {
+        self.cell_bars(col, row)
+            .into_iter()
+            .filter(|bar| self.bar_get(*bar) == CellState::Free)
+            .count() as u32
+    }
+
+    /// Boxes next to the box at `col`, `row` behind one of its free bars.
+    fn open_neighbors(&self, col: u32, row: u32) -> Vec<(u32, u32)> {
+        self.cell_bars(col, row)
+            .into_iter()
+            .filter(|bar| self.bar_get(*bar) == CellState::Free)
+            .flat_map(|bar| self.bar_neighbors(bar))
+            .filter(|neighbor| *neighbor != (col, row))
+            .collect()
+    }
+}
+
@@ -0,0 +1,29 @@
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use yew::Callback;
+
+/// Copies `text` with the async Clipboard API. Where that API is missing or
+/// denied, the text is shown in a prompt for the user to copy by hand.
+/// `on_done` receives whether the text ended up on the clipboard.
+pub fn copy_text(text: String, on_done: Callback<bool>) {
+    spawn_local(async move {
+        let copied = write_text(&text).await.is_ok();
+        if !copied {
+            if let Some(window) = web_sys::window() {
+                let _ = window.prompt_with_message_and_default("Copy the position:", &text);
+            }
+        }
+        on_done.emit(copied);
+    });
+}
+
+async fn write_text(text: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let navigator = window.navigator();
+    if !Reflect::has(&navigator, &JsValue::from_str("clipboard"))? {
+        return Err(JsValue::from_str("clipboard API not supported"));
+    }
+    JsFuture::from(navigator.clipboard().write_text(text)).await?;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::{BarId, BoardState, CellState};
+
+/// Where a string of the strings-and-coins view is tied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringEnd {
+    /// The coin of the box at `col`, `row`.
+    Coin(u32, u32),
+    /// The ground around the board, for bars on its border.
+    Ground,
+}
+
+/// A string of the strings-and-coins view: a free bar, tying together the
+/// coins of the boxes on either side of it, or the coin of a box on the
+/// border to the ground. Drawing the bar cuts the string, and a coin that
+/// hangs by no string any more is taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoinString {
+    pub bar: BarId,
+    pub ends: [StringEnd; 2],
+}
+
+/// The strings-and-coins dual of a position, which many strategy texts
+/// reason in: every free box is a coin, and every free bar a string. Chains
+/// are the runs of coins with two strings each, loops the rings of them.
+impl BoardState {
+    /// The coins still on the table, the free boxes, row by row.
+    pub fn coins(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (self.cells())
+            .filter(|(_, _, state)| *state == CellState::Free)
+            .map(|(col, row, _)| (col, row))
+    }
+
+    /// The strings not cut yet, in the order of `bars`.
+    pub fn strings(&self) -> impl Iterator<Item = CoinString> + '_ {
+        (self.bars())
+            .filter(|(_, state)| *state == CellState::Free)
+            .map(|(bar, _)| {
+                let mut ends = [StringEnd::Ground; 2];
+                for (end, (col, row)) in ends.iter_mut().zip(self.bar_neighbors(bar)) {
+                    *end = StringEnd::Coin(col, row);
+                }
+                CoinString { bar, ends }
+            })
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+use web_sys::console;
+
+use crate::theme::Theme;
+use crate::{is_valid_size, CellMask, CellValues};
+
+/// AI engine the human plays against. Ignored in builds without the `ai`
+/// feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Engine {
+    #[default]
+    MinMax,
+    Mcts,
+    /// Plays random moves.
+    Random,
+    /// Takes boxes when it can and avoids giving them away, nothing more.
+    Greedy,
+    /// A bot written in JavaScript, plugged in with `registerBot`.
+    External,
+}
+
+impl Engine {
+    pub const ALL: [Engine; 5] = [
+        Engine::MinMax,
+        Engine::Mcts,
+        Engine::Random,
+        Engine::Greedy,
+        Engine::External,
+    ];
+}
+
+impl FromStr for Engine {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minmax" => Ok(Engine::MinMax),
+            "mcts" => Ok(Engine::Mcts),
+            "random" => Ok(Engine::Random),
+            "greedy" => Ok(Engine::Greedy),
+            "external" => Ok(Engine::External),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Engine::MinMax => write!(f, "minmax"),
+            Engine::Mcts => write!(f, "mcts"),
+            Engine::Random => write!(f, "random"),
+            Engine::Greedy => write!(f, "greedy"),
+            Engine::External => write!(f, "external"),
+        }
+    }
+}
+
+/// Deployment defaults, fixed at build time. Each field can be overridden
+/// with a `DAB_*` environment variable when compiling, or through a JSON
+/// preset named by `DAB_CONFIG` (see `build.rs`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub board_width: u32,
+    pub board_height: u32,
+    /// The boxes played, for boards that are not rectangles. Its size is
+    /// the board's.
+    pub board_mask: Option<CellMask>,
+    /// Points of each box, for boards where some boxes count more. Its size
+    /// is the board's.
+    pub board_values: Option<CellValues>,
+    pub theme: Theme,
+    pub gamepad: bool,
+    pub sound: bool,
+    pub share: bool,
+    pub engine: Engine,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            board_width: 4,
+            board_height: 4,
+            board_mask: None,
+            board_values: None,
+            theme: Theme::default(),
+            gamepad: true,
+            sound: true,
+            share: true,
+            engine: Engine::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_build_env() -> Self {
+        let default = Self::default();
+        let theme = default.theme;
+        // A mask gives the board's size as well.
+        let board_mask = parse::<CellMask>("DAB_BOARD_MASK", option_env!("DAB_BOARD_MASK"))
+            .filter(|mask| is_valid_size(mask.width()) && is_valid_size(mask.height()));
+        let board_width = (board_mask.as_ref().map(CellMask::width))
+            .or_else(|| parse("DAB_BOARD_WIDTH", option_env!("DAB_BOARD_WIDTH")))
+            .filter(|width| is_valid_size(*width))
+            .unwrap_or(default.board_width);
+        let board_height = (board_mask.as_ref().map(CellMask::height))
+            .or_else(|| parse("DAB_BOARD_HEIGHT", option_env!("DAB_BOARD_HEIGHT")))
+            .filter(|height| is_valid_size(*height))
+            .unwrap_or(default.board_height);
+        let board_values =
+            parse::<CellValues>("DAB_BOARD_VALUES", option_env!("DAB_BOARD_VALUES"))
+                .filter(|values| values.width() == board_width && values.height() == board_height);
+        Self {
+            board_width,
+            board_height,
+            board_mask,
+            board_values,
+            theme: Theme {
+                red: option_env!("DAB_THEME_RED").map_or(theme.red, Into::into),
+                blue: option_env!("DAB_THEME_BLUE").map_or(theme.blue, Into::into),
+                board: option_env!("DAB_THEME_BOARD").map_or(theme.board, Into::into),
+                board_size: option_env!("DAB_THEME_BOARD_SIZE")
+                    .map_or(theme.board_size, Into::into),
+                ..theme
+            },
+            gamepad: parse("DAB_GAMEPAD", option_env!("DAB_GAMEPAD")).unwrap_or(default.gamepad),
+            sound: parse("DAB_SOUND", option_env!("DAB_SOUND")).unwrap_or(default.sound),
+            share: parse("DAB_SHARE", option_env!("DAB_SHARE")).unwrap_or(default.share),
+            engine: parse("DAB_ENGINE", option_env!("DAB_ENGINE")).unwrap_or(default.engine),
+        }
+    }
+}
+
+pub(crate) fn parse<T: FromStr>(name: &str, value: Option<&str>) -> Option<T> {
+    let value = value?;
+    let parsed = value.parse().ok();
+    if parsed.is_none() {
+        console::error_1(&format!("ignoring invalid {}: {:?}", name, value).into());
+    }
+    parsed
+}
@@ -0,0 +1,172 @@
+use js_sys::{Object, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsValue;
+use web_sys::console;
+use yew::Callback;
+
+use crate::{BarDirection, BarId, BoardState, SearchStats};
+
+const GLOBAL_NAME: &str = "dab";
+
+/// Changes to the live game requested from the browser console.
+pub enum DevCommand {
+    Load(Box<BoardState>),
+    ForceMove(BarId),
+}
+
+/// Installs `window.dab` with a few functions for reproducing positions:
+///
+/// - `dab.dump()` returns the current position as a FEN string.
+/// - `dab.load(fen)` replaces the current position.
+/// - `dab.forceMove('H', col, row)` plays a bar for the side to move,
+///   without letting the AI respond.
+/// - `dab.eval()` returns the AI's quick evaluation of the position, Red's
+///   expected boxes minus Blue's at the end of the game, or only the boxes
+///   taken so far in builds without the AI.
+/// - `dab.stats()` returns the statistics of the AI's last search as JSON.
+///
+/// The owning component keeps the console up to date through `sync` and
+/// receives the commands that modify the game. With several boards on the
+/// page, `window.dab` controls the one created last. It is removed again
+/// when that board's console is dropped.
+pub struct DevConsole {
+    board: Rc<RefCell<Option<BoardState>>>,
+    stats: Rc<RefCell<Option<SearchStats>>>,
+    api: Object,
+    _dump: Closure<dyn Fn() -> JsValue>,
+    _load: Closure<dyn Fn(String) -> bool>,
+    _force_move: Closure<dyn Fn(String, u32, u32) -> bool>,
+    _eval: Closure<dyn Fn() -> JsValue>,
+    _stats: Closure<dyn Fn() -> JsValue>,
+}
+
+impl DevConsole {
+    pub fn new(on_command: Callback<DevCommand>) -> Self {
+        let board: Rc<RefCell<Option<BoardState>>> = Rc::new(RefCell::new(None));
+        let dump = {
+            let board = board.clone();
+            Closure::wrap(Box::new(move || match &*board.borrow() {
+                Some(board) => board.to_fen().into(),
+                None => JsValue::NULL,
+            }) as Box<dyn Fn() -> JsValue>)
+        };
+        let load = {
+            let on_command = on_command.clone();
+            Closure::wrap(
+                Box::new(move |fen: String| match BoardState::from_fen(&fen) {
+                    Ok(board) => {
+                        on_command.emit(DevCommand::Load(Box::new(board)));
+                        true
+                    }
+                    Err(err) => {
+                        console::error_1(&format!("invalid position {}: {}", fen, err).into());
+                        false
+                    }
+                }) as Box<dyn Fn(String) -> bool>,
+            )
+        };
+        let force_move = {
+            let board = board.clone();
+            Closure::wrap(Box::new(move |direction: String, col: u32, row: u32| {
+                let direction = match direction.as_str() {
+                    "H" | "h" => BarDirection::Horizontal,
+                    "V" | "v" => BarDirection::Vertical,
+                    _ => {
+                        console::error_1(&"direction must be 'H' or 'V'".into());
+                        return false;
+                    }
+                };
+                let bar = BarId {
+                    direction,
+                    col,
+                    row,
+                };
+                let playable = match board.borrow().as_ref() {
+                    Some(board) => board.validate(bar).map_err(|err| err.to_string()),
+                    None => Err("no game".to_string()),
+                };
+                match playable {
+                    Ok(()) => {
+                        on_command.emit(DevCommand::ForceMove(bar));
+                        true
+                    }
+                    Err(err) => {
+                        console::error_1(&format!("bar not playable: {:?}: {}", bar, err).into());
+                        false
+                    }
+                }
+            }) as Box<dyn Fn(String, u32, u32) -> bool>)
+        };
+        let eval = {
+            let board = board.clone();
+            Closure::wrap(Box::new(move || match &*board.borrow() {
+                Some(board) => evaluate(board).into(),
+                None => JsValue::NULL,
+            }) as Box<dyn Fn() -> JsValue>)
+        };
+        let stats: Rc<RefCell<Option<SearchStats>>> = Rc::new(RefCell::new(None));
+        let stats_fn = {
+            let stats = stats.clone();
+            Closure::wrap(Box::new(move || match &*stats.borrow() {
+                Some(stats) => stats.to_json().into(),
+                None => JsValue::NULL,
+            }) as Box<dyn Fn() -> JsValue>)
+        };
+        let api = Object::new();
+        let functions: [(&str, &JsValue); 5] = [
+            ("dump", dump.as_ref()),
+            ("load", load.as_ref()),
+            ("forceMove", force_move.as_ref()),
+            ("eval", eval.as_ref()),
+            ("stats", stats_fn.as_ref()),
+        ];
+        for (name, function) in functions {
+            let _ = Reflect::set(&api, &name.into(), function);
+        }
+        if let Some(window) = web_sys::window() {
+            let _ = Reflect::set(&window, &GLOBAL_NAME.into(), &api);
+        }
+        Self {
+            board,
+            stats,
+            api,
+            _dump: dump,
+            _load: load,
+            _force_move: force_move,
+            _eval: eval,
+            _stats: stats_fn,
+        }
+    }
+
+    pub fn sync(&self, board: &BoardState, stats: Option<&SearchStats>) {
+        *self.board.borrow_mut() = Some(board.clone());
+        *self.stats.borrow_mut() = stats.cloned();
+    }
+}
+
+impl Drop for DevConsole {
+    fn drop(&mut self) {
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return,
+        };
+        let installed = Reflect::get(&window, &GLOBAL_NAME.into());
+        if installed.is_ok_and(|installed| installed == *self.api) {
+            let _ = Reflect::delete_property(&window, &GLOBAL_NAME.into());
+        }
+    }
+}
+
+/// Evaluates without searching, `dab.eval()` runs on the page's thread.
+fn evaluate(board: &BoardState) -> i32 {
+    #[cfg(feature = "ai")]
+    {
+        crate::ai::static_evaluation(board).margin
+    }
+    #[cfg(not(feature = "ai"))]
+    {
+        crate::box_balance(board)
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt;
+
+use crate::{
+    is_valid_size, BarDirection, BarId, BoardState, CellMask, CellState, CellValues, Handicap,
+    Player, Ruleset,
+};
+
+/// The two high bits of the width and height bytes hold the rules and
+/// whether the board has a shape of its own, see `BoardState::to_bytes`. Every
+/// supported size fits in the other bits.
+const RULES_BITS: u8 = 0xc0;
+const HIGH_FLAG: u8 = 0x80;
+const LOW_FLAG: u8 = 0x40;
+
+/// Why packed bytes could not be decoded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecodeError {
+    /// The dimensions are not a supported size.
+    Size,
+    /// The bytes end in the middle of a move or position.
+    Truncated,
+    /// More bytes follow the position.
+    TrailingBytes,
+    /// A box is worth less than 1 or more than 9 points.
+    Values,
+    /// The move with this index is not a bar of the board.
+    InvalidMove(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            DecodeError::Size => write!(f, "invalid board size"),
+            DecodeError::Truncated => write!(f, "unexpected end of the data"),
+            DecodeError::TrailingBytes => write!(f, "unexpected bytes after the position"),
+            DecodeError::Values => write!(f, "invalid box values"),
+            DecodeError::InvalidMove(index) => write!(f, "move {}: not a bar", index + 1),
+        }
+    }
+}
+
+/// Packs moves on a board of `width` by `height` dots, for messages, links
+/// and storage where text is too bulky. Each bar is written as its index,
+/// the vertical bars first, then the horizontal ones, each row by row: in
+/// one byte below 128, otherwise in two with the high bit of the first set.
+/// Every bar of the supported sizes fits. The bars must lie on the board.
+pub fn encode_moves(width: u32, height: u32, moves: &[BarId]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(moves.len());
+    for bar in moves {
+        let index = bar_index(width, height, *bar);
+        if index < 0x80 {
+            bytes.push(index as u8);
+        } else {
+            bytes.extend([0x80 | (index >> 8) as u8, index as u8]);
+        }
+    }
+    bytes
+}
+
+/// Reads moves written by `encode_moves`. Fails on bars outside the
+/// board, but does not check that the moves can be played.
+pub fn decode_moves(width: u32, height: u32, bytes: &[u8]) -> Result<Vec<BarId>, DecodeError> {
+    if !is_valid_size(width) || !is_valid_size(height) {
+        return Err(DecodeError::Size);
+    }
+    let mut bytes = bytes.iter();
+    let mut moves = vec![];
+    while let Some(&first) = bytes.next() {
+        let index = if first < 0x80 {
+            first as u32
+        } else {
+            let second = *bytes.next().ok_or(DecodeError::Truncated)?;
+            ((first & 0x7f) as u32) << 8 | second as u32
+        };
+        let bar = bar_at(width, height, index).ok_or(DecodeError::InvalidMove(moves.len()))?;
+        moves.push(bar);
+    }
+    Ok(moves)
+}
+
+impl BoardState {
+    /// Packs the position in a few bytes: the width, with its high bit set
+    /// in misère play and the next one when turns always alternate, and
+    /// the height, with its high bit set on Swedish boards and the next one
+    /// on boards that are not plain rectangles of boxes worth a point. Those
+    /// go on with a bit for whether the board is a torus, a bit for whether
+    /// it has a mask, a bit for whether its boxes have values and a bit for
+    /// whether the game has a handicap. Then, if it has a mask, come a bit
+    /// per box for whether it is part of the board, if they have values,
+    /// four bits per box for its value, and with a handicap, a bit for
+    /// whether it favors Blue and 16 bits each for its boxes and bars, lowest
+    /// first. Then come a bit
+    /// per bar for whether it is drawn, a bit per drawn bar and per taken
+    /// box for whether Blue owns it, and a bit for whether Blue is to move. A
+    /// box is taken when its four bars are drawn, so only its owner is
+    /// written. Bars are in the order of `encode_moves`, boxes row by row.
+    /// Neutral bars, off the board or around a Swedish one, are left out.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bits = BitWriter::default();
+        let rectangular = self.is_rectangular();
+        let shaped = !rectangular
+            || self.rules.torus
+            || self.values.is_some()
+            || self.handicap.is_some();
+        if shaped {
+            bits.push(self.rules.torus);
+            bits.push(!rectangular);
+            bits.push(self.values.is_some());
+            bits.push(self.handicap.is_some());
+        }
+        if !rectangular {
+            for (col, row, _) in self.cells() {
+                bits.push(self.has_box(col, row));
+            }
+        }
+        if self.values.is_some() {
+            for (col, row, _) in self.cells() {
+                bits.push_number(self.cell_value(col, row), 4);
+            }
+        }
+        if let Some(handicap) = self.handicap {
+            bits.push(handicap.player == Player::Blue);
+            bits.push_number(handicap.boxes, 16);
+            bits.push_number(handicap.bars, 16);
+        }
+        let bars = (self.bars())
+            .map(|(_, state)| state)
+            .filter(|state| *state != CellState::Neutral)
+            .collect::<Vec<_>>();
+        for state in &bars {
+            bits.push(*state != CellState::Free);
+        }
+        let owners = (bars.into_iter())
+            .chain(self.cells().map(|(_, _, state)| state))
+            .filter(|state| matches!(state, CellState::Player(_)));
+        for owner in owners {
+            bits.push(owner == CellState::Player(Player::Blue));
+        }
+        bits.push(self.cur_turn == Player::Blue);
+        let flag = |set, flag| if set { flag } else { 0 };
+        let mut bytes = vec![
+            self.width as u8
+                | flag(self.rules.misere, HIGH_FLAG)
+                | flag(self.rules.alternate_turns, LOW_FLAG),
+            self.height as u8 | flag(self.rules.swedish, HIGH_FLAG) | flag(shaped, LOW_FLAG),
+        ];
+        bytes.extend(bits.bytes);
+        bytes
+    }
+
+    /// Reads a position written by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (&width, &height) = match bytes {
+            [width, height, ..] => (width, height),
+            _ => return Err(DecodeError::Truncated),
+        };
+        let shaped = height & LOW_FLAG != 0;
+        let mut bits = BitReader::new(&bytes[2..]);
+        let mut shape_bit = || {
+            if shaped {
+                bits.next().ok_or(DecodeError::Truncated)
+            } else {
+                Ok(false)
+            }
+        };
+        let (torus, masked) = (shape_bit()?, shape_bit()?);
+        let (weighted, handicapped) = (shape_bit()?, shape_bit()?);
+        let rules = Ruleset {
+            misere: width & HIGH_FLAG != 0,
+            swedish: height & HIGH_FLAG != 0,
+            alternate_turns: width & LOW_FLAG != 0,
+            torus,
+        };
+        let (width, height) = ((width & !RULES_BITS) as u32, (height & !RULES_BITS) as u32);
+        if !is_valid_size(width) || !is_valid_size(height) {
+            return Err(DecodeError::Size);
+        }
+        if !rules.is_playable(width, height) {
+            return Err(DecodeError::Size);
+        }
+        let mut board = BoardState::new(width, height);
+        if masked {
+            let mut mask = CellMask::full(width, height);
+            for (col, row, _) in board.cells() {
+                mask.set(col, row, bits.next().ok_or(DecodeError::Truncated)?);
+            }
+            board.set_mask(&mask);
+        }
+        if weighted {
+            let mut values = CellValues::uniform(width, height);
+            for (col, row, _) in board.cells() {
+                let value = bits.next_number(4).ok_or(DecodeError::Truncated)?;
+                if !(1..=9).contains(&value) {
+                    return Err(DecodeError::Values);
+                }
+                values.set(col, row, value);
+            }
+            board.set_values(&values);
+        }
+        if handicapped {
+            let mut number = |len| bits.next_number(len).ok_or(DecodeError::Truncated);
+            let player = if number(1)? == 1 { Player::Blue } else { Player::Red };
+            // Its bars are read with the others.
+            board.handicap = Some(Handicap {
+                player,
+                boxes: number(16)?,
+                bars: number(16)?,
+            });
+            board.hashes = board.full_hashes();
+        }
+        board.set_rules(rules);
+        if board
+            .cells()
+            .any(|(col, row, _)| board.cell_is_full(col, row))
+        {
+            return Err(DecodeError::Size);
+        }
+        let bars = (board.bars())
+            .filter(|(_, state)| *state != CellState::Neutral)
+            .map(|(bar, _)| bar)
+            .collect::<Vec<_>>();
+        let mut drawn = vec![];
+        for bar in bars {
+            if bits.next().ok_or(DecodeError::Truncated)? {
+                drawn.push(bar);
+            }
+        }
+        let owner = |bits: &mut BitReader| match bits.next() {
+            Some(false) => Ok(CellState::Player(Player::Red)),
+            Some(true) => Ok(CellState::Player(Player::Blue)),
+            None => Err(DecodeError::Truncated),
+        };
+        for bar in drawn {
+            board.bar_set(bar, owner(&mut bits)?);
+        }
+        let full = (board.cells())
+            .filter(|(col, row, _)| board.cell_is_full(*col, *row))
+            .map(|(col, row, _)| (col, row))
+            .collect::<Vec<_>>();
+        for (col, row) in full {
+            board.cell_set(col, row, owner(&mut bits)?);
+        }
+        if owner(&mut bits)? == CellState::Player(Player::Blue) {
+            board.set_turn(Player::Blue);
+        }
+        if !bits.is_at_end() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        Ok(board)
+    }
+}
+
+fn bar_index(width: u32, height: u32, bar: BarId) -> u32 {
+    match bar.direction {
+        BarDirection::Vertical => bar.row * width + bar.col,
+        BarDirection::Horizontal => width * (height - 1) + bar.row * (width - 1) + bar.col,
+    }
+}
+
+fn bar_at(width: u32, height: u32, index: u32) -> Option<BarId> {
+    let vertical = width * (height - 1);
+    let (direction, index, row_length) = if index < vertical {
+        (BarDirection::Vertical, index, width)
+    } else {
+        (BarDirection::Horizontal, index - vertical, width - 1)
+    };
+    (index < row_length * height).then_some(BarId {
+        direction,
+        col: index % row_length,
+        row: index / row_length,
+    })
+}
+
+/// Bits packed into bytes, lowest bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().expect("a byte was pushed") |= 1 << (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    /// The lowest `len` bits of `value`, lowest first.
+    fn push_number(&mut self, value: u32, len: u32) {
+        for bit in 0..len {
+            self.push(value & 1 << bit != 0);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn next(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.pos / 8)?;
+        let bit = byte & 1 << (self.pos % 8) != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+
+    /// A number of `len` bits written by `BitWriter::push_number`.
+    fn next_number(&mut self, len: u32) -> Option<u32> {
+        let mut value = 0;
+        for bit in 0..len {
+            value |= (self.next()? as u32) << bit;
+        }
+        Some(value)
+    }
+
+    /// Whether only the padding of the last byte is left, which must be
+    /// zero.
+    fn is_at_end(&self) -> bool {
+        self.bytes.len() == self.pos.div_ceil(8)
+            && (self.pos.is_multiple_of(8) || self.bytes[self.pos / 8] >> (self.pos % 8) == 0)
+    }
+}
@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
+use wasm_bindgen::JsValue;
+use web_sys::{Document, Element};
+use yew::prelude::*;
+
+thread_local! {
+    static RECOVERY_TARGETS: RefCell<Vec<Element>> = const { RefCell::new(vec![]) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+#[derive(Properties, PartialEq)]
+pub struct ErrorBoundaryProps {
+    pub children: Children,
+}
+
+/// Replaces its children with a recovery panel when the app panics.
+///
+/// Panics abort on wasm, so nothing can be rendered through Yew once one
+/// happened: the scheduler may be left mid-update. Instead the panic hook
+/// logs the panic with `console_error_panic_hook` and then builds the panel
+/// with plain DOM calls inside the boundary's element. Its button reloads
+/// the page without calling back into wasm. A panic leaves the whole module
+/// unusable, so every boundary on the page shows the panel.
+pub struct ErrorBoundaryComp {
+    node: NodeRef,
+}
+
+impl Component for ErrorBoundaryComp {
+    type Message = ();
+    type Properties = ErrorBoundaryProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        INSTALL_HOOK.call_once(|| panic::set_hook(Box::new(on_panic)));
+        Self {
+            node: NodeRef::default(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="error-boundary" ref={self.node.clone()}>
+                { for ctx.props().children.iter() }
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
+        if let Some(element) = self.node.cast::<Element>().filter(|_| first_render) {
+            RECOVERY_TARGETS.with(|targets| targets.borrow_mut().push(element));
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(element) = self.node.cast::<Element>() {
+            RECOVERY_TARGETS
+                .with(|targets| targets.borrow_mut().retain(|target| *target != element));
+        }
+    }
+}
+
+fn on_panic(info: &PanicHookInfo) {
+    console_error_panic_hook::hook(info);
+    let targets = RECOVERY_TARGETS.with(|targets| targets.borrow().clone());
+    for target in targets {
+        let _ = show_recovery(&target, &info.to_string());
+    }
+}
+
+fn show_recovery(target: &Element, message: &str) -> Result<(), JsValue> {
+    let document = target
+        .owner_document()
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let panel = create_element(&document, "div", "error-recovery", None)?;
+    let title = create_element(&document, "h2", "", Some("Something went wrong"))?;
+    let details = create_element(&document, "pre", "", Some(message))?;
+    let button = create_element(&document, "button", "", Some("Start a new game"))?;
+    button.set_attribute("onclick", "location.reload()")?;
+    panel.append_child(&title)?;
+    panel.append_child(&details)?;
+    panel.append_child(&button)?;
+    target.set_inner_html("");
+    target.append_child(&panel)?;
+    Ok(())
+}
+
+fn create_element(
+    document: &Document,
+    tag: &str,
+    class: &str,
+    text: Option<&str>,
+) -> Result<Element, JsValue> {
+    let element = document.create_element(tag)?;
+    if !class.is_empty() {
+        element.set_class_name(class);
+    }
+    element.set_text_content(text);
+    Ok(element)
+}
@@ -0,0 +1,51 @@
+use crate::{BarId, Difficulty, GameMode, Player};
+
+/// High level events reported to an [`EventSink`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    /// A game started, with the sides the AI plays and how strongly. The
+    /// difficulties are Red's then Blue's, whether or not the AI plays them.
+    GameStarted {
+        width: u32,
+        height: u32,
+        starting_player: Player,
+        mode: GameMode,
+        difficulties: [Difficulty; 2],
+    },
+    GameFinished {
+        red_boxes: u32,
+        blue_boxes: u32,
+    },
+    Error(String),
+}
+
+/// Receiver for [`GameEvent`]s, for deployments that want to plug in their
+/// own telemetry. Every method defaults to doing nothing.
+pub trait EventSink {
+    fn emit(&self, _event: &GameEvent) {}
+}
+
+/// Told about every move of a game it is added to with
+/// `GameTrait::add_observer`, whether a human or the AI played it, so sounds,
+/// animations and integrations can react without polling the game. Every
+/// method defaults to doing nothing.
+///
+/// A move is reported with `on_move_played` first, then the boxes it
+/// completed, then either the player moving next or the end of the game.
+pub trait GameObserver {
+    fn on_move_played(&self, _player: Player, _bar: BarId) {}
+
+    /// `player` took the box at `col`, `row`.
+    fn on_box_completed(&self, _player: Player, _col: u32, _row: u32) {}
+
+    /// `player` moves next, after the other player's move took no box.
+    fn on_turn_changed(&self, _player: Player) {}
+
+    /// The last box was taken. `winner` is `None` for a draw.
+    fn on_game_over(&self, _winner: Option<Player>) {}
+}
+
+/// Sink that drops every event.
+pub struct NoopEventSink;
+
+impl EventSink for NoopEventSink {}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+
+use js_sys::{Function, Promise};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::console;
+use yew::Callback;
+
+use crate::ai::{AIOptions, AI};
+use crate::worker::parse_bar;
+use crate::{BarId, BoardState, Player, SearchStats};
+
+thread_local! {
+    /// The bot registered with `registerBot`.
+    static BOT: RefCell<Option<Function>> = const { RefCell::new(None) };
+}
+
+/// Plugs a bot written in JavaScript into the app, as the `external`
+/// engine. `callback` is called with the position as a FEN string whenever
+/// it is the bot's turn, and returns its move, or a promise of it, written
+/// like `"H 2 3"` for the horizontal bar in column 2 and row 3. Pass `null`
+/// to unregister the bot.
+#[wasm_bindgen(js_name = registerBot)]
+pub fn register_bot(callback: Option<Function>) {
+    BOT.with(|bot| *bot.borrow_mut() = callback);
+}
+
+/// Asks the registered bot for its move in `board`, and passes it to
+/// `on_move` once the bot answers, `None` if it has no valid answer.
+pub(crate) fn request_move(board: &BoardState, on_move: Callback<Option<BarId>>) {
+    let Some(reply) = call_bot(board) else {
+        on_move.emit(None);
+        return;
+    };
+    spawn_local(async move {
+        let bar = match JsFuture::from(Promise::resolve(&reply)).await {
+            Ok(reply) => parse_move(&reply),
+            Err(err) => {
+                console::error_2(&"external bot failed:".into(), &err);
+                None
+            }
+        };
+        on_move.emit(bar);
+    });
+}
+
+/// Calls the registered bot with `board`, and returns its raw reply.
+fn call_bot(board: &BoardState) -> Option<JsValue> {
+    let bot = BOT.with(|bot| bot.borrow().clone());
+    let Some(bot) = bot else {
+        console::error_1(&"no external bot registered, see registerBot".into());
+        return None;
+    };
+    match bot.call1(&JsValue::NULL, &board.to_fen().into()) {
+        Ok(reply) => Some(reply),
+        Err(err) => {
+            console::error_2(&"external bot failed:".into(), &err);
+            None
+        }
+    }
+}
+
+fn parse_move(reply: &JsValue) -> Option<BarId> {
+    let bar = reply.as_string().and_then(|reply| {
+        let mut parts = reply.split_whitespace();
+        let bar = parse_bar(&mut parts)??;
+        parts.next().is_none().then_some(bar)
+    });
+    if bar.is_none() {
+        console::error_2(&"invalid move from external bot:".into(), reply);
+    }
+    bar
+}
+
+/// The bot registered with `registerBot`, for games played on the main
+/// thread. Only bots answering right away can play there: a promise cannot
+/// be waited for, so the web app asks for its moves with `request_move`.
+pub struct AIExternal {}
+
+impl AI for AIExternal {
+    fn new(_board_state: &BoardState, _ai_player: Player) -> Self {
+        Self {}
+    }
+
+    /// The bot has its own settings, if any.
+    fn set_options(&mut self, _options: AIOptions) {}
+
+    fn next_move(&mut self, board_state: &BoardState) -> Option<BarId> {
+        let reply = call_bot(board_state)?;
+        if reply.is_instance_of::<Promise>() {
+            console::error_1(
+                &"external bot answered with a promise, which cannot be awaited here".into(),
+            );
+            return None;
+        }
+        parse_move(&reply)
+    }
+
+    fn review_move(&mut self, _board_state: &BoardState, _bar: BarId) -> Option<BarId> {
+        None
+    }
+
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        None
+    }
+}
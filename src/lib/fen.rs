@@ -0,0 +1,270 @@
+use std::fmt;
+
+use crate::{is_valid_size, BoardState, CellState, CellValues, Handicap, Player, Ruleset};
+
+/// Why `BoardState::from_fen` rejected a string.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FenError {
+    /// The dimensions are missing, malformed or not a supported size.
+    Size,
+    /// A list of bars or boxes is missing, malformed or does not match the
+    /// dimensions. Names the list.
+    States(&'static str),
+    /// The side to move is missing or neither `r` nor `b`.
+    Turn,
+    /// The rules are not a set of known letters.
+    Rules,
+    /// The box values are not a digit from 1 to 9 per box.
+    Values,
+    /// The handicap is not a player, a head start, `+` and a number of
+    /// bars.
+    Handicap,
+    /// More fields follow the rules, the values and the handicap.
+    TrailingFields,
+    /// The box at this column and row is owned without all four of its
+    /// bars, or free with them.
+    Inconsistent(u32, u32),
+    /// The neutral bars are not the ones off the board's boxes and, on a
+    /// Swedish board, around them.
+    Neutral,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            FenError::Size => write!(f, "invalid board size"),
+            FenError::States(name) => write!(f, "invalid {}", name),
+            FenError::Turn => write!(f, "invalid side to move"),
+            FenError::Rules => write!(f, "invalid rules"),
+            FenError::Values => write!(f, "invalid box values"),
+            FenError::Handicap => write!(f, "invalid handicap"),
+            FenError::TrailingFields => write!(f, "unexpected fields after the handicap"),
+            FenError::Inconsistent(col, row) => {
+                write!(f, "box ({},{}) does not match its bars", col, row)
+            }
+            FenError::Neutral => write!(f, "neutral bars that do not fit the board's boxes"),
+        }
+    }
+}
+
+impl BoardState {
+    /// Compact position string in the spirit of chess FEN: dimensions,
+    /// vertical bars, horizontal bars, box owners and side to move, separated
+    /// by `/`. Bars and boxes are listed row by row as `r` or `b`, or `n`
+    /// for neutral bars and boxes left out of the board, with runs of free
+    /// entries written as their count, e.g. `3x3/r5/b5/4/r`. Games not
+    /// played by the standard rules add a field of letters for them: `m`
+    /// for misère play, `s` for a Swedish board, `a` for turns that always
+    /// alternate, `t` for a torus. Boards whose boxes are not worth a point
+    /// each end in a field of their values, a digit per box, row by row, and
+    /// games with a handicap in a field of the player it favors, their head
+    /// start, `+` and the number of bars drawn for them, e.g. `b2+3`.
+    pub fn to_fen(&self) -> String {
+        let mut fen = format!(
+            "{}x{}/{}/{}/{}/{}",
+            self.width,
+            self.height,
+            states_to_fen(self.vstates.states()),
+            states_to_fen(self.hstates.states()),
+            states_to_fen(self.cellstates.iter().copied()),
+            player_to_fen(self.cur_turn),
+        );
+        if self.rules != Ruleset::default() {
+            fen.push('/');
+            fen.push_str(&rules_to_fen(self.rules));
+        }
+        if let Some(values) = &self.values {
+            fen.push('/');
+            fen.push_str(&values.to_string().replace('/', ""));
+        }
+        if let Some(handicap) = self.handicap {
+            let player = player_to_fen(handicap.player);
+            fen.push_str(&format!("/{}{}+{}", player, handicap.boxes, handicap.bars));
+        }
+        fen
+    }
+
+    /// Parses a string written by `to_fen`. Fails if it is malformed, does
+    /// not match the dimensions it declares, owns boxes without all their
+    /// bars, or has neutral bars where its boxes and rules do not.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut parts = fen.split('/');
+        let (width, height) = (parts.next())
+            .and_then(|size| size.split_once('x'))
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .filter(|(width, height)| is_valid_size(*width) && is_valid_size(*height))
+            .ok_or(FenError::Size)?;
+        let mut board = BoardState::new(width, height);
+        let mut states = |name, len| {
+            (parts.next())
+                .and_then(|fen| states_from_fen(fen, len))
+                .ok_or(FenError::States(name))
+        };
+        let vstates = states("vertical bars", board.vstates.length as usize)?;
+        board.vstates.load(&vstates);
+        let hstates = states("horizontal bars", board.hstates.length as usize)?;
+        board.hstates.load(&hstates);
+        board.cellstates = states("boxes", board.cellstates.len())?;
+        let mut turn = parts.next().unwrap_or_default().chars();
+        board.cur_turn = (turn.next())
+            .and_then(player_from_fen)
+            .filter(|_| turn.next().is_none())
+            .ok_or(FenError::Turn)?;
+        // The rules are letters, the values digits, and the handicap starts
+        // with a player.
+        let mut field = parts.next();
+        let is_handicap = |field: &&str| field.starts_with(['r', 'b']);
+        let is_values = |field: &&str| field.starts_with(|c: char| c.is_ascii_digit());
+        if let Some(rules) = field.filter(|field| !is_handicap(field) && !is_values(field)) {
+            board.rules = rules_from_fen(rules).ok_or(FenError::Rules)?;
+            field = parts.next();
+        }
+        if let Some(values) = field.filter(is_values) {
+            let values = values_from_fen(values, width).ok_or(FenError::Values)?;
+            if values.height() != height {
+                return Err(FenError::Values);
+            }
+            board.values = (!values.is_uniform()).then_some(values);
+            field = parts.next();
+        }
+        if let Some(handicap) = field.filter(is_handicap) {
+            board.handicap = Some(handicap_from_fen(handicap).ok_or(FenError::Handicap)?);
+            field = parts.next();
+        }
+        if field.is_some() {
+            return Err(FenError::TrailingFields);
+        }
+        if !board.neutral_is_consistent() {
+            return Err(FenError::Neutral);
+        }
+        for (col, row, owner) in board.cells() {
+            if board.cell_is_full(col, row) != matches!(owner, CellState::Player(_)) {
+                return Err(FenError::Inconsistent(col, row));
+            }
+        }
+        board.hashes = board.full_hashes();
+        Ok(board)
+    }
+}
+
+fn player_to_fen(player: Player) -> char {
+    match player {
+        Player::Red => 'r',
+        Player::Blue => 'b',
+    }
+}
+
+fn player_from_fen(c: char) -> Option<Player> {
+    match c {
+        'r' => Some(Player::Red),
+        'b' => Some(Player::Blue),
+        _ => None,
+    }
+}
+
+fn state_from_fen(c: char) -> Option<CellState> {
+    match c {
+        'n' => Some(CellState::Neutral),
+        _ => player_from_fen(c).map(CellState::from),
+    }
+}
+
+fn rules_to_fen(rules: Ruleset) -> String {
+    let mut res = String::new();
+    if rules.misere {
+        res.push('m');
+    }
+    if rules.swedish {
+        res.push('s');
+    }
+    if rules.alternate_turns {
+        res.push('a');
+    }
+    if rules.torus {
+        res.push('t');
+    }
+    res
+}
+
+/// Each letter at most once, and at least one.
+fn rules_from_fen(fen: &str) -> Option<Ruleset> {
+    let mut rules = Ruleset::default();
+    for c in fen.chars() {
+        match c {
+            'm' if !rules.misere => rules.misere = true,
+            's' if !rules.swedish => rules.swedish = true,
+            'a' if !rules.alternate_turns => rules.alternate_turns = true,
+            't' if !rules.torus => rules.torus = true,
+            _ => return None,
+        }
+    }
+    (rules != Ruleset::default()).then_some(rules)
+}
+
+fn handicap_from_fen(fen: &str) -> Option<Handicap> {
+    let mut chars = fen.chars();
+    let player = player_from_fen(chars.next()?)?;
+    let (boxes, bars) = chars.as_str().split_once('+')?;
+    Some(Handicap {
+        player,
+        boxes: boxes.parse().ok()?,
+        bars: bars.parse().ok()?,
+    })
+}
+
+/// A digit per box, in rows of `width - 1`.
+fn values_from_fen(fen: &str, width: u32) -> Option<CellValues> {
+    let cell_width = (width - 1) as usize;
+    if fen.is_empty() || !fen.len().is_multiple_of(cell_width) {
+        return None;
+    }
+    let rows = fen.as_bytes().chunks(cell_width).map(std::str::from_utf8);
+    let rows = rows.collect::<Result<Vec<_>, _>>().ok()?;
+    rows.join("/").parse().ok()
+}
+
+fn states_to_fen(states: impl Iterator<Item = CellState>) -> String {
+    let mut res = String::new();
+    let mut free_run = 0;
+    for state in states {
+        let c = match state {
+            CellState::Free => {
+                free_run += 1;
+                continue;
+            }
+            CellState::Player(player) => player_to_fen(player),
+            CellState::Neutral => 'n',
+        };
+        if free_run > 0 {
+            res.push_str(&free_run.to_string());
+            free_run = 0;
+        }
+        res.push(c);
+    }
+    if free_run > 0 {
+        res.push_str(&free_run.to_string());
+    }
+    res
+}
+
+fn states_from_fen(fen: &str, len: usize) -> Option<Vec<CellState>> {
+    let mut res = Vec::with_capacity(len);
+    let mut free_run: usize = 0;
+    for c in fen.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            free_run = free_run.checked_mul(10)?.checked_add(digit as usize)?;
+            continue;
+        }
+        if res.len() + free_run >= len {
+            return None;
+        }
+        res.resize(res.len() + free_run, CellState::Free);
+        free_run = 0;
+        res.push(state_from_fen(c)?);
+    }
+    if res.len() + free_run != len {
+        return None;
+    }
+    res.resize(len, CellState::Free);
+    Some(res)
+}
@@ -1,8 +1,31 @@
-use web_sys::console;
+// `html!` expands component props into bare expressions.
+#![allow(clippy::unnecessary_operation)]
+
+use gloo::events::EventListener;
+use gloo::timers::callback::Timeout;
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+use web_sys::{console, Element, HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew::Properties;
 
-use crate::lib::{ai::AIMinMax, *};
+use crate::animation::{Animator, Easing, Tween};
+use crate::audio::{AudioManager, AudioSettings, SoundCategory, SoundId};
+use crate::clipboard;
+use crate::config::Config;
+#[cfg(feature = "debug")]
+use crate::dev_console::{DevCommand, DevConsole};
+use crate::error_boundary::ErrorBoundaryComp;
+use crate::events::{EventSink, GameEvent, NoopEventSink};
+#[cfg(feature = "debug")]
+use crate::hot_reload;
+use crate::input::{self, GamepadPoller, InputAction};
+use crate::share::{self, ShareOutcome};
+use crate::theme::Theme;
+use crate::wake_lock::WakeLock;
+use crate::worker::{AIWorker, WorkerEvent};
+use crate::*;
 
 pub enum BoardMsg {
     ClickBar {
@@ -10,18 +33,185 @@ pub enum BoardMsg {
         col: u32,
         row: u32,
     },
-    StartGame(Player),
+    NewGame(GameConfig),
+    CopyPosition,
+    SharePosition,
+    Input(InputAction),
+    PressStart(BoardPress),
+    PressCancel,
+    OpenMenu(BoardPress),
+    CloseMenu,
+    MenuAction(ContextAction),
+    ViewportChanged,
+    TakeBack,
+    DismissReview,
+    Worker(WorkerEvent),
+    AIStep,
+    #[cfg(feature = "debug")]
+    Dev(DevCommand),
+}
+
+const LONG_PRESS_MS: u32 = 500;
+/// Pause before each move when the AI plays both sides, so the game can be
+/// followed.
+const AI_DEMO_MOVE_MS: u32 = 600;
+const BAR_DRAW_MS: f64 = 150.0;
+const CELL_FILL_MS: f64 = 250.0;
+
+/// Boards with at least this many dots along a side scroll inside a fixed
+/// viewport and only create elements for the part that is visible.
+const VIRTUALIZE_MIN_SIZE: u32 = 30;
+const VIRTUAL_UNIT_PX: u32 = 8;
+const VIRTUAL_MARGIN: u32 = 2;
+
+/// Size of a box in the strings-and-coins view, in SVG units.
+const COIN_STEP: f64 = 100.0;
+
+/// Moves of the AI's principal variation shown below the board.
+const PLAN_SHOWN_MOVES: usize = 6;
+
+fn apply_ai_settings<G: GameTrait>(game: &mut G, props: &BoardProps) {
+    game.set_engine(props.engine);
+    game.set_teams(props.teams);
+    game.set_mode(props.mode);
+    game.set_difficulty(Player::Blue, props.difficulty);
+    game.set_difficulty(Player::Red, props.red_difficulty);
+}
+
+/// The score line above a board: both scores, Red's first, and who is to
+/// move or the result. In a team game, `participant` is the one to move,
+/// and the scores are the teams'.
+fn gen_score(
+    scores: [u32; 2],
+    winner: Option<Option<Player>>,
+    current_turn: Player,
+    participant: Option<Participant>,
+) -> Html {
+    let (team, to_move) = match participant {
+        Some(participant) => (" team", participant.to_string()),
+        None => ("", current_turn.to_string()),
+    };
+    let turn = match winner {
+        None => format!(" · {} to move", to_move),
+        Some(Some(winner)) => format!(" · {}{} wins", winner, team),
+        Some(None) => " · Draw".to_string(),
+    };
+    html! {
+        <p class="score">
+            <span class="red">{ format!("Red{} {}", team, scores[0]) }</span>
+            { " – " }
+            <span class="blue">{ format!("{} Blue{}", scores[1], team) }</span>
+            { turn }
+        </p>
+    }
+}
+
+fn supported_size(size: u32) -> u32 {
+    if !is_valid_size(size) {
+        console::error_1(&format!("unsupported board size {}, clamping", size).into());
+    }
+    size.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE)
+}
+
+/// What the context menu is opened on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuTarget {
+    Bar(BarId),
+    /// A box, by column and row.
+    Cell(u32, u32),
+}
+
+/// A press on a bar or a box, at client coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardPress {
+    target: MenuTarget,
+    x: i32,
+    y: i32,
+}
+
+/// Per-bar overlay flags, rendered as extra classes on the bar.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BarHighlight {
+    cursor: bool,
+    marked: bool,
+    hint: bool,
+    /// Best move the AI has found so far while it is thinking.
+    preview: bool,
+}
+
+/// Teaching mode feedback on the last human move: the better move that was
+/// available instead, and what the move did wrong with the chains, if
+/// that explains it.
+struct MoveReview {
+    better: BarId,
+    mistake: Option<ChainMistake>,
+}
+
+/// Callbacks shared by every `BarComp` and `CellComp`, created once so
+/// that they compare equal between renders. Boxes cannot be clicked.
+#[derive(Clone, PartialEq)]
+struct BoardCallbacks {
+    onclick: Callback<BarId>,
+    onpressstart: Callback<BoardPress>,
+    onpresscancel: Callback<()>,
+    oncontextmenu: Callback<BoardPress>,
 }
 
 #[derive(PartialEq, Properties)]
 struct BoardProps {
     width: u32,
     height: u32,
+    mask: Option<CellMask>,
+    /// Points of each box, when they are not all worth one.
+    values: Option<CellValues>,
+    theme: Theme,
+    gamepad: bool,
+    teaching: bool,
+    /// Shows the strings-and-coins view of the position instead of the
+    /// dots and boxes.
+    coins: bool,
+    mode: GameMode,
+    /// The participants of a 2v2 game, which then replace the mode.
+    teams: Option<Teams>,
+    engine: Engine,
+    /// Strength of Blue's AI, and of Red's when the AI plays both sides.
+    difficulty: Difficulty,
+    red_difficulty: Difficulty,
     app_update: Callback<AppMsg>,
 }
 
 struct BoardComp<G: GameTrait> {
     board_state: G,
+    callbacks: BoardCallbacks,
+    cursor: BarId,
+    marked: Vec<BarId>,
+    review: Option<MoveReview>,
+    hint: Option<BarId>,
+    /// Chain picked with the context menu, highlighted until the next move.
+    shown_chain: Option<Chain>,
+    /// Started on the AI's first turn. `None` until then, or for good if
+    /// the worker failed.
+    ai_worker: Option<AIWorker>,
+    worker_failed: bool,
+    /// Number of searches started so far, which identifies the last one.
+    searches: u32,
+    /// Id of the search the AI is waiting for, in the worker or by the
+    /// external bot.
+    thinking: Option<u32>,
+    /// Best move so far of that search.
+    ai_preview: Option<BarId>,
+    /// Pending delay before the AI's next move, when it plays both sides.
+    ai_delay: Option<Timeout>,
+    long_press: Option<Timeout>,
+    menu: Option<BoardPress>,
+    viewport: NodeRef,
+    wake_lock: WakeLock,
+    #[cfg(feature = "debug")]
+    dev_console: DevConsole,
+    #[cfg(feature = "debug")]
+    hot_reload_slot: u32,
+    _gamepad: Option<GamepadPoller>,
+    _resize_listener: Option<EventListener>,
 }
 
 impl<G: GameTrait + 'static> Component for BoardComp<G> {
@@ -33,27 +223,331 @@ impl<G: GameTrait + 'static> Component for BoardComp<G> {
         ctx.props()
             .app_update
             .emit(AppMsg::BoardUpdate(board_update));
-        let board_state = G::new(ctx.props().width, ctx.props().height);
-        Self { board_state }
+        let (width, height) = (
+            supported_size(ctx.props().width),
+            supported_size(ctx.props().height),
+        );
+        let mut board_state = G::new(width, height);
+        let (mask, values) = (&ctx.props().mask, &ctx.props().values);
+        if mask.is_some() || values.is_some() {
+            let config = GameConfig {
+                mask: mask.clone(),
+                values: values.clone(),
+                ..GameConfig::new(width, height)
+            };
+            if let Err(err) = board_state.reconfigure(config) {
+                console::error_1(&format!("ignoring the board shape: {}", err).into());
+            }
+        }
+        #[cfg(feature = "debug")]
+        let hot_reload_slot = hot_reload::next_slot();
+        #[cfg(feature = "debug")]
+        if let Some(board) = hot_reload::restore(hot_reload_slot) {
+            board_state.set_board(board);
+        }
+        apply_ai_settings(&mut board_state, ctx.props());
+        let cursor = BarId {
+            direction: BarDirection::Horizontal,
+            col: 0,
+            row: 0,
+        };
+        let link = ctx.link();
+        let callbacks = BoardCallbacks {
+            onclick: link.callback(|bar: BarId| BoardMsg::ClickBar {
+                direction: bar.direction,
+                col: bar.col,
+                row: bar.row,
+            }),
+            onpressstart: link.callback(BoardMsg::PressStart),
+            onpresscancel: link.callback(|_| BoardMsg::PressCancel),
+            oncontextmenu: link.callback(BoardMsg::OpenMenu),
+        };
+        let gamepad = ctx
+            .props()
+            .gamepad
+            .then(|| GamepadPoller::new(ctx.link().callback(BoardMsg::Input)));
+        let link = ctx.link().clone();
+        let resize_listener = web_sys::window().map(|window| {
+            EventListener::new(&window, "resize", move |_| {
+                link.send_message(BoardMsg::ViewportChanged)
+            })
+        });
+        Self {
+            board_state,
+            callbacks,
+            cursor,
+            marked: vec![],
+            review: None,
+            hint: None,
+            shown_chain: None,
+            ai_worker: None,
+            worker_failed: false,
+            searches: 0,
+            thinking: None,
+            ai_preview: None,
+            ai_delay: None,
+            long_press: None,
+            menu: None,
+            viewport: NodeRef::default(),
+            wake_lock: WakeLock::new(),
+            #[cfg(feature = "debug")]
+            dev_console: DevConsole::new(ctx.link().callback(BoardMsg::Dev)),
+            #[cfg(feature = "debug")]
+            hot_reload_slot,
+            _gamepad: gamepad,
+            _resize_listener: resize_listener,
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        apply_ai_settings(&mut self.board_state, ctx.props());
+        if self.thinking.is_none() && self.ai_delay.is_none() {
+            self.start_ai(ctx);
+        }
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        self.gen_table(ctx)
+        html! {
+            <>
+                { self.gen_score() }
+                if ctx.props().coins {
+                    { self.gen_coins(ctx) }
+                } else {
+                    { self.gen_table(ctx) }
+                }
+                if self.thinking.is_some() {
+                    <p class="ai-thinking">{ "Thinking..." }</p>
+                } else if self.board_state.endgame_solved() && self.game_in_progress() {
+                    <p class="ai-thinking">{ "AI has solved the endgame" }</p>
+                }
+                { self.gen_plan() }
+                { self.gen_review(ctx) }
+            </>
+        }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        if first_render && self.is_virtualized() {
+            ctx.link().send_message(BoardMsg::ViewportChanged);
+        }
+        self.wake_lock.set_active(self.game_in_progress());
+        #[cfg(feature = "debug")]
+        {
+            self.dev_console.sync(
+                self.board_state.board(),
+                self.board_state.last_search_stats(),
+            );
+            hot_reload::save(self.hot_reload_slot, self.board_state.board());
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             BoardMsg::ClickBar {
                 direction,
                 col,
                 row,
-            } => self.board_state.do_move(BarId {
-                direction,
-                col,
-                row,
-            }),
-            BoardMsg::StartGame(player) => {
-                self.board_state.restart(player);
+            } => self.play_move(
+                ctx,
+                BarId {
+                    direction,
+                    col,
+                    row,
+                },
+            ),
+            BoardMsg::NewGame(config) => {
+                let starting_player = config.starting_player;
+                if let Err(err) = self.board_state.reconfigure(config) {
+                    let error = format!("cannot start a new game: {}", err);
+                    console::error_1(&error.clone().into());
+                    (ctx.props().app_update).emit(AppMsg::GameEvent(GameEvent::Error(error)));
+                    return false;
+                }
+                self.review = None;
+                self.hint = None;
+                self.shown_chain = None;
+                self.stop_ai();
+                let props = ctx.props();
+                props.app_update.emit(AppMsg::GameEvent(GameEvent::GameStarted {
+                    width: self.board_state.get_width(),
+                    height: self.board_state.get_height(),
+                    starting_player,
+                    mode: props.mode,
+                    difficulties: [props.red_difficulty, props.difficulty],
+                }));
+                self.start_ai(ctx);
+                true
+            }
+            BoardMsg::CopyPosition => {
+                let app_update = ctx.props().app_update.clone();
+                let on_done = Callback::from(move |copied| {
+                    if copied {
+                        app_update.emit(AppMsg::ShowToast("Position copied".into()));
+                    }
+                });
+                clipboard::copy_text(self.board_state.board().to_fen(), on_done);
+                false
+            }
+            BoardMsg::SharePosition => {
+                let app_update = ctx.props().app_update.clone();
+                let on_done = Callback::from(move |outcome| {
+                    if outcome == ShareOutcome::Copied {
+                        app_update.emit(AppMsg::ShowToast("Position copied".into()));
+                    }
+                });
+                let fen = self.board_state.board().to_fen();
+                share::share_text("Dots and Boxes position", fen, on_done);
+                false
+            }
+            BoardMsg::Input(InputAction::MoveCursor(direction)) => {
+                self.cursor = input::move_cursor(
+                    self.cursor,
+                    direction,
+                    self.board_state.get_width(),
+                    self.board_state.get_height(),
+                );
+                true
+            }
+            BoardMsg::Input(InputAction::Play) => self.play_move(ctx, self.cursor),
+            BoardMsg::Input(InputAction::Undo) => {
+                if !self.board_state.can_undo() {
+                    return false;
+                }
+                self.stop_ai();
+                self.board_state.undo();
+                self.history_changed(ctx);
+                true
+            }
+            BoardMsg::Input(InputAction::Redo) => {
+                if !self.board_state.can_redo() {
+                    return false;
+                }
+                self.stop_ai();
+                self.board_state.redo();
+                self.history_changed(ctx);
+                true
+            }
+            BoardMsg::Input(InputAction::Hint) => {
+                if self.thinking.is_some() {
+                    return false;
+                }
+                self.hint = self.board_state.suggest_move();
+                if let Some(hint) = self.hint {
+                    self.cursor = hint;
+                }
+                self.hint.is_some()
+            }
+            BoardMsg::PressStart(press) => {
+                let link = ctx.link().clone();
+                self.long_press = Some(Timeout::new(LONG_PRESS_MS, move || {
+                    link.send_message(BoardMsg::OpenMenu(press))
+                }));
+                false
+            }
+            BoardMsg::PressCancel => {
+                self.long_press = None;
+                false
+            }
+            BoardMsg::OpenMenu(press) => {
+                self.long_press = None;
+                if self.menu_actions(press.target).is_empty() {
+                    return false;
+                }
+                self.menu = Some(press);
+                true
+            }
+            BoardMsg::ViewportChanged => self.is_virtualized(),
+            BoardMsg::TakeBack => match self.review.take() {
+                Some(review) => {
+                    self.stop_ai();
+                    self.board_state.undo();
+                    self.hint = Some(review.better);
+                    self.cursor = review.better;
+                    true
+                }
+                None => false,
+            },
+            BoardMsg::DismissReview => self.review.take().is_some(),
+            BoardMsg::Worker(WorkerEvent::Progress(id, bar)) => {
+                if self.thinking != Some(id) {
+                    return false;
+                }
+                self.ai_preview = Some(bar);
+                true
+            }
+            BoardMsg::Worker(WorkerEvent::Move(id, bar, stats)) => {
+                if self.thinking != Some(id) {
+                    return false;
+                }
+                self.thinking = None;
+                self.ai_preview = None;
+                self.board_state.record_search_stats(stats);
+                let outcome = bar.map(|bar| self.board_state.do_single_move(bar));
+                match outcome {
+                    Some(outcome) if outcome.is_legal() => {
+                        self.shown_chain = None;
+                        self.move_played(ctx, &outcome);
+                        self.start_ai(ctx);
+                    }
+                    // Only the external bot fails to move. Picking another
+                    // engine changes the props, which asks that one instead.
+                    _ => {
+                        let error = format!("AI move failed: {:?}", bar);
+                        console::error_1(&error.clone().into());
+                        let app_update = &ctx.props().app_update;
+                        app_update.emit(AppMsg::GameEvent(GameEvent::Error(error)));
+                        app_update.emit(AppMsg::ShowToast(
+                            "The bot did not move, pick another engine to go on".into(),
+                        ));
+                    }
+                }
+                true
+            }
+            BoardMsg::Worker(WorkerEvent::Failed) => {
+                self.ai_worker = None;
+                self.worker_failed = true;
+                if self.thinking.take().is_some() {
+                    self.search_ai_move(ctx);
+                }
+                true
+            }
+            BoardMsg::AIStep => {
+                self.ai_delay = None;
+                self.search_ai_move(ctx);
+                true
+            }
+            BoardMsg::CloseMenu => self.menu.take().is_some(),
+            BoardMsg::MenuAction(action) => {
+                let target = match self.menu.take() {
+                    Some(menu) => menu.target,
+                    None => return false,
+                };
+                match (action, target) {
+                    (ContextAction::Mark, MenuTarget::Bar(bar)) => self.marked.push(bar),
+                    (ContextAction::Unmark, MenuTarget::Bar(bar)) => {
+                        self.marked.retain(|marked| *marked != bar)
+                    }
+                    (ContextAction::ShowChain, _) => self.shown_chain = self.chain_at(target),
+                    (ContextAction::Hint, _) => {
+                        ctx.link().send_message(BoardMsg::Input(InputAction::Hint))
+                    }
+                    (ContextAction::Mark | ContextAction::Unmark, MenuTarget::Cell(..)) => {}
+                }
+                true
+            }
+            #[cfg(feature = "debug")]
+            BoardMsg::Dev(DevCommand::Load(board)) => {
+                self.board_state.set_board(*board);
+                self.stop_ai();
+                true
+            }
+            #[cfg(feature = "debug")]
+            BoardMsg::Dev(DevCommand::ForceMove(bar)) => {
+                let mut board = self.board_state.board().clone();
+                board.play(bar);
+                self.board_state.set_board(board);
+                self.stop_ai();
                 true
             }
         }
@@ -61,83 +555,1062 @@ impl<G: GameTrait + 'static> Component for BoardComp<G> {
 }
 
 impl<G: GameTrait + 'static> BoardComp<G> {
+    fn play_move(&mut self, ctx: &Context<Self>, bar: BarId) -> bool {
+        if self.thinking.is_some() || self.board_state.ai_to_move() {
+            return false;
+        }
+        let bar = self.board_state.board().wrap_bar(bar);
+        let review = if ctx.props().teaching {
+            (self.board_state.review_move(bar)).map(|better| MoveReview {
+                better,
+                mistake: self.board_state.board().chain_mistake(bar),
+            })
+        } else {
+            None
+        };
+        let outcome = self.board_state.do_single_move(bar);
+        if !outcome.is_legal() {
+            return false;
+        }
+        self.review = review;
+        self.hint = None;
+        self.shown_chain = None;
+        self.move_played(ctx, &outcome);
+        self.start_ai(ctx);
+        true
+    }
+
+    /// Drops what referred to the position before an undo or redo, and lets
+    /// the AI move if it is its turn now.
+    fn history_changed(&mut self, ctx: &Context<Self>) {
+        self.review = None;
+        self.hint = None;
+        self.shown_chain = None;
+        self.start_ai(ctx);
+    }
+
+    /// Lets the AI move if it is its turn. When it plays both sides, or
+    /// every participant of a team game, each move waits for
+    /// `AI_DEMO_MOVE_MS` first.
+    fn start_ai(&mut self, ctx: &Context<Self>) {
+        if !self.board_state.ai_to_move() {
+            return;
+        }
+        let props = ctx.props();
+        let demo = match props.teams {
+            Some(teams) => teams.ai_plays_all(),
+            None => props.mode == GameMode::AIVsAI,
+        };
+        if demo {
+            let link = ctx.link().clone();
+            self.ai_delay = Some(Timeout::new(AI_DEMO_MOVE_MS, move || {
+                link.send_message(BoardMsg::AIStep)
+            }));
+        } else {
+            self.search_ai_move(ctx);
+        }
+    }
+
+    /// Searches the AI's move in the AI worker if possible, otherwise right
+    /// away on the main thread. The external bot is asked directly, as it
+    /// lives on the page.
+    fn search_ai_move(&mut self, ctx: &Context<Self>) {
+        let request = match self.board_state.pending_search() {
+            Some(request) => request,
+            None => return,
+        };
+        self.searches = self.searches.wrapping_add(1);
+        let id = self.searches;
+        #[cfg(feature = "ai")]
+        if request.engine == Engine::External {
+            let on_move = (ctx.link())
+                .callback(move |bar| BoardMsg::Worker(WorkerEvent::Move(id, bar, None)));
+            crate::external::request_move(&request.board, on_move);
+            self.thinking = Some(id);
+            return;
+        }
+        if self.ai_worker.is_none() && !self.worker_failed {
+            self.ai_worker = AIWorker::new(ctx.link().callback(BoardMsg::Worker));
+            self.worker_failed = self.ai_worker.is_none();
+        }
+        match &mut self.ai_worker {
+            Some(worker) => {
+                worker.search(id, &request);
+                self.thinking = Some(id);
+            }
+            None => {
+                if let Some(outcome) = self.board_state.play_ai_move() {
+                    self.move_played(ctx, &outcome);
+                    self.start_ai(ctx);
+                }
+            }
+        }
+    }
+
+    /// Cancels the AI's pending move, e.g. because the position changed.
+    /// A worker still searching is terminated rather than left to finish,
+    /// and a new one is started for the next search.
+    fn stop_ai(&mut self) {
+        if self.thinking.take().is_some() {
+            self.ai_worker = None;
+        }
+        self.ai_preview = None;
+        self.ai_delay = None;
+    }
+
+    /// Plays the sound of a move, and reports the end of the game.
+    fn move_played(&self, ctx: &Context<Self>, outcome: &MoveOutcome) {
+        let sound = if !outcome.completed().is_empty() {
+            SoundId::BoxCompleted
+        } else {
+            SoundId::BarPlaced
+        };
+        let app_update = &ctx.props().app_update;
+        app_update.emit(AppMsg::PlaySound(sound));
+        if outcome.game_over() {
+            app_update.emit(AppMsg::GameEvent(GameEvent::GameFinished {
+                red_boxes: self.board_state.score(Player::Red),
+                blue_boxes: self.board_state.score(Player::Blue),
+            }));
+        }
+    }
+
+    /// A game is in progress from the first bar until the last box is taken.
+    fn game_in_progress(&self) -> bool {
+        let board = self.board_state.board();
+        let bar_played = (board.bars()).any(|(_, state)| matches!(state, CellState::Player(_)));
+        bar_played && !board.is_finished()
+    }
+
     fn gen_table(&self, ctx: &Context<Self>) -> Html {
-        let span = 4;
-        let columns = (self.board_state.get_width() - 1) * span + self.board_state.get_width();
-        let style = format!("--nr-columns: {}; --span: {}", columns, span);
-        let mut rows = vec![];
-        for row_id in 0..self.board_state.get_height() - 1 {
-            rows.push(self.gen_thin_row(ctx, row_id));
-            rows.push(self.gen_thick_row(ctx, row_id));
-        }
-        rows.push(self.gen_thin_row(ctx, self.board_state.get_height() - 1));
+        let theme = &ctx.props().theme;
+        let span = theme.span;
+        let width = self.board_state.get_width();
+        let height = self.board_state.get_height();
+        let tracks = |dots: u32| format!("repeat({}, 1fr {}fr) 1fr", dots - 1, span);
+        let units = |dots: u32| (dots - 1) * (span + 1) + 1;
+        let mut board_style = format!(
+            "grid-template-columns: {}; grid-template-rows: {}; aspect-ratio: {} / {};",
+            tracks(width),
+            tracks(height),
+            units(width),
+            units(height),
+        );
+        let (cols, rows) = if self.is_virtualized() {
+            board_style.push_str(&format!(" width: {}px;", units(width) * VIRTUAL_UNIT_PX));
+            self.visible_range(span)
+        } else {
+            (0..width, 0..height)
+        };
+        // Dots, bars and boxes off a board that is not a rectangle are
+        // left out, and show the board's background.
+        let board = self.board_state.board();
+        let has_bar = |direction, col, row| {
+            board.has_bar(board.wrap_bar(BarId { direction, col, row }))
+        };
+        let mut items = vec![];
+        for row in rows {
+            for col in cols.clone() {
+                if board.has_dot(col, row) {
+                    items.push(html! { <DotComp key={format!("d {} {}", col, row)} {col} {row}/> });
+                }
+                if col + 1 < width && has_bar(BarDirection::Horizontal, col, row) {
+                    items.push(self.gen_bar(BarDirection::Horizontal, col, row));
+                }
+                if row + 1 < height && has_bar(BarDirection::Vertical, col, row) {
+                    items.push(self.gen_bar(BarDirection::Vertical, col, row));
+                }
+                if col + 1 < width && row + 1 < height && board.has_box(col, row) {
+                    items.push(self.gen_inner_cell(col, row));
+                }
+            }
+        }
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            let action = InputAction::from_key(&e.key());
+            if action.is_some() {
+                e.prevent_default();
+            }
+            action.map(BoardMsg::Input)
+        });
+        let board = html! {
+            <div
+                class={classes!("board", self.thinking.is_some().then_some("thinking"))}
+                tabindex="0"
+                style={ board_style }
+                { onkeydown }
+            >
+                { items.into_iter().collect::<Html>() }
+                { self.gen_context_menu(ctx) }
+            </div>
+        };
+        let style = theme.css_vars();
+        if self.is_virtualized() {
+            html! {
+                <div
+                    class="board-viewport"
+                    { style }
+                    ref={self.viewport.clone()}
+                    onscroll={ctx.link().callback(|_| BoardMsg::ViewportChanged)}
+                >{ board }</div>
+            }
+        } else {
+            html! { <div class="board-root" { style }>{ board }</div> }
+        }
+    }
+
+    /// The strings-and-coins view of the position, see `coins.rs`: a coin
+    /// in the middle of every box, and a string across every free bar.
+    /// Clicking a string plays its bar.
+    fn gen_coins(&self, ctx: &Context<Self>) -> Html {
+        let board = self.board_state.board();
+        let (width, height) = (board.width(), board.height());
+        let center = |col: u32, row: u32| {
+            ((col as f64 + 0.5) * COIN_STEP, (row as f64 + 0.5) * COIN_STEP)
+        };
+        // The middle of the bar next to the box, which on a torus can be
+        // the copy of the bar on the other side of the board.
+        let middle = |bar: BarId, (col, row): (u32, u32)| match bar.direction {
+            BarDirection::Vertical => {
+                let x = if bar.col == col || bar.col == col + 1 { bar.col } else { width - 1 };
+                (x as f64 * COIN_STEP, (bar.row as f64 + 0.5) * COIN_STEP)
+            }
+            BarDirection::Horizontal => {
+                let y = if bar.row == row || bar.row == row + 1 { bar.row } else { height - 1 };
+                ((bar.col as f64 + 0.5) * COIN_STEP, y as f64 * COIN_STEP)
+            }
+        };
+        let strings = board.strings().map(|string| {
+            let bar = string.bar;
+            let path = (string.ends.iter())
+                .filter_map(|end| match end {
+                    StringEnd::Coin(col, row) => Some((*col, *row)),
+                    StringEnd::Ground => None,
+                })
+                .map(|cell| {
+                    let ((x1, y1), (x2, y2)) = (center(cell.0, cell.1), middle(bar, cell));
+                    format!("M {} {} L {} {}", x1, y1, x2, y2)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let onclick = ctx.link().callback(move |_| BoardMsg::ClickBar {
+                direction: bar.direction,
+                col: bar.col,
+                row: bar.row,
+            });
+            html! {
+                <path
+                    key={bar.to_string()}
+                    class={classes!("string", (self.hint == Some(bar)).then_some("hint"))}
+                    d={path}
+                    {onclick}
+                />
+            }
+        });
+        let coins = (board.cells())
+            .filter(|(col, row, _)| board.has_box(*col, *row))
+            .map(|(col, row, state)| {
+                let (x, y) = center(col, row);
+                html! {
+                    <circle
+                        class="coin"
+                        data-state={state.to_string()}
+                        cx={x.to_string()}
+                        cy={y.to_string()}
+                        r={(COIN_STEP / 4.0).to_string()}
+                    />
+                }
+            });
+        let view_box = format!(
+            "0 0 {} {}",
+            (width - 1) as f64 * COIN_STEP,
+            (height - 1) as f64 * COIN_STEP
+        );
         html! {
-            <div class="board" { style }> { rows.into_iter().collect::<Html>() } </div>
+            <div class="board-root" style={ctx.props().theme.css_vars()}>
+                <svg class="coins-board" viewBox={view_box}>
+                    { for strings }
+                    { for coins }
+                </svg>
+            </div>
         }
     }
 
-    fn gen_thin_row(&self, ctx: &Context<Self>, row_idx: u32) -> Html {
-        let mut cells = vec![];
-        for col_idx in 0..self.board_state.get_width() - 1 {
-            cells.push(Self::gen_dot(col_idx, row_idx));
-            cells.push(self.gen_hbar(ctx, col_idx, row_idx));
+    fn is_virtualized(&self) -> bool {
+        self.board_state
+            .get_width()
+            .max(self.board_state.get_height())
+            >= VIRTUALIZE_MIN_SIZE
+    }
+
+    /// Dot columns and rows that overlap the scrolled viewport, plus a
+    /// margin. Empty until the viewport has been rendered once.
+    fn visible_range(&self, span: u32) -> (Range<u32>, Range<u32>) {
+        let viewport = match self.viewport.cast::<Element>() {
+            Some(viewport) => viewport,
+            None => return (0..0, 0..0),
+        };
+        let pitch = (VIRTUAL_UNIT_PX * (span + 1)) as i32;
+        let range = |scroll: i32, size: i32, dots: u32| {
+            let first = (scroll / pitch) as u32;
+            let last = ((scroll + size) / pitch) as u32 + 1;
+            first.saturating_sub(VIRTUAL_MARGIN)..(last + VIRTUAL_MARGIN).min(dots)
+        };
+        (
+            range(
+                viewport.scroll_left(),
+                viewport.client_width(),
+                self.board_state.get_width(),
+            ),
+            range(
+                viewport.scroll_top(),
+                viewport.client_height(),
+                self.board_state.get_height(),
+            ),
+        )
+    }
+
+    fn gen_bar(&self, direction: BarDirection, col: u32, row: u32) -> Html {
+        let place = BarId {
+            direction,
+            col,
+            row,
+        };
+        // On a torus, the last column and row of bars show and play the
+        // first ones.
+        let bar = self.board_state.board().wrap_bar(place);
+        let (state, class) = match direction {
+            BarDirection::Horizontal => {
+                (self.board_state.horizontal_get(bar.col, bar.row), "hbar")
+            }
+            BarDirection::Vertical => (self.board_state.vertical_get(bar.col, bar.row), "vbar"),
+        };
+        let key = format!("{} {} {}", class, col, row);
+        let highlight = BarHighlight {
+            cursor: self.cursor == bar,
+            marked: self.marked.contains(&bar),
+            hint: self.hint == Some(bar),
+            preview: self.thinking.is_some() && self.ai_preview == Some(bar),
+        };
+        html! { <BarComp
+            { key }
+            { bar }
+            { place }
+            { state }
+            { highlight }
+            callbacks={ self.callbacks.clone() }
+        /> }
+    }
+
+    /// The chain or loop `target` is in, or for a bar, the one of a box
+    /// next to it.
+    fn chain_at(&self, target: MenuTarget) -> Option<Chain> {
+        let board = self.board_state.board();
+        let cells = match target {
+            MenuTarget::Bar(bar) => board.bar_neighbors(bar).collect::<Vec<_>>(),
+            MenuTarget::Cell(col, row) => vec![(col, row)],
+        };
+        (board.chains().into_iter())
+            .find(|chain| (chain.cells.iter()).any(|cell| cells.contains(&(cell.col, cell.row))))
+    }
+
+    /// What the context menu offers on `target`: marking bars, showing
+    /// the chain there, and a hint while the human is to move.
+    fn menu_actions(&self, target: MenuTarget) -> Vec<ContextAction> {
+        let mut actions = vec![];
+        if let MenuTarget::Bar(bar) = target {
+            actions.push(if self.marked.contains(&bar) {
+                ContextAction::Unmark
+            } else {
+                ContextAction::Mark
+            });
         }
-        cells.push(Self::gen_dot(self.board_state.get_width() - 1, row_idx));
-        cells.into_iter().collect::<Html>()
+        if self.chain_at(target).is_some() {
+            actions.push(ContextAction::ShowChain);
+        }
+        let game = &self.board_state;
+        if self.thinking.is_none() && !game.ai_to_move() && !game.board().is_finished() {
+            actions.push(ContextAction::Hint);
+        }
+        actions
     }
 
-    fn gen_thick_row(&self, ctx: &Context<Self>, row_idx: u32) -> Html {
-        let mut cells = vec![];
-        for col_idx in 0..self.board_state.get_width() - 1 {
-            cells.push(self.gen_vbar(ctx, col_idx, row_idx));
-            cells.push(self.gen_inner_cell(col_idx, row_idx));
+    fn gen_context_menu(&self, ctx: &Context<Self>) -> Html {
+        let menu = match &self.menu {
+            Some(menu) => menu,
+            None => return html! {},
+        };
+        html! {
+            <ContextMenuComp
+                x={menu.x}
+                y={menu.y}
+                actions={self.menu_actions(menu.target)}
+                onselect={ctx.link().callback(BoardMsg::MenuAction)}
+                onclose={ctx.link().callback(|_| BoardMsg::CloseMenu)}
+            />
         }
-        cells.push(self.gen_vbar(ctx, self.board_state.get_width() - 1, row_idx));
-        cells.into_iter().collect::<Html>()
     }
 
-    fn gen_dot(col: u32, row: u32) -> Html {
-        let key = format!("d {} {}", col, row);
-        html! { <div
+    /// Points of each side, and whose move it is while the game goes on or
+    /// its result once it is over.
+    fn gen_score(&self) -> Html {
+        let game = &self.board_state;
+        gen_score(
+            [game.score(Player::Red), game.score(Player::Blue)],
+            game.winner(),
+            game.current_turn(),
+            game.participant_to_move(),
+        )
+    }
+
+    /// The line of play the AI expected in its last search, while the game
+    /// goes on.
+    fn gen_plan(&self) -> Html {
+        let line = match self.board_state.last_search_stats() {
+            Some(stats) if self.thinking.is_none() && self.game_in_progress() => {
+                &stats.principal_variation
+            }
+            _ => return html! {},
+        };
+        if line.is_empty() {
+            return html! {};
+        }
+        let mut plan = (line.iter().take(PLAN_SHOWN_MOVES))
+            .map(|bar| bar.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if line.len() > PLAN_SHOWN_MOVES {
+            plan += ", …";
+        }
+        html! { <p class="ai-plan">{ format!("AI plans: {}", plan) }</p> }
+    }
+
+    fn gen_review(&self, ctx: &Context<Self>) -> Html {
+        let Some(review) = &self.review else {
+            return html! {};
+        };
+        let explanation = match &review.mistake {
+            Some(ChainMistake::OpensLongChain(chain)) => {
+                let kind = match chain.kind {
+                    ChainKind::Chain => "chain",
+                    ChainKind::Loop => "loop",
+                };
+                format!(
+                    " It opens a {} of {} boxes for your opponent to take.",
+                    kind,
+                    chain.cells.len()
+                )
+            }
+            Some(ChainMistake::GivesUpControl) => {
+                " It hands your opponent the control of the long chains, so you \
+                 will have to open them."
+                    .to_string()
+            }
+            None => String::new(),
+        };
+        html! {
+            <div class="move-review">
+                <p>{ format!("There was a better move.{}", explanation) }</p>
+                <button onclick={ctx.link().callback(|_| BoardMsg::TakeBack)}>
+                    { "Take back and show it" }
+                </button>
+                <button onclick={ctx.link().callback(|_| BoardMsg::DismissReview)}>
+                    { "Keep my move" }
+                </button>
+            </div>
+        }
+    }
+
+    fn gen_inner_cell(&self, col: u32, row: u32) -> Html {
+        let board = self.board_state.board();
+        let state = board.cell_get(col, row);
+        let value = board.has_values().then(|| board.cell_value(col, row));
+        let key = format!("c {} {}", col, row);
+        let in_chain = (self.shown_chain.iter())
+            .any(|chain| (chain.cells.iter()).any(|cell| (cell.col, cell.row) == (col, row)));
+        html! { <CellComp
             { key }
-            class="dot"
-        ></div> }
+            { col }
+            { row }
+            { state }
+            { value }
+            { in_chain }
+            callbacks={ self.callbacks.clone() }
+        /> }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct DotProps {
+    col: u32,
+    row: u32,
+}
+
+struct DotComp {}
+
+impl Component for DotComp {
+    type Message = ();
+    type Properties = DotProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        DotComp {}
     }
 
-    fn gen_hbar(&self, ctx: &Context<Self>, col: u32, row: u32) -> Html {
-        let link = ctx.link();
-        let state = self.board_state.horizontal_get(col, row).to_string();
-        let key = format!("h {} {} {}", state, col, row);
-        let direction = BarDirection::Horizontal;
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
         html! { <div
-            { key }
-            class="bar hbar"
-            data-state={ state }
-            onclick={link.callback(move |_| BoardMsg::ClickBar {direction, col, row})}
+            class="dot"
+            style={ grid_area(2 * props.col, 2 * props.row) }
         ></div> }
     }
+}
 
-    fn gen_vbar(&self, ctx: &Context<Self>, col: u32, row: u32) -> Html {
-        let link = ctx.link();
-        let state = self.board_state.vertical_get(col, row).to_string();
-        let key = format!("v {} {} {}", state, col, row);
-        let direction = BarDirection::Vertical;
+#[derive(Properties, PartialEq)]
+struct BarProps {
+    bar: BarId,
+    /// Where the bar is drawn, `bar` itself unless it is a copy of the
+    /// first bar of its column or row on a torus.
+    place: BarId,
+    state: CellState,
+    highlight: BarHighlight,
+    callbacks: BoardCallbacks,
+}
+
+/// A single bar. Its props only change when the bar itself does, so a move
+/// re-renders the touched bar instead of the whole board.
+struct BarComp {
+    state: CellState,
+    animator: Animator,
+}
+
+impl Component for BarComp {
+    type Message = f64;
+    type Properties = BarProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        BarComp {
+            state: ctx.props().state,
+            animator: Animator::new(ctx.link().callback(std::convert::identity)),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let state = ctx.props().state;
+        if self.state == CellState::Free && state != CellState::Free {
+            let tween = Tween::new(0.0, 1.0, BAR_DRAW_MS, Easing::EaseOut);
+            self.animator.start("draw", tween);
+        }
+        self.state = state;
+        true
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, timestamp: Self::Message) -> bool {
+        self.animator.tick(timestamp)
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let bar = props.bar;
+        let BarId {
+            direction,
+            col,
+            row,
+        } = props.place;
+        let (class, mut style) = match direction {
+            BarDirection::Horizontal => ("hbar", grid_area(2 * col + 1, 2 * row)),
+            BarDirection::Vertical => ("vbar", grid_area(2 * col, 2 * row + 1)),
+        };
+        if let Some(progress) = self.animator.value("draw") {
+            style.push_str(&format!("; --progress: {}", progress));
+        }
+        let callbacks = &props.callbacks;
+        let (onpointerdown, oncontextmenu) = press_handlers(callbacks, MenuTarget::Bar(bar));
+        let highlight = props.highlight;
         html! { <div
-            { key }
-            class="bar vbar"
-            data-state={ state }
-            onclick={link.callback(move |_| BoardMsg::ClickBar {direction, col, row})}
+            class={classes!(
+                "bar",
+                class,
+                highlight.cursor.then_some("cursor"),
+                highlight.marked.then_some("marked"),
+                highlight.hint.then_some("hint"),
+                highlight.preview.then_some("preview"),
+            )}
+            { style }
+            data-state={ props.state.to_string() }
+            onclick={callbacks.onclick.reform(move |_| bar)}
+            { onpointerdown }
+            onpointerup={callbacks.onpresscancel.reform(|_| ())}
+            onpointerleave={callbacks.onpresscancel.reform(|_| ())}
+            onpointercancel={callbacks.onpresscancel.reform(|_| ())}
+            { oncontextmenu }
         ></div> }
     }
+}
 
-    fn gen_inner_cell(&self, col: u32, row: u32) -> Html {
-        let state = self.board_state.cell_get(col, row).to_string();
-        let key = format!("c {} {} {}", state, col, row);
+#[derive(Properties, PartialEq)]
+struct CellProps {
+    col: u32,
+    row: u32,
+    state: CellState,
+    /// Points the box is worth, shown on boards with values.
+    value: Option<u32>,
+    /// Whether the box is in the chain shown with the context menu.
+    in_chain: bool,
+    callbacks: BoardCallbacks,
+}
+
+struct CellComp {
+    state: CellState,
+    animator: Animator,
+}
+
+impl Component for CellComp {
+    type Message = f64;
+    type Properties = CellProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        CellComp {
+            state: ctx.props().state,
+            animator: Animator::new(ctx.link().callback(std::convert::identity)),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let state = ctx.props().state;
+        if self.state == CellState::Free && state != CellState::Free {
+            let tween = Tween::new(0.0, 1.0, CELL_FILL_MS, Easing::Linear);
+            self.animator.start("fill", tween);
+        }
+        self.state = state;
+        true
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, timestamp: Self::Message) -> bool {
+        self.animator.tick(timestamp)
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let mut style = grid_area(2 * props.col + 1, 2 * props.row + 1);
+        if let Some(progress) = self.animator.value("fill") {
+            style.push_str(&format!("; --progress: {}", progress));
+        }
+        let callbacks = &props.callbacks;
+        let target = MenuTarget::Cell(props.col, props.row);
+        let (onpointerdown, oncontextmenu) = press_handlers(callbacks, target);
         html! { <div
-            { key }
-            class="inner-cell"
-            data-state={ state }
-        ></div> }
+            class={classes!("inner-cell", props.in_chain.then_some("in-chain"))}
+            { style }
+            data-state={ props.state.to_string() }
+            { onpointerdown }
+            onpointerup={callbacks.onpresscancel.reform(|_| ())}
+            onpointerleave={callbacks.onpresscancel.reform(|_| ())}
+            onpointercancel={callbacks.onpresscancel.reform(|_| ())}
+            { oncontextmenu }
+        >
+            if let Some(value) = props.value {
+                <span class="cell-value">{ value }</span>
+            }
+        </div> }
+    }
+}
+
+/// Handlers opening the context menu on `target`: a long press, as touch
+/// devices have no right click, and a right click.
+fn press_handlers(
+    callbacks: &BoardCallbacks,
+    target: MenuTarget,
+) -> (Callback<PointerEvent>, Callback<MouseEvent>) {
+    let onpointerdown = {
+        let onpressstart = callbacks.onpressstart.clone();
+        Callback::from(move |e: PointerEvent| {
+            if e.pointer_type() == "touch" {
+                onpressstart.emit(BoardPress {
+                    target,
+                    x: e.client_x(),
+                    y: e.client_y(),
+                });
+            }
+        })
+    };
+    let oncontextmenu = callbacks.oncontextmenu.reform(move |e: MouseEvent| {
+        e.prevent_default();
+        BoardPress {
+            target,
+            x: e.client_x(),
+            y: e.client_y(),
+        }
+    });
+    (onpointerdown, oncontextmenu)
+}
+
+/// Places an element on the board grid, where even coordinates are dot
+/// tracks and odd coordinates are bar/cell tracks.
+fn grid_area(x: u32, y: u32) -> String {
+    format!("grid-area: {} / {}", y + 1, x + 1)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContextAction {
+    Mark,
+    Unmark,
+    ShowChain,
+    Hint,
+}
+
+impl ContextAction {
+    fn label(&self) -> &'static str {
+        match self {
+            ContextAction::Mark => "Mark edge",
+            ContextAction::Unmark => "Unmark edge",
+            ContextAction::ShowChain => "Show chain",
+            ContextAction::Hint => "Hint",
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ContextMenuProps {
+    x: i32,
+    y: i32,
+    actions: Vec<ContextAction>,
+    onselect: Callback<ContextAction>,
+    onclose: Callback<()>,
+}
+
+struct ContextMenuComp {}
+
+impl Component for ContextMenuComp {
+    type Message = ();
+    type Properties = ContextMenuProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        ContextMenuComp {}
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let style = format!("left: {}px; top: {}px", props.x, props.y);
+        let items = props.actions.iter().map(|action| {
+            let action = *action;
+            html! {
+                <li onclick={props.onselect.reform(move |_| action)}>{action.label()}</li>
+            }
+        });
+        let oncontextmenu = props.onclose.reform(|e: MouseEvent| e.prevent_default());
+        html! {
+            <>
+            <div
+                class="context-backdrop"
+                onclick={props.onclose.reform(|_| ())}
+                { oncontextmenu }
+            ></div>
+            <ul class="context-menu" { style }>{ for items }</ul>
+            </>
+        }
+    }
+}
+
+/// Which board the next game is played on.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoardShape {
+    #[default]
+    Squares,
+    Lattice(LatticeKind),
+}
+
+impl BoardShape {
+    pub const ALL: [BoardShape; 5] = [
+        BoardShape::Squares,
+        BoardShape::Lattice(LatticeKind::Triangles),
+        BoardShape::Lattice(LatticeKind::Hexagons),
+        BoardShape::Lattice(LatticeKind::Layers(2)),
+        BoardShape::Lattice(LatticeKind::Layers(3)),
+    ];
+}
+
+impl fmt::Display for BoardShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            BoardShape::Squares => write!(f, "Squares"),
+            BoardShape::Lattice(kind) => write!(f, "{}", kind),
+        }
+    }
+}
+
+/// Size of one step between neighboring dots of a lattice board, in SVG
+/// units.
+const LATTICE_STEP: f64 = 100.0;
+
+/// Where the grid above the shown one is drawn on a 3D board, in steps to
+/// the right and down, so it shows behind it in perspective.
+const LAYER_OFFSET: (f64, f64) = (0.35, -0.35);
+
+#[derive(PartialEq, Properties)]
+struct LatticeBoardProps {
+    kind: LatticeKind,
+    width: u32,
+    height: u32,
+    theme: Theme,
+    mode: GameMode,
+    difficulty: Difficulty,
+    red_difficulty: Difficulty,
+    app_update: Callback<AppMsg>,
+}
+
+enum LatticeMsg {
+    ClickEdge(u32),
+    NewGame(Player),
+    Undo,
+    Redo,
+    AIStep,
+    ShowLayer(u32),
+}
+
+/// A board whose cells are not squares, drawn as an SVG. Simpler than
+/// `BoardComp`: the AI moves on the main thread, with the AI settings of
+/// the control bar, and there is no keyboard play, teaching mode or
+/// position sharing. A 3D board shows one grid at a time, picked with a
+/// tab per layer, with the grid above it and the connectors between them.
+struct LatticeBoardComp {
+    game: LatticeGame,
+    /// Pending delay before the AI's next move.
+    ai_delay: Option<Timeout>,
+    /// The grid shown of a 3D board, from the bottom.
+    layer: u32,
+}
+
+impl Component for LatticeBoardComp {
+    type Message = LatticeMsg;
+    type Properties = LatticeBoardProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        // The control bar talks to the board through `BoardMsg`s, of which
+        // a lattice board understands starting a game, undo and redo.
+        let board_update = ctx.link().batch_callback(|msg| match msg {
+            BoardMsg::NewGame(config) => Some(LatticeMsg::NewGame(config.starting_player)),
+            BoardMsg::Input(InputAction::Undo) => Some(LatticeMsg::Undo),
+            BoardMsg::Input(InputAction::Redo) => Some(LatticeMsg::Redo),
+            _ => None,
+        });
+        let props = ctx.props();
+        props.app_update.emit(AppMsg::BoardUpdate(board_update));
+        let (width, height) = (supported_size(props.width), supported_size(props.height));
+        let game = LatticeGame::new(props.kind, width, height).expect("size was clamped");
+        let mut comp = Self {
+            game,
+            ai_delay: None,
+            layer: 0,
+        };
+        comp.apply_ai_settings(props);
+        comp
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        self.apply_ai_settings(ctx.props());
+        self.start_ai(ctx);
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            LatticeMsg::ClickEdge(edge) => {
+                if self.game.ai_to_move() {
+                    return false;
+                }
+                self.play(ctx, |game| game.do_single_move(edge).then_some(edge))
+            }
+            LatticeMsg::AIStep => {
+                self.ai_delay = None;
+                self.play(ctx, LatticeGame::play_ai_move)
+            }
+            LatticeMsg::NewGame(starting_player) => {
+                self.ai_delay = None;
+                self.game.restart(starting_player);
+                let props = ctx.props();
+                props.app_update.emit(AppMsg::GameEvent(GameEvent::GameStarted {
+                    width: self.game.board().width(),
+                    height: self.game.board().height(),
+                    starting_player,
+                    mode: props.mode,
+                    difficulties: [props.red_difficulty, props.difficulty],
+                }));
+                self.start_ai(ctx);
+                true
+            }
+            LatticeMsg::Undo => {
+                self.ai_delay = None;
+                let undone = self.game.undo();
+                self.start_ai(ctx);
+                undone
+            }
+            LatticeMsg::Redo => {
+                self.ai_delay = None;
+                let redone = self.game.redo();
+                self.start_ai(ctx);
+                redone
+            }
+            LatticeMsg::ShowLayer(layer) => {
+                let changed = layer != self.layer;
+                self.layer = layer;
+                changed
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let board = self.game.board();
+        let (width, height) = board.extent();
+        let (shift_x, shift_y) = match board.layer_count() {
+            1 => (0.0, 0.0),
+            _ => LAYER_OFFSET,
+        };
+        let margin = LATTICE_STEP / 4.0;
+        let view_box = format!(
+            "{} {} {} {}",
+            -margin,
+            shift_y * LATTICE_STEP - margin,
+            (width + shift_x) * LATTICE_STEP + 2.0 * margin,
+            (height - shift_y) * LATTICE_STEP + 2.0 * margin,
+        );
+        // How far above the shown grid each dot lies: 0 or 1 for the dots
+        // drawn, as they are all of the shown grid and the one above it.
+        let depth = |dot| board.dot_layer(dot).checked_sub(self.layer).filter(|depth| *depth <= 1);
+        let point = |dot| {
+            let (x, y) = board.dot_position(dot);
+            let depth = depth(dot).unwrap_or_default() as f64;
+            (
+                (x + depth * shift_x) * LATTICE_STEP,
+                (y + depth * shift_y) * LATTICE_STEP,
+            )
+        };
+        // Whether all `dots` are drawn, and whether they all lie in the
+        // grid above, which is drawn first, behind the others.
+        let shown = |dots: &[u32]| dots.iter().all(|dot| depth(*dot).is_some());
+        let behind = |dots: &[u32]| dots.iter().all(|dot| depth(*dot) == Some(1));
+        let mut cells = (0..board.cell_count())
+            .filter(|cell| shown(board.cell_dots(*cell)))
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|cell| !behind(board.cell_dots(*cell)));
+        let mut edges = (0..board.edge_count())
+            .filter(|edge| shown(&board.edge_dots(*edge)))
+            .collect::<Vec<_>>();
+        edges.sort_by_key(|edge| !behind(&board.edge_dots(*edge)));
+        let cells = cells.into_iter().map(|cell| {
+            let points = (board.cell_dots(cell).iter())
+                .map(|dot| {
+                    let (x, y) = point(*dot);
+                    format!("{},{}", x, y)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let state = board.cell_get(cell).to_string();
+            let class = classes!(
+                "lattice-cell",
+                behind(board.cell_dots(cell)).then_some("behind")
+            );
+            html! { <polygon {class} data-state={state} {points}/> }
+        });
+        let edges = edges.into_iter().map(|edge| {
+            let [from, to] = board.edge_dots(edge);
+            let ((x1, y1), (x2, y2)) = (point(from), point(to));
+            let state = board.edge_get(edge).to_string();
+            let onclick = ctx.link().callback(move |_| LatticeMsg::ClickEdge(edge));
+            let class = classes!("lattice-edge", behind(&[from, to]).then_some("behind"));
+            html! {
+                <line
+                    {class}
+                    data-state={state}
+                    x1={x1.to_string()}
+                    y1={y1.to_string()}
+                    x2={x2.to_string()}
+                    y2={y2.to_string()}
+                    {onclick}
+                />
+            }
+        });
+        let dots = (0..board.dot_count()).filter(|dot| depth(*dot).is_some()).map(|dot| {
+            let (x, y) = point(dot);
+            html! { <circle class="lattice-dot" cx={x.to_string()} cy={y.to_string()} r="8"/> }
+        });
+        let tabs = (0..board.layer_count()).map(|layer| {
+            let class = classes!("layer-tab", (layer == self.layer).then_some("active"));
+            let onclick = ctx.link().callback(move |_| LatticeMsg::ShowLayer(layer));
+            html! { <button {class} {onclick}>{format!("Layer {}", layer + 1)}</button> }
+        });
+        html! {
+            <>
+                { gen_score(
+                    [board.score(Player::Red), board.score(Player::Blue)],
+                    board.winner(),
+                    board.current_turn(),
+                    None,
+                ) }
+                if board.layer_count() > 1 {
+                    <div class="layer-tabs">{ for tabs }</div>
+                }
+                <div class="board-root" style={ctx.props().theme.css_vars()}>
+                    <svg class="lattice-board" viewBox={view_box}>
+                        { for cells }
+                        { for edges }
+                        { for dots }
+                    </svg>
+                </div>
+            </>
+        }
+    }
+}
+
+impl LatticeBoardComp {
+    fn apply_ai_settings(&mut self, props: &LatticeBoardProps) {
+        self.game.set_mode(props.mode);
+        self.game.set_difficulty(Player::Blue, props.difficulty);
+        self.game.set_difficulty(Player::Red, props.red_difficulty);
+    }
+
+    /// Plays a move with `play`, which returns the edge it drew, and lets
+    /// the AI respond. Returns whether a move was played.
+    fn play(
+        &mut self,
+        ctx: &Context<Self>,
+        play: impl FnOnce(&mut LatticeGame) -> Option<u32>,
+    ) -> bool {
+        let board = self.game.board();
+        let taken_before = board.score(Player::Red) + board.score(Player::Blue);
+        if play(&mut self.game).is_none() {
+            return false;
+        }
+        let board = self.game.board();
+        let (red_boxes, blue_boxes) = (board.score(Player::Red), board.score(Player::Blue));
+        let sound = if red_boxes + blue_boxes > taken_before {
+            SoundId::BoxCompleted
+        } else {
+            SoundId::BarPlaced
+        };
+        let app_update = &ctx.props().app_update;
+        app_update.emit(AppMsg::PlaySound(sound));
+        if board.is_finished() {
+            app_update.emit(AppMsg::GameEvent(GameEvent::GameFinished {
+                red_boxes,
+                blue_boxes,
+            }));
+        }
+        self.start_ai(ctx);
+        true
+    }
+
+    /// Schedules the AI's move if it is to move, after a pause when it
+    /// plays both sides, and right after the human's move is drawn
+    /// otherwise.
+    fn start_ai(&mut self, ctx: &Context<Self>) {
+        if !self.game.ai_to_move() || self.ai_delay.is_some() {
+            return;
+        }
+        let delay = match ctx.props().mode {
+            GameMode::AIVsAI => AI_DEMO_MOVE_MS,
+            GameMode::HumanVsAI | GameMode::HotSeat => 0,
+        };
+        let link = ctx.link().clone();
+        self.ai_delay = Some(Timeout::new(delay, move || link.send_message(LatticeMsg::AIStep)));
     }
 }
 
@@ -172,6 +1645,32 @@ impl Component for StartButtonComp {
 #[derive(Properties, PartialEq)]
 struct ControlBarProps {
     app_update: Callback<AppMsg>,
+    audio_settings: AudioSettings,
+    sound: bool,
+    share: bool,
+    /// Board of the next game started.
+    shape: BoardShape,
+    /// Rules of the next game started.
+    rules: Ruleset,
+    /// Handicap of the next game started.
+    handicap: Option<Handicap>,
+    /// Random bars played before the next game started, 0 for none.
+    scramble: u32,
+    /// The participants of a 2v2 game, `None` for two players.
+    teams: Option<Teams>,
+    /// Whether the strings-and-coins view is on, or `None` off the square
+    /// board.
+    coins: Option<bool>,
+    /// Whether teaching mode is on, or `None` unless a human plays the AI.
+    teaching: Option<bool>,
+    /// Which sides the AI plays, or `None` in builds without an AI.
+    mode: Option<GameMode>,
+    /// AI engine, or `None` unless the AI plays a side.
+    engine: Option<Engine>,
+    /// Strength of Blue's AI, or `None` unless the AI plays Blue.
+    difficulty: Option<Difficulty>,
+    /// Strength of Red's AI, or `None` unless the AI plays Red.
+    red_difficulty: Option<Difficulty>,
 }
 
 struct ControlBarComp {}
@@ -185,62 +1684,614 @@ impl Component for ControlBarComp {
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let app_update = ctx.props().app_update.clone();
+        let props = ctx.props();
+        let app_update = props.app_update.clone();
+        let audio_settings = &props.audio_settings;
+        let onmute = {
+            let mut settings = audio_settings.clone();
+            settings.muted = !settings.muted;
+            app_update.reform(move |_| AppMsg::AudioSettings(settings.clone()))
+        };
+        let onscramble = app_update.reform(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            AppMsg::SetScramble(input.value().parse().unwrap_or(0))
+        });
+        let onmisere = {
+            let mut rules = props.rules;
+            rules.misere = !rules.misere;
+            app_update.reform(move |_| AppMsg::SetRules(rules))
+        };
+        let onswedish = {
+            let mut rules = props.rules;
+            rules.swedish = !rules.swedish;
+            app_update.reform(move |_| AppMsg::SetRules(rules))
+        };
+        let onextraturn = {
+            let mut rules = props.rules;
+            rules.alternate_turns = !rules.alternate_turns;
+            app_update.reform(move |_| AppMsg::SetRules(rules))
+        };
+        let ontorus = {
+            let mut rules = props.rules;
+            rules.torus = !rules.torus;
+            app_update.reform(move |_| AppMsg::SetRules(rules))
+        };
+        // Cycles through no handicap, one for Red and one for Blue.
+        let onhandicap = {
+            let handicap = match props.handicap {
+                None => Some(Handicap {
+                    player: Player::Red,
+                    boxes: 1,
+                    bars: 0,
+                }),
+                Some(handicap) if handicap.player == Player::Red => Some(Handicap {
+                    player: Player::Blue,
+                    ..handicap
+                }),
+                Some(_) => None,
+            };
+            app_update.reform(move |_| AppMsg::SetHandicap(handicap))
+        };
+        // Who plays each participant, in builds with the AI.
+        let participant_buttons = (props.teams)
+            .filter(|_| cfg!(feature = "ai"))
+            .into_iter()
+            .flat_map(|teams| Participant::ALL.map(|participant| (teams, participant)))
+            .map(|(teams, participant)| {
+                Self::gen_participant_button(&app_update, teams, participant)
+            });
+        let onteams = {
+            let teams = match props.teams {
+                Some(_) => None,
+                None => Some(Teams::default()),
+            };
+            app_update.reform(move |_| AppMsg::SetTeams(teams))
+        };
+        let handicap_label = match props.handicap {
+            None => "Handicap: none".to_string(),
+            Some(handicap) => format!("Handicap: {}", handicap.player),
+        };
         html! {
         <div class={"control-bar"}>
             <h2>{"control bar"}</h2>
             <StartButtonComp player={Player::Red} app_update={app_update.clone()}/>
-            <StartButtonComp player={Player::Blue} app_update={app_update}/>
+            <StartButtonComp player={Player::Blue} app_update={app_update.clone()}/>
+            <button onclick={app_update.reform(|_| AppMsg::Undo)}>{"Undo"}</button>
+            <button onclick={app_update.reform(|_| AppMsg::Redo)}>{"Redo"}</button>
+            <button onclick={app_update.reform(|_| AppMsg::CopyPosition)}>
+                {"Copy position"}
+            </button>
+            if props.share {
+                <button onclick={app_update.reform(|_| AppMsg::SharePosition)}>
+                    {"Share"}
+                </button>
+            }
+            { Self::gen_select("Board", &BoardShape::ALL, props.shape, app_update.reform(AppMsg::SetShape)) }
+            // The rule variants are only played on the square board.
+            if props.shape == BoardShape::Squares {
+                <button onclick={onmisere}>
+                    { if props.rules.misere { "Misère: on" } else { "Misère: off" } }
+                </button>
+                <button onclick={onswedish}>
+                    { if props.rules.swedish { "Swedish board: on" } else { "Swedish board: off" } }
+                </button>
+                <button onclick={onextraturn}>
+                    { if props.rules.alternate_turns { "Extra turn: off" } else { "Extra turn: on" } }
+                </button>
+                <button onclick={ontorus}>
+                    { if props.rules.torus { "Torus: on" } else { "Torus: off" } }
+                </button>
+                <button onclick={onhandicap}>{ handicap_label }</button>
+                if let Some(handicap) = props.handicap {
+                    { Self::gen_handicap_input(&app_update, handicap, "Head start", |handicap| &mut handicap.boxes) }
+                    { Self::gen_handicap_input(&app_update, handicap, "Bars", |handicap| &mut handicap.bars) }
+                }
+                <label>
+                    { "Scramble" }
+                    <input
+                        type="number"
+                        min="0"
+                        max="99"
+                        value={props.scramble.to_string()}
+                        oninput={onscramble}
+                    />
+                </label>
+                <button onclick={onteams}>
+                    { if props.teams.is_some() { "Teams: 2v2" } else { "Teams: off" } }
+                </button>
+                { for participant_buttons }
+            }
+            if let Some(coins) = props.coins {
+                <button onclick={app_update.reform(move |_| AppMsg::SetCoins(!coins))}>
+                    { if coins { "View: strings and coins" } else { "View: dots and boxes" } }
+                </button>
+            }
+            if let Some(teaching) = props.teaching {
+                <button onclick={app_update.reform(move |_| AppMsg::SetTeaching(!teaching))}>
+                    { if teaching { "Teaching mode: on" } else { "Teaching mode: off" } }
+                </button>
+            }
+            if let Some(mode) = props.mode {
+                { Self::gen_select("Mode", &GameMode::ALL, mode, app_update.reform(AppMsg::SetMode)) }
+            }
+            if let Some(engine) = props.engine {
+                { Self::gen_select("Engine", &Engine::ALL, engine, app_update.reform(AppMsg::SetEngine)) }
+            }
+            if let Some(difficulty) = props.red_difficulty {
+                {
+                    Self::gen_select(
+                        "Red difficulty",
+                        &Difficulty::ALL,
+                        difficulty,
+                        app_update.reform(|difficulty| AppMsg::SetDifficulty(Player::Red, difficulty)),
+                    )
+                }
+            }
+            if let Some(difficulty) = props.difficulty {
+                {
+                    Self::gen_select(
+                        if props.red_difficulty.is_some() { "Blue difficulty" } else { "Difficulty" },
+                        &Difficulty::ALL,
+                        difficulty,
+                        app_update.reform(|difficulty| AppMsg::SetDifficulty(Player::Blue, difficulty)),
+                    )
+                }
+            }
+            if props.sound {
+                <div class="audio-settings">
+                    <button onclick={onmute}>
+                        { if audio_settings.muted { "Sound: off" } else { "Sound: on" } }
+                    </button>
+                    { Self::gen_volume_slider(&app_update, audio_settings, SoundCategory::Effects) }
+                    { Self::gen_volume_slider(&app_update, audio_settings, SoundCategory::Interface) }
+                </div>
+            }
         </div>}
     }
 }
 
+impl ControlBarComp {
+    fn gen_select<T: Copy + PartialEq + fmt::Display + 'static>(
+        label: &str,
+        options: &'static [T],
+        selected: T,
+        on_select: Callback<T>,
+    ) -> Html {
+        let onchange = on_select.reform(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let index = select.selected_index().max(0) as usize;
+            options[index.min(options.len() - 1)]
+        });
+        html! {
+            <label>
+                { label }
+                <select { onchange }>
+                    { for options.iter().map(|option| html! {
+                        <option selected={*option == selected}>{ option.to_string() }</option>
+                    }) }
+                </select>
+            </label>
+        }
+    }
+
+    /// Button switching `participant` of a team game between a human and
+    /// the AI.
+    fn gen_participant_button(
+        app_update: &Callback<AppMsg>,
+        teams: Teams,
+        participant: Participant,
+    ) -> Html {
+        let ai = teams.ai_plays(participant);
+        let mut toggled = teams;
+        toggled.ai[participant.index()] = !ai;
+        let onclick = app_update.reform(move |_| AppMsg::SetTeams(Some(toggled)));
+        html! {
+            <button { onclick }>
+                { format!("{}: {}", participant, if ai { "AI" } else { "human" }) }
+            </button>
+        }
+    }
+
+    /// Input of one number of the handicap, picked by `field`.
+    fn gen_handicap_input(
+        app_update: &Callback<AppMsg>,
+        handicap: Handicap,
+        label: &str,
+        field: fn(&mut Handicap) -> &mut u32,
+    ) -> Html {
+        let mut current = handicap;
+        let value = field(&mut current).to_string();
+        let oninput = app_update.reform(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut handicap = handicap;
+            *field(&mut handicap) = input.value().parse().unwrap_or(0);
+            AppMsg::SetHandicap(Some(handicap))
+        });
+        html! {
+            <label>
+                { label }
+                <input type="number" min="0" max="99" { value } { oninput }/>
+            </label>
+        }
+    }
+
+    fn gen_volume_slider(
+        app_update: &Callback<AppMsg>,
+        audio_settings: &AudioSettings,
+        category: SoundCategory,
+    ) -> Html {
+        let label = match category {
+            SoundCategory::Effects => "Effects",
+            SoundCategory::Interface => "Interface",
+        };
+        let value = (audio_settings.volume(category) * 100.0)
+            .round()
+            .to_string();
+        let settings = audio_settings.clone();
+        let oninput = app_update.reform(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let volume = input.value().parse::<f32>().unwrap_or(0.0) / 100.0;
+            let mut settings = settings.clone();
+            settings.set_volume(category, volume);
+            AppMsg::AudioSettings(settings)
+        });
+        html! {
+            <label>
+                { label }
+                <input
+                    type="range"
+                    min="0"
+                    max="100"
+                    { value }
+                    disabled={ audio_settings.muted }
+                    { oninput }
+                />
+            </label>
+        }
+    }
+}
+
 pub enum AppMsg {
     StartGame(Player),
     BoardUpdate(Callback<BoardMsg>),
+    PlaySound(SoundId),
+    AudioSettings(AudioSettings),
+    CopyPosition,
+    SharePosition,
+    Undo,
+    Redo,
+    ShowToast(String),
+    HideToast,
+    /// Sets the board of the next game started, and starts one on it.
+    SetShape(BoardShape),
+    /// Sets the rules of the next game started.
+    SetRules(Ruleset),
+    /// Sets the handicap of the next game started.
+    SetHandicap(Option<Handicap>),
+    /// Sets the random bars played before the next game started.
+    SetScramble(u32),
+    /// Switches to a 2v2 game, or back, from the next move on.
+    SetTeams(Option<Teams>),
+    SetTeaching(bool),
+    /// Switches between the dots-and-boxes and the strings-and-coins view.
+    SetCoins(bool),
+    SetMode(GameMode),
+    SetEngine(Engine),
+    SetDifficulty(Player, Difficulty),
+    GameEvent(GameEvent),
+}
+
+/// Props of one game instance. A page can mount several `AppComp`s, each
+/// with its own settings, e.g. through `yew::start_app_with_props_in_element`.
+#[derive(Properties)]
+pub struct AppProps {
+    /// Receives game events, e.g. for telemetry.
+    pub event_sink: Rc<dyn EventSink>,
+    /// Settings of this instance. Changing the board size starts a new game.
+    pub config: Config,
 }
 
+impl Default for AppProps {
+    fn default() -> Self {
+        Self {
+            event_sink: Rc::new(NoopEventSink),
+            config: Config::from_build_env(),
+        }
+    }
+}
+
+impl PartialEq for AppProps {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.event_sink, &other.event_sink) && self.config == other.config
+    }
+}
+
+const TOAST_MS: u32 = 2000;
+
 pub struct AppComp {
     board_update: Option<Callback<BoardMsg>>,
+    audio: AudioManager,
+    toast: Option<(String, Timeout)>,
+    shape: BoardShape,
+    /// Rules of the next game started.
+    rules: Ruleset,
+    /// Handicap of the next game started.
+    handicap: Option<Handicap>,
+    /// Random bars played before the next game started, 0 for none.
+    scramble: u32,
+    /// The participants of a 2v2 game, `None` for two players.
+    teams: Option<Teams>,
+    teaching: bool,
+    coins: bool,
+    mode: GameMode,
+    /// Starts as `Config::engine`. Changing it hands the game in progress
+    /// to the new engine.
+    engine: Engine,
+    difficulty: Difficulty,
+    red_difficulty: Difficulty,
 }
 
 impl Component for AppComp {
     type Message = AppMsg;
-    type Properties = ();
+    type Properties = AppProps;
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        Self { board_update: None }
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut audio = AudioManager::new();
+        if !ctx.props().config.sound {
+            audio.set_settings(AudioSettings {
+                muted: true,
+                ..AudioSettings::default()
+            });
+        }
+        Self {
+            board_update: None,
+            audio,
+            toast: None,
+            shape: BoardShape::default(),
+            rules: Ruleset::default(),
+            handicap: None,
+            scramble: 0,
+            teams: None,
+            teaching: false,
+            coins: false,
+            mode: GameMode::default(),
+            engine: ctx.props().config.engine,
+            difficulty: Difficulty::default(),
+            red_difficulty: Difficulty::default(),
+        }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         console::log_1(&"page load".into());
         let app_update = ctx.link().callback(std::convert::identity);
+        let config = &ctx.props().config;
+        // Teaching mode, the engines and teams are only there on the square
+        // board.
+        let squares = self.shape == BoardShape::Squares;
+        let teams = self.teams.filter(|_| squares);
         html! {
             <>
             <h1>{ "Dots and Boxes" }</h1>
             <div class="content">
-                <ControlBarComp app_update={app_update.clone()}/>
-                <BoardComp<Game<AIMinMax>> width=4 height=4 app_update={app_update.clone()}/>
+                <ControlBarComp
+                    app_update={app_update.clone()}
+                    audio_settings={self.audio.settings().clone()}
+                    sound={config.sound}
+                    share={config.share}
+                    shape={self.shape}
+                    rules={self.rules}
+                    handicap={self.handicap}
+                    scramble={self.scramble}
+                    teams={self.teams}
+                    coins={squares.then_some(self.coins)}
+                    teaching={
+                        (cfg!(feature = "ai")
+                            && squares
+                            && teams.is_none()
+                            && self.mode == GameMode::HumanVsAI)
+                            .then_some(self.teaching)
+                    }
+                    mode={(cfg!(feature = "ai") && teams.is_none()).then_some(self.mode)}
+                    engine={
+                        (cfg!(feature = "ai")
+                            && squares
+                            && (self.ai_plays(Player::Red) || self.ai_plays(Player::Blue)))
+                            .then_some(self.engine)
+                    }
+                    difficulty={
+                        (cfg!(feature = "ai") && self.ai_plays(Player::Blue))
+                            .then_some(self.difficulty)
+                    }
+                    red_difficulty={
+                        (cfg!(feature = "ai") && self.ai_plays(Player::Red))
+                            .then_some(self.red_difficulty)
+                    }
+                />
+                <ErrorBoundaryComp>
+                    { self.gen_board(config, app_update) }
+                </ErrorBoundaryComp>
             </div>
+            if let Some((message, _)) = &self.toast {
+                <div class="toast">{ message }</div>
+            }
             </>
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             AppMsg::StartGame(starting_player) => {
-                if let Some(board_update) = &self.board_update {
-                    board_update.emit(BoardMsg::StartGame(starting_player));
-                    true
-                } else {
-                    console::error_1(&"didn't get board_update callback".into());
-                    false
-                }
+                self.audio.play(SoundId::ButtonClick);
+                let config = &ctx.props().config;
+                let config = GameConfig {
+                    width: supported_size(config.board_width),
+                    height: supported_size(config.board_height),
+                    mask: config.board_mask.clone(),
+                    values: config.board_values.clone(),
+                    starting_player,
+                    rules: self.rules,
+                    handicap: self.handicap,
+                    // A new seed every game, so each one starts afresh.
+                    scramble: (self.scramble > 0).then(|| Scramble {
+                        bars: self.scramble,
+                        seed: js_sys::Date::now() as u64,
+                    }),
+                    teams: self.teams,
+                    mode: self.mode,
+                    engine: self.engine,
+                    difficulties: [self.red_difficulty, self.difficulty],
+                };
+                self.send_board(ctx, BoardMsg::NewGame(config))
+            }
+            AppMsg::CopyPosition => self.send_board(ctx, BoardMsg::CopyPosition),
+            AppMsg::SharePosition => self.send_board(ctx, BoardMsg::SharePosition),
+            AppMsg::Undo => self.send_board(ctx, BoardMsg::Input(InputAction::Undo)),
+            AppMsg::Redo => self.send_board(ctx, BoardMsg::Input(InputAction::Redo)),
+            AppMsg::ShowToast(message) => {
+                let link = ctx.link().clone();
+                let timeout = Timeout::new(TOAST_MS, move || link.send_message(AppMsg::HideToast));
+                self.toast = Some((message, timeout));
+                true
+            }
+            AppMsg::HideToast => self.toast.take().is_some(),
+            AppMsg::SetShape(shape) => {
+                self.shape = shape;
+                true
+            }
+            AppMsg::SetRules(rules) => {
+                self.rules = rules;
+                true
+            }
+            AppMsg::SetHandicap(handicap) => {
+                self.handicap = handicap;
+                true
+            }
+            AppMsg::SetScramble(bars) => {
+                self.scramble = bars;
+                true
+            }
+            AppMsg::SetTeams(teams) => {
+                self.teams = teams;
+                true
+            }
+            AppMsg::SetTeaching(teaching) => {
+                self.teaching = teaching;
+                true
+            }
+            AppMsg::SetCoins(coins) => {
+                self.coins = coins;
+                true
+            }
+            AppMsg::SetMode(mode) => {
+                self.mode = mode;
+                true
+            }
+            AppMsg::SetEngine(engine) => {
+                self.engine = engine;
+                true
+            }
+            AppMsg::SetDifficulty(Player::Red, difficulty) => {
+                self.red_difficulty = difficulty;
+                true
+            }
+            AppMsg::SetDifficulty(Player::Blue, difficulty) => {
+                self.difficulty = difficulty;
+                true
+            }
+            AppMsg::GameEvent(event) => {
+                ctx.props().event_sink.emit(&event);
+                false
             }
             AppMsg::BoardUpdate(cb) => {
                 self.board_update = Some(cb);
                 false
             }
+            AppMsg::PlaySound(sound) => {
+                self.audio.play(sound);
+                false
+            }
+            AppMsg::AudioSettings(settings) => {
+                self.audio.set_settings(settings);
+                true
+            }
+        }
+    }
+}
+
+impl AppComp {
+    /// Whether the AI plays a member of `player`'s side in the next game.
+    fn ai_plays(&self, player: Player) -> bool {
+        match self.teams.filter(|_| self.shape == BoardShape::Squares) {
+            Some(teams) => teams.ai_plays_in(player),
+            None => self.mode.ai_plays(player),
+        }
+    }
+
+    fn gen_board(&self, config: &Config, app_update: Callback<AppMsg>) -> Html {
+        match self.shape {
+            BoardShape::Squares => self.gen_square_board(config, app_update),
+            BoardShape::Lattice(kind) => html! {
+                <LatticeBoardComp
+                    key={format!("{} {}x{}", kind, config.board_width, config.board_height)}
+                    {kind}
+                    width={config.board_width}
+                    height={config.board_height}
+                    theme={config.theme.clone()}
+                    mode={self.mode}
+                    difficulty={self.difficulty}
+                    red_difficulty={self.red_difficulty}
+                    { app_update }
+                />
+            },
+        }
+    }
+
+    #[cfg(feature = "ai")]
+    fn gen_square_board(&self, config: &Config, app_update: Callback<AppMsg>) -> Html {
+        self.gen_board_with::<Game>(config, app_update)
+    }
+
+    /// Builds without the `ai` feature only have hot-seat games.
+    #[cfg(not(feature = "ai"))]
+    fn gen_square_board(&self, config: &Config, app_update: Callback<AppMsg>) -> Html {
+        self.gen_board_with::<HotSeatGame>(config, app_update)
+    }
+
+    fn gen_board_with<G: GameTrait + 'static>(
+        &self,
+        config: &Config,
+        app_update: Callback<AppMsg>,
+    ) -> Html {
+        html! {
+            <BoardComp<G>
+                key={format!("{}x{}", config.board_width, config.board_height)}
+                width={config.board_width}
+                height={config.board_height}
+                mask={config.board_mask.clone()}
+                values={config.board_values.clone()}
+                theme={config.theme.clone()}
+                gamepad={config.gamepad}
+                teaching={self.teaching}
+                coins={self.coins}
+                mode={self.mode}
+                teams={self.teams}
+                engine={self.engine}
+                difficulty={self.difficulty}
+                red_difficulty={self.red_difficulty}
+                { app_update }
+            />
+        }
+    }
+
+    fn send_board(&self, ctx: &Context<Self>, msg: BoardMsg) -> bool {
+        if let Some(board_update) = &self.board_update {
+            board_update.emit(msg);
+            true
+        } else {
+            let error = "didn't get board_update callback";
+            console::error_1(&error.into());
+            ctx.props().event_sink.emit(&GameEvent::Error(error.into()));
+            false
         }
     }
 }
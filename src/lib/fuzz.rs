@@ -0,0 +1,76 @@
+use crate::ai::AIState;
+use crate::minmax::MinMaxState;
+use crate::symmetry::MAX_SYMMETRIES;
+use crate::BoardState;
+
+/// What undoing moves must restore: the position with its side to move,
+/// and its hashes under every symmetry.
+type Snapshot = (String, [u64; MAX_SYMMETRIES]);
+
+fn snapshot(board: &BoardState) -> Snapshot {
+    (board.to_fen(), board.hashes)
+}
+
+/// Checks `AIState`'s undo against arbitrary input, for the libFuzzer
+/// target in `fuzz/`. The first two bytes pick the board size, from 2 to 7
+/// dots a side. Each further byte with the high bit clear plays the free
+/// bar its value indexes, modulo their number, and one with the high bit
+/// set undoes as many moves as its low bits say, modulo the moves played,
+/// plus one. Panics if an undo fails or leaves another position than the
+/// one before the undone moves. All remaining moves are undone at the end.
+pub fn fuzz_undo(data: &[u8]) {
+    let [width, height, ops @ ..] = data else {
+        return;
+    };
+    let size = |byte: u8| 2 + u32::from(byte) % 6;
+    let mut state = AIState::from(BoardState::new(size(*width), size(*height)));
+    // The snapshot before each move played and not undone yet.
+    let mut history = vec![];
+    for op in ops {
+        if op & 0x80 == 0 {
+            let moves = state.possible_moves().collect::<Vec<_>>();
+            if moves.is_empty() {
+                continue;
+            }
+            history.push(snapshot(&state));
+            assert!(state._apply_move(moves[usize::from(*op) % moves.len()]));
+        } else if !history.is_empty() {
+            let count = usize::from(op & 0x7f) % history.len() + 1;
+            undo_and_check(&mut state, &mut history, count);
+        }
+    }
+    let count = history.len();
+    undo_and_check(&mut state, &mut history, count);
+}
+
+fn undo_and_check(state: &mut AIState, history: &mut Vec<Snapshot>, count: usize) {
+    if count == 0 {
+        return;
+    }
+    assert!(state._undo_moves(count as u32), "undo failed");
+    let expected = history.split_off(history.len() - count).swap_remove(0);
+    assert_eq!(snapshot(state), expected, "undo left another position");
+}
+
+/// Checks `AIState`'s undo after every sequence of moves on a
+/// `width`x`height` board, each undone one move at a time. Only tiny boards
+/// finish: their sequences number the factorial of their bars.
+pub fn check_undo_exhaustive(width: u32, height: u32) {
+    let mut state = AIState::from(BoardState::new(width, height));
+    check_undo_from(&mut state);
+}
+
+fn check_undo_from(state: &mut AIState) {
+    let before = snapshot(state);
+    for mv in state.possible_moves().collect::<Vec<_>>() {
+        assert!(state._apply_move(mv));
+        check_undo_from(state);
+        assert!(state._undo_moves(1), "undo of {:?} failed", mv);
+        assert_eq!(
+            snapshot(state),
+            before,
+            "undo of {:?} left another position",
+            mv
+        );
+    }
+}
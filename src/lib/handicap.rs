@@ -0,0 +1,74 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{BoardState, CellState, Player};
+
+/// A head start for the weaker of two unequal players, set up with the
+/// game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Handicap {
+    /// The weaker player, whom the handicap favors.
+    pub player: Player,
+    /// Points the player starts with, as if they owned that many boxes
+    /// worth a point. In misère play they go to the opponent instead.
+    pub boxes: u32,
+    /// Bars drawn for the player before the game starts. Only bars that
+    /// leave every box at least two free bars are drawn, so none can be
+    /// taken right away, which may be fewer.
+    pub bars: u32,
+}
+
+impl BoardState {
+    /// The handicap the game was set up with, if any.
+    pub fn handicap(&self) -> Option<Handicap> {
+        self.handicap
+    }
+
+    /// Points `player` starts with, see `Handicap::boxes`.
+    pub fn head_start(&self, player: Player) -> u32 {
+        match self.handicap {
+            Some(handicap) if (handicap.player == player) != self.rules.misere => handicap.boxes,
+            _ => 0,
+        }
+    }
+
+    /// Red's head start minus Blue's.
+    pub(crate) fn head_start_balance(&self) -> i32 {
+        self.head_start(Player::Red) as i32 - self.head_start(Player::Blue) as i32
+    }
+
+    /// Sets the handicap of an empty board and draws its bars, once the
+    /// board's shape and rules are set.
+    pub(crate) fn set_handicap(&mut self, handicap: Option<Handicap>) {
+        let key = Self::handicap_key(self.handicap) ^ Self::handicap_key(handicap);
+        crate::zobrist::toggle_keys(&mut self.hashes, [key; crate::MAX_SYMMETRIES]);
+        self.handicap = handicap;
+        self.draw_handicap_bars();
+    }
+
+    /// Draws the bars of the handicap, if any, on an empty board. They are
+    /// picked in the order of their keys, see `zobrist.rs`, which spreads
+    /// them over the board the same way every game.
+    pub(crate) fn draw_handicap_bars(&mut self) {
+        let Some(handicap) = self.handicap else {
+            return;
+        };
+        let mut bars = (self.bars())
+            .filter(|(_, state)| *state == CellState::Free)
+            .map(|(bar, _)| bar)
+            .collect::<Vec<_>>();
+        bars.sort_by_key(|bar| self.bar_key(*bar));
+        let mut drawn = 0;
+        for bar in bars {
+            if drawn == handicap.bars {
+                break;
+            }
+            let safe = (self.bar_neighbors(bar)).all(|(col, row)| self.open_edges(col, row) > 2);
+            if safe {
+                self.bar_set(bar, handicap.player.into());
+                drawn += 1;
+            }
+        }
+    }
+}
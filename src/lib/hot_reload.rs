@@ -0,0 +1,46 @@
+use std::cell::Cell;
+use web_sys::{console, Storage};
+
+use crate::BoardState;
+
+const STORAGE_KEY: &str = "dab-hot-reload";
+
+thread_local! {
+    static NEXT_SLOT: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Storage slot for the next board created on the page. Boards are created
+/// in the same order after a reload, so each one gets its own game back.
+pub fn next_slot() -> u32 {
+    NEXT_SLOT.with(|next| next.replace(next.get() + 1))
+}
+
+/// Remembers the position in `sessionStorage`, so that the page reload
+/// triggered by `trunk serve` after a rebuild resumes the same game.
+pub fn save(slot: u32, board: &BoardState) {
+    if let Some(storage) = session_storage() {
+        if let Err(err) = storage.set_item(&storage_key(slot), &board.to_fen()) {
+            console::warn_2(&"failed to save position:".into(), &err);
+        }
+    }
+}
+
+/// Position saved by `save` before the last reload, if any.
+pub fn restore(slot: u32) -> Option<BoardState> {
+    let fen = session_storage()?.get_item(&storage_key(slot)).ok()??;
+    match BoardState::from_fen(&fen) {
+        Ok(board) => Some(board),
+        Err(err) => {
+            console::warn_1(&format!("ignoring saved position {}: {}", fen, err).into());
+            None
+        }
+    }
+}
+
+fn storage_key(slot: u32) -> String {
+    format!("{}-{}", STORAGE_KEY, slot)
+}
+
+fn session_storage() -> Option<Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
@@ -0,0 +1,172 @@
+use gloo::timers::callback::Interval;
+use wasm_bindgen::JsCast;
+use web_sys::{Gamepad, GamepadButton};
+use yew::Callback;
+
+use crate::{BarDirection, BarId};
+
+const GAMEPAD_POLL_MS: u32 = 50;
+const STICK_DEADZONE: f64 = 0.5;
+
+const BUTTON_PLAY: u32 = 0;
+const BUTTON_HINT: u32 = 3;
+const BUTTON_UNDO: u32 = 4;
+const BUTTON_REDO: u32 = 5;
+const BUTTON_DPAD_UP: u32 = 12;
+const BUTTON_DPAD_DOWN: u32 = 13;
+const BUTTON_DPAD_LEFT: u32 = 14;
+const BUTTON_DPAD_RIGHT: u32 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Device independent action, produced by both the keyboard and gamepads.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputAction {
+    MoveCursor(CursorDirection),
+    Play,
+    /// Highlights the move the AI suggests and moves the cursor to it.
+    Hint,
+    Undo,
+    Redo,
+}
+
+impl InputAction {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "ArrowUp" | "w" => Some(InputAction::MoveCursor(CursorDirection::Up)),
+            "ArrowDown" | "s" => Some(InputAction::MoveCursor(CursorDirection::Down)),
+            "ArrowLeft" | "a" => Some(InputAction::MoveCursor(CursorDirection::Left)),
+            "ArrowRight" | "d" => Some(InputAction::MoveCursor(CursorDirection::Right)),
+            "Enter" | " " => Some(InputAction::Play),
+            "h" => Some(InputAction::Hint),
+            "u" => Some(InputAction::Undo),
+            "r" => Some(InputAction::Redo),
+            _ => None,
+        }
+    }
+
+    fn from_button(button: u32) -> Option<Self> {
+        match button {
+            BUTTON_PLAY => Some(InputAction::Play),
+            BUTTON_HINT => Some(InputAction::Hint),
+            BUTTON_UNDO => Some(InputAction::Undo),
+            BUTTON_REDO => Some(InputAction::Redo),
+            BUTTON_DPAD_UP => Some(InputAction::MoveCursor(CursorDirection::Up)),
+            BUTTON_DPAD_DOWN => Some(InputAction::MoveCursor(CursorDirection::Down)),
+            BUTTON_DPAD_LEFT => Some(InputAction::MoveCursor(CursorDirection::Left)),
+            BUTTON_DPAD_RIGHT => Some(InputAction::MoveCursor(CursorDirection::Right)),
+            _ => None,
+        }
+    }
+}
+
+/// Moves the cursor to the adjacent bar in the given direction. Moving
+/// up or down alternates between horizontal and vertical bars, so two
+/// steps down from a horizontal bar end on the horizontal bar below it.
+pub fn move_cursor(bar: BarId, direction: CursorDirection, width: u32, height: u32) -> BarId {
+    let BarId { col, row, .. } = bar;
+    let (new_direction, new_col, new_row) = match (bar.direction, direction) {
+        (BarDirection::Horizontal, CursorDirection::Left) if col > 0 => {
+            (BarDirection::Horizontal, col - 1, row)
+        }
+        (BarDirection::Horizontal, CursorDirection::Right) if col + 2 < width => {
+            (BarDirection::Horizontal, col + 1, row)
+        }
+        (BarDirection::Horizontal, CursorDirection::Up) if row > 0 => {
+            (BarDirection::Vertical, col, row - 1)
+        }
+        (BarDirection::Horizontal, CursorDirection::Down) if row + 1 < height => {
+            (BarDirection::Vertical, col, row)
+        }
+        (BarDirection::Vertical, CursorDirection::Left) if col > 0 => {
+            (BarDirection::Vertical, col - 1, row)
+        }
+        (BarDirection::Vertical, CursorDirection::Right) if col + 1 < width => {
+            (BarDirection::Vertical, col + 1, row)
+        }
+        (BarDirection::Vertical, CursorDirection::Up) => {
+            (BarDirection::Horizontal, col.min(width - 2), row)
+        }
+        (BarDirection::Vertical, CursorDirection::Down) => {
+            (BarDirection::Horizontal, col.min(width - 2), row + 1)
+        }
+        _ => return bar,
+    };
+    BarId {
+        direction: new_direction,
+        col: new_col,
+        row: new_row,
+    }
+}
+
+/// Polls the Gamepad API and emits an action whenever a mapped button or
+/// stick direction goes from released to pressed. Polling stops when the
+/// poller is dropped.
+pub struct GamepadPoller {
+    _interval: Interval,
+}
+
+impl GamepadPoller {
+    pub fn new(on_action: Callback<InputAction>) -> Self {
+        let mut prev_held = vec![];
+        let interval = Interval::new(GAMEPAD_POLL_MS, move || {
+            let held = held_gamepad_actions();
+            for action in held.iter() {
+                if !prev_held.contains(action) {
+                    on_action.emit(*action);
+                }
+            }
+            prev_held = held;
+        });
+        Self {
+            _interval: interval,
+        }
+    }
+}
+
+fn held_gamepad_actions() -> Vec<InputAction> {
+    let gamepads = match web_sys::window().map(|window| window.navigator().get_gamepads()) {
+        Some(Ok(gamepads)) => gamepads,
+        _ => return vec![],
+    };
+    let mut held = vec![];
+    for gamepad in gamepads.iter() {
+        let gamepad = match gamepad.dyn_into::<Gamepad>() {
+            Ok(gamepad) if gamepad.connected() => gamepad,
+            _ => continue,
+        };
+        for (index, button) in gamepad.buttons().iter().enumerate() {
+            let pressed = button
+                .dyn_into::<GamepadButton>()
+                .map(|button| button.pressed())
+                .unwrap_or(false);
+            if let Some(action) = InputAction::from_button(index as u32).filter(|_| pressed) {
+                held.push(action);
+            }
+        }
+        let axes = gamepad.axes();
+        let x = axes.get(0).as_f64().unwrap_or(0.0);
+        let y = axes.get(1).as_f64().unwrap_or(0.0);
+        let stick_direction = if x <= -STICK_DEADZONE {
+            Some(CursorDirection::Left)
+        } else if x >= STICK_DEADZONE {
+            Some(CursorDirection::Right)
+        } else if y <= -STICK_DEADZONE {
+            Some(CursorDirection::Up)
+        } else if y >= STICK_DEADZONE {
+            Some(CursorDirection::Down)
+        } else {
+            None
+        };
+        if let Some(direction) = stick_direction {
+            held.push(InputAction::MoveCursor(direction));
+        }
+    }
+    held
+}
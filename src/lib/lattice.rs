@@ -0,0 +1,632 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "ai")]
+use crate::lattice_ai;
+use crate::zobrist::key;
+use crate::{is_valid_size, BarError, CellState, Difficulty, GameError, GameMode, Player};
+
+// Kinds of keys after those of `zobrist.rs`, so lattice positions do not
+// share their hashes with positions on the square board.
+const EDGE_KEYS: u64 = 9;
+const RED_CELL_KEYS: u64 = 10;
+const BLUE_CELL_KEYS: u64 = 11;
+const TURN_KEYS: u64 = 12;
+
+/// Most grids a `LatticeKind::Layers` board stacks.
+pub const MAX_LAYERS: u32 = 4;
+
+/// Shape of the cells of a `LatticeBoard`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LatticeKind {
+    /// Dots and triangles: three edges close a cell. The dots lie in rows
+    /// shifted by half a step each, so every cell is an equilateral
+    /// triangle and the board a rhombus.
+    Triangles,
+    /// Six edges close a cell. A board of `width` by `height` dots has as
+    /// many hexagons as a square board has boxes, in rows shifted by half a
+    /// hexagon every other row.
+    Hexagons,
+    /// Experimental 3D boards: this many square grids of `width` by
+    /// `height` dots stacked on top of each other, with vertical connector
+    /// edges between dots above each other. Four edges close a cell, a box
+    /// of a grid or an upright square between two grids, so an edge lies
+    /// on up to four cells.
+    Layers(u32),
+}
+
+impl LatticeKind {
+    pub const ALL: [LatticeKind; 4] = [
+        LatticeKind::Triangles,
+        LatticeKind::Hexagons,
+        LatticeKind::Layers(2),
+        LatticeKind::Layers(3),
+    ];
+}
+
+impl fmt::Display for LatticeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            LatticeKind::Triangles => write!(f, "Triangles"),
+            LatticeKind::Hexagons => write!(f, "Hexagons"),
+            LatticeKind::Layers(layers) => write!(f, "3D, {} layers", layers),
+        }
+    }
+}
+
+/// Where the dots, edges and cells of a lattice lie, shared by every
+/// position on it.
+#[derive(Debug, PartialEq)]
+struct Layout {
+    kind: LatticeKind,
+    width: u32,
+    height: u32,
+    /// Position of each dot, one step apart from its neighbors.
+    dots: Vec<(f64, f64)>,
+    /// The dots at the ends of each edge.
+    edge_dots: Vec<[u32; 2]>,
+    /// The cells each edge lies on: one or two, or up to four on a 3D
+    /// board.
+    edge_cells: Vec<Vec<u32>>,
+    /// The corners of each cell, in order around it.
+    cell_dots: Vec<Vec<u32>>,
+    /// The edges around each cell, the one from its first corner first.
+    cell_edges: Vec<Vec<u32>>,
+}
+
+impl Layout {
+    fn new(kind: LatticeKind, width: u32, height: u32) -> Self {
+        let (dots, cells) = match kind {
+            LatticeKind::Triangles => triangles(width, height),
+            LatticeKind::Hexagons => hexagons(width, height),
+            LatticeKind::Layers(layers) => stacked(width, height, layers),
+        };
+        let mut layout = Self {
+            kind,
+            width,
+            height,
+            dots,
+            edge_dots: vec![],
+            edge_cells: vec![],
+            cell_dots: vec![],
+            cell_edges: vec![],
+        };
+        // Cells sharing two consecutive corners share the edge between
+        // them.
+        let mut edges = HashMap::new();
+        for (cell, corners) in cells.into_iter().enumerate() {
+            let mut cell_edges = vec![];
+            for (index, &from) in corners.iter().enumerate() {
+                let to = corners[(index + 1) % corners.len()];
+                let edge = *edges
+                    .entry((from.min(to), from.max(to)))
+                    .or_insert_with(|| {
+                        layout.edge_dots.push([from, to]);
+                        layout.edge_cells.push(vec![]);
+                        layout.edge_dots.len() as u32 - 1
+                    });
+                layout.edge_cells[edge as usize].push(cell as u32);
+                cell_edges.push(edge);
+            }
+            layout.cell_dots.push(corners);
+            layout.cell_edges.push(cell_edges);
+        }
+        layout
+    }
+}
+
+/// Dots of a triangular lattice of `width` by `height` dots, row by row,
+/// each row half a step left of the one above, and its triangles: the
+/// upper and the lower one of each rhombus of four dots.
+fn triangles(width: u32, height: u32) -> (Vec<(f64, f64)>, Vec<Vec<u32>>) {
+    let row_height = 3f64.sqrt() / 2.0;
+    let mut dots = vec![];
+    for row in 0..height {
+        for col in 0..width {
+            let shift = (height - 1 - row) as f64 / 2.0;
+            dots.push((col as f64 + shift, row as f64 * row_height));
+        }
+    }
+    let dot = |col: u32, row: u32| row * width + col;
+    let mut cells = vec![];
+    for row in 0..height - 1 {
+        for col in 0..width - 1 {
+            cells.push(vec![
+                dot(col, row),
+                dot(col + 1, row),
+                dot(col + 1, row + 1),
+            ]);
+            cells.push(vec![
+                dot(col, row),
+                dot(col + 1, row + 1),
+                dot(col, row + 1),
+            ]);
+        }
+    }
+    (dots, cells)
+}
+
+/// Dots and cells of `width - 1` by `height - 1` hexagons with a point on
+/// top and sides one step long, odd rows shifted right by half a hexagon.
+/// Neighboring hexagons share their corners, so each dot is numbered once,
+/// the first time a hexagon has it.
+fn hexagons(width: u32, height: u32) -> (Vec<(f64, f64)>, Vec<Vec<u32>>) {
+    let half_width = 3f64.sqrt() / 2.0;
+    // From the center, clockwise from the top.
+    let corners = [
+        (0.0, -1.0),
+        (half_width, -0.5),
+        (half_width, 0.5),
+        (0.0, 1.0),
+        (-half_width, 0.5),
+        (-half_width, -0.5),
+    ];
+    let mut dots = vec![];
+    let mut numbers = HashMap::new();
+    let mut cells = vec![];
+    for row in 0..height - 1 {
+        for col in 0..width - 1 {
+            let x = half_width * (2 * col + 1 + row % 2) as f64;
+            let y = 1.0 + 1.5 * row as f64;
+            let cell = corners.map(|(dx, dy)| {
+                let (x, y) = (x + dx, y + dy);
+                // Rounded, so a corner computed from either hexagon is
+                // the same dot.
+                let position = ((x * 1000.0).round() as i64, (y * 1000.0).round() as i64);
+                *numbers.entry(position).or_insert_with(|| {
+                    dots.push((x, y));
+                    dots.len() as u32 - 1
+                })
+            });
+            cells.push(cell.to_vec());
+        }
+    }
+    (dots, cells)
+}
+
+/// Dots and cells of `layers` square grids of `width` by `height` dots,
+/// layer by layer from the bottom, each dot at its place in its grid. The
+/// cells are the boxes of each grid, then the upright squares between
+/// each grid and the next, facing down the board and across it.
+fn stacked(width: u32, height: u32, layers: u32) -> (Vec<(f64, f64)>, Vec<Vec<u32>>) {
+    let mut dots = vec![];
+    for _ in 0..layers {
+        for row in 0..height {
+            for col in 0..width {
+                dots.push((col as f64, row as f64));
+            }
+        }
+    }
+    let dot = |col: u32, row: u32, layer: u32| (layer * height + row) * width + col;
+    let mut cells = vec![];
+    for layer in 0..layers {
+        for row in 0..height - 1 {
+            for col in 0..width - 1 {
+                cells.push(vec![
+                    dot(col, row, layer),
+                    dot(col + 1, row, layer),
+                    dot(col + 1, row + 1, layer),
+                    dot(col, row + 1, layer),
+                ]);
+            }
+        }
+    }
+    for layer in 0..layers - 1 {
+        for row in 0..height {
+            for col in 0..width - 1 {
+                cells.push(vec![
+                    dot(col, row, layer),
+                    dot(col + 1, row, layer),
+                    dot(col + 1, row, layer + 1),
+                    dot(col, row, layer + 1),
+                ]);
+            }
+        }
+        for row in 0..height - 1 {
+            for col in 0..width {
+                cells.push(vec![
+                    dot(col, row, layer),
+                    dot(col, row + 1, layer),
+                    dot(col, row + 1, layer + 1),
+                    dot(col, row, layer + 1),
+                ]);
+            }
+        }
+    }
+    (dots, cells)
+}
+
+/// A position on a board whose cells are not squares, see `LatticeKind`.
+/// Edges, cells and dots are numbered from zero. Like on the square board,
+/// drawing the last edge of a cell takes it and earns another move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatticeBoard {
+    layout: Arc<Layout>,
+    edges: Vec<CellState>,
+    cells: Vec<CellState>,
+    cur_turn: Player,
+    /// Zobrist hash of the drawn edges, the cell owners and the side to
+    /// move, kept up to date by the moves.
+    hash: u64,
+}
+
+impl LatticeBoard {
+    /// An empty board of `width` by `height` dots, Red to move. Fails on
+    /// an unsupported size or number of layers.
+    pub fn new(kind: LatticeKind, width: u32, height: u32) -> Result<Self, GameError> {
+        if !is_valid_size(width) || !is_valid_size(height) {
+            return Err(GameError::InvalidSize(width, height));
+        }
+        if let LatticeKind::Layers(layers) = kind {
+            if !(2..=MAX_LAYERS).contains(&layers) {
+                return Err(GameError::InvalidLayers(layers));
+            }
+        }
+        let layout = Layout::new(kind, width, height);
+        Ok(Self {
+            edges: vec![CellState::Free; layout.edge_dots.len()],
+            cells: vec![CellState::Free; layout.cell_dots.len()],
+            layout: Arc::new(layout),
+            cur_turn: Player::Red,
+            hash: 0,
+        })
+    }
+
+    pub fn kind(&self) -> LatticeKind {
+        self.layout.kind
+    }
+
+    /// Width in dots of the board size, see `LatticeKind` for what it
+    /// means for each shape.
+    pub fn width(&self) -> u32 {
+        self.layout.width
+    }
+
+    /// Height in dots of the board size.
+    pub fn height(&self) -> u32 {
+        self.layout.height
+    }
+
+    /// Grids stacked on a 3D board, 1 on the others.
+    pub fn layer_count(&self) -> u32 {
+        match self.layout.kind {
+            LatticeKind::Layers(layers) => layers,
+            LatticeKind::Triangles | LatticeKind::Hexagons => 1,
+        }
+    }
+
+    /// The grid `dot` lies in, counted from the bottom, always 0 on a flat
+    /// board.
+    pub fn dot_layer(&self, dot: u32) -> u32 {
+        match self.layout.kind {
+            LatticeKind::Layers(_) => dot / (self.layout.width * self.layout.height),
+            LatticeKind::Triangles | LatticeKind::Hexagons => 0,
+        }
+    }
+
+    pub fn dot_count(&self) -> u32 {
+        self.layout.dots.len() as u32
+    }
+
+    pub fn edge_count(&self) -> u32 {
+        self.edges.len() as u32
+    }
+
+    pub fn cell_count(&self) -> u32 {
+        self.cells.len() as u32
+    }
+
+    /// Where the dot lies, in steps between neighboring dots from the top
+    /// left. On a 3D board, that is its place in its grid.
+    pub fn dot_position(&self, dot: u32) -> (f64, f64) {
+        self.layout.dots[dot as usize]
+    }
+
+    /// Width and height of the board, in steps.
+    pub fn extent(&self) -> (f64, f64) {
+        (self.layout.dots.iter()).fold((0.0, 0.0), |(width, height), (x, y)| {
+            (f64::max(width, *x), f64::max(height, *y))
+        })
+    }
+
+    pub fn edge_dots(&self, edge: u32) -> [u32; 2] {
+        self.layout.edge_dots[edge as usize]
+    }
+
+    /// The one or two cells on either side of `edge`, up to four on a 3D
+    /// board.
+    pub fn edge_cells(&self, edge: u32) -> &[u32] {
+        &self.layout.edge_cells[edge as usize]
+    }
+
+    /// The corners of `cell`, in order around it.
+    pub fn cell_dots(&self, cell: u32) -> &[u32] {
+        &self.layout.cell_dots[cell as usize]
+    }
+
+    pub fn cell_edges(&self, cell: u32) -> &[u32] {
+        &self.layout.cell_edges[cell as usize]
+    }
+
+    /// The player who drew `edge`, `CellState::Free` if nobody did.
+    pub fn edge_get(&self, edge: u32) -> CellState {
+        self.edges[edge as usize]
+    }
+
+    /// The owner of `cell`.
+    pub fn cell_get(&self, cell: u32) -> CellState {
+        self.cells[cell as usize]
+    }
+
+    /// The side to move.
+    pub fn current_turn(&self) -> Player {
+        self.cur_turn
+    }
+
+    /// Zobrist hash of the position, see `BoardState::hash`. As there, who
+    /// drew the edges is left out.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Cells `player` owns.
+    pub fn score(&self, player: Player) -> u32 {
+        let owner = CellState::Player(player);
+        self.cells.iter().filter(|state| **state == owner).count() as u32
+    }
+
+    /// Whether every cell is taken.
+    pub fn is_finished(&self) -> bool {
+        !self.cells.contains(&CellState::Free)
+    }
+
+    /// The result once every cell is taken, like `GameTrait::winner`.
+    pub fn winner(&self) -> Option<Option<Player>> {
+        if !self.is_finished() {
+            return None;
+        }
+        let (red, blue) = (self.score(Player::Red), self.score(Player::Blue));
+        Some(match red.cmp(&blue) {
+            std::cmp::Ordering::Greater => Some(Player::Red),
+            std::cmp::Ordering::Less => Some(Player::Blue),
+            std::cmp::Ordering::Equal => None,
+        })
+    }
+
+    /// Edges of `cell` nobody has drawn yet.
+    pub fn free_edges(&self, cell: u32) -> u32 {
+        (self.cell_edges(cell).iter())
+            .filter(|edge| self.edge_get(**edge) == CellState::Free)
+            .count() as u32
+    }
+
+    /// Checks that `edge` lies on the board and is not drawn yet.
+    pub fn validate(&self, edge: u32) -> Result<(), BarError> {
+        match self.edges.get(edge as usize) {
+            None => Err(BarError::OutOfBounds),
+            Some(CellState::Free) => Ok(()),
+            Some(_) => Err(BarError::Taken),
+        }
+    }
+
+    /// Whether drawing the free `edge` takes a cell.
+    pub fn completes_cell(&self, edge: u32) -> bool {
+        (self.edge_cells(edge).iter()).any(|cell| self.free_edges(*cell) == 1)
+    }
+
+    /// Whether drawing the free `edge` leaves a cell for the opponent to
+    /// take, without taking one.
+    pub fn gives_cell(&self, edge: u32) -> bool {
+        !self.completes_cell(edge)
+            && (self.edge_cells(edge).iter()).any(|cell| self.free_edges(*cell) == 2)
+    }
+
+    /// Draws `edge` for the side to move, takes the cells it completes and
+    /// passes the turn unless it took one. Returns whether `edge` was free.
+    pub fn do_move(&mut self, edge: u32) -> bool {
+        if self.validate(edge).is_err() {
+            return false;
+        }
+        let player = self.cur_turn;
+        self.edges[edge as usize] = player.into();
+        self.hash ^= key(EDGE_KEYS, edge);
+        let mut took_cell = false;
+        for cell in self.layout.clone().edge_cells[edge as usize].iter() {
+            if self.free_edges(*cell) == 0 {
+                self.cell_set(*cell, player.into());
+                took_cell = true;
+            }
+        }
+        if !took_cell {
+            self.set_turn(player.other());
+        }
+        true
+    }
+
+    /// Takes back `edge`, which has to be the last edge drawn, and gives
+    /// the move back to the player who drew it. Returns that player, `None`
+    /// if `edge` is free.
+    pub(crate) fn unplay(&mut self, edge: u32) -> Option<Player> {
+        let CellState::Player(player) = self.edge_get(edge) else {
+            return None;
+        };
+        self.edges[edge as usize] = CellState::Free;
+        self.hash ^= key(EDGE_KEYS, edge);
+        for cell in self.layout.clone().edge_cells[edge as usize].iter() {
+            self.cell_set(*cell, CellState::Free);
+        }
+        self.set_turn(player);
+        Some(player)
+    }
+
+    /// Empties the board, with `starting_player` to move.
+    pub fn restart(&mut self, starting_player: Player) {
+        self.edges.fill(CellState::Free);
+        self.cells.fill(CellState::Free);
+        self.cur_turn = Player::Red;
+        self.hash = 0;
+        self.set_turn(starting_player);
+    }
+
+    fn cell_set(&mut self, cell: u32, state: CellState) {
+        self.hash ^= Self::cell_key(cell, self.cell_get(cell)) ^ Self::cell_key(cell, state);
+        self.cells[cell as usize] = state;
+    }
+
+    fn cell_key(cell: u32, state: CellState) -> u64 {
+        match state {
+            CellState::Free | CellState::Neutral => 0,
+            CellState::Player(Player::Red) => key(RED_CELL_KEYS, cell),
+            CellState::Player(Player::Blue) => key(BLUE_CELL_KEYS, cell),
+        }
+    }
+
+    fn set_turn(&mut self, player: Player) {
+        if player != self.cur_turn {
+            self.hash ^= key(TURN_KEYS, 0);
+            self.cur_turn = player;
+        }
+    }
+}
+
+/// A game on a `LatticeBoard`, between humans or against the AI. It
+/// mirrors the parts of `GameTrait` the lattice boards support: moves with
+/// the AI's replies, undo and redo, and the AI's settings.
+pub struct LatticeGame {
+    board: LatticeBoard,
+    /// Edges drawn since the game started, the last one last.
+    played: Vec<u32>,
+    /// Edges taken back since, the last one taken back last.
+    undone: Vec<u32>,
+    mode: GameMode,
+    /// Strength of the AI on each side, Red first.
+    difficulties: [Difficulty; 2],
+}
+
+impl LatticeGame {
+    /// A game on an empty board, Red to move. Fails on an unsupported
+    /// size.
+    pub fn new(kind: LatticeKind, width: u32, height: u32) -> Result<Self, GameError> {
+        Ok(Self {
+            board: LatticeBoard::new(kind, width, height)?,
+            played: vec![],
+            undone: vec![],
+            mode: GameMode::default(),
+            difficulties: [Difficulty::default(); 2],
+        })
+    }
+
+    pub fn board(&self) -> &LatticeBoard {
+        &self.board
+    }
+
+    /// Draws `edge` for the side to move and lets the AI respond. Returns
+    /// whether `edge` could be drawn.
+    pub fn do_move(&mut self, edge: u32) -> bool {
+        let played = self.do_single_move(edge);
+        if played {
+            self.play_ai_moves();
+        }
+        played
+    }
+
+    /// Draws `edge` for the side to move, without letting the AI respond.
+    pub fn do_single_move(&mut self, edge: u32) -> bool {
+        if !self.board.do_move(edge) {
+            return false;
+        }
+        self.played.push(edge);
+        self.undone.clear();
+        true
+    }
+
+    /// Lets the AI play until it is no longer its turn.
+    pub fn play_ai_moves(&mut self) {
+        while self.play_ai_move().is_some() {}
+    }
+
+    /// Plays one move for the side to move if the AI plays it. Returns the
+    /// edge it drew.
+    #[cfg(feature = "ai")]
+    pub fn play_ai_move(&mut self) -> Option<u32> {
+        if !self.ai_to_move() {
+            return None;
+        }
+        let difficulty = self.difficulties[seat(self.board.cur_turn)];
+        let edge = lattice_ai::best_move(&self.board, difficulty)?;
+        self.do_single_move(edge).then_some(edge)
+    }
+
+    /// Builds without the `ai` feature have no AI to move.
+    #[cfg(not(feature = "ai"))]
+    pub fn play_ai_move(&mut self) -> Option<u32> {
+        None
+    }
+
+    /// Whether the side to move is played by the AI, and the game is not
+    /// over yet.
+    pub fn ai_to_move(&self) -> bool {
+        cfg!(feature = "ai") && self.mode.ai_plays(self.board.cur_turn) && !self.board.is_finished()
+    }
+
+    /// Takes back the last move, and against the AI its moves since, so a
+    /// human is to move again. Returns whether there was a move to take
+    /// back.
+    pub fn undo(&mut self) -> bool {
+        let Some(edge) = self.played.pop() else {
+            return false;
+        };
+        self.board.unplay(edge);
+        self.undone.push(edge);
+        while self.ai_to_move() && self.mode != GameMode::AIVsAI {
+            let Some(edge) = self.played.pop() else {
+                break;
+            };
+            self.board.unplay(edge);
+            self.undone.push(edge);
+        }
+        true
+    }
+
+    /// Plays again the moves the last `undo` took back. Returns whether
+    /// there were any.
+    pub fn redo(&mut self) -> bool {
+        let Some(edge) = self.undone.pop() else {
+            return false;
+        };
+        self.board.do_move(edge);
+        self.played.push(edge);
+        while self.ai_to_move() {
+            let Some(edge) = self.undone.pop() else {
+                break;
+            };
+            self.board.do_move(edge);
+            self.played.push(edge);
+        }
+        true
+    }
+
+    pub fn restart(&mut self, starting_player: Player) {
+        self.board.restart(starting_player);
+        self.played.clear();
+        self.undone.clear();
+    }
+
+    /// Sets which sides the AI plays.
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+    }
+
+    /// Sets the strength of the AI playing `player`, from its next move on.
+    pub fn set_difficulty(&mut self, player: Player, difficulty: Difficulty) {
+        self.difficulties[seat(player)] = difficulty;
+    }
+}
+
+fn seat(player: Player) -> usize {
+    match player {
+        Player::Red => 0,
+        Player::Blue => 1,
+    }
+}
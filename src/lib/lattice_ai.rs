@@ -0,0 +1,142 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::ai::{entropy_seed, AIOptions};
+use crate::lattice::LatticeBoard;
+use crate::minmax::*;
+use crate::{CellState, Difficulty, Player};
+
+/// A `LatticeBoard` with the edges drawn during the search, so they can be
+/// taken back.
+#[derive(Clone)]
+pub(crate) struct LatticeState {
+    board: LatticeBoard,
+    played: Vec<u32>,
+}
+
+pub(crate) struct LatticeMovesIter {
+    next_edge: u32,
+}
+
+impl PossibleMovesIterator<LatticeState, u32> for LatticeMovesIter {
+    fn new(_state: &LatticeState) -> Self {
+        Self { next_edge: 0 }
+    }
+
+    fn next(&mut self, state: &LatticeState) -> Option<u32> {
+        let edge = (self.next_edge..state.board.edge_count())
+            .find(|edge| state.board.edge_get(*edge) == CellState::Free)?;
+        self.next_edge = edge + 1;
+        Some(edge)
+    }
+}
+
+impl MinMaxState for LatticeState {
+    type Move = u32;
+    type PossibleMovesIterator = LatticeMovesIter;
+
+    fn position_hash(&self) -> u64 {
+        self.board.hash()
+    }
+
+    fn _apply_move(&mut self, edge: u32) -> bool {
+        self.played.push(edge);
+        self.board.do_move(edge)
+    }
+
+    fn _undo_moves(&mut self, nr_moves: u32) -> bool {
+        (0..nr_moves).all(|_| {
+            let edge = self.played.pop().expect("mutation stack empty during undo");
+            self.board.unplay(edge).is_some()
+        })
+    }
+}
+
+/// Scores lattice positions for `player`. There is no chain evaluation for
+/// these boards: the heuristic counts the cells each side owns, crediting
+/// the cells that can be taken right away to the side to move.
+#[derive(Clone)]
+pub(crate) struct LatticeInterface {
+    player: Player,
+    rng: SmallRng,
+}
+
+impl LatticeInterface {
+    fn sign(&self) -> i32 {
+        match self.player {
+            Player::Red => 1,
+            Player::Blue => -1,
+        }
+    }
+}
+
+impl MinMaxInterface for LatticeInterface {
+    type State = LatticeState;
+
+    fn heuristic(&mut self, state: &mut Self::State) -> i32 {
+        let board = &state.board;
+        let balance = board.score(Player::Red) as i32 - board.score(Player::Blue) as i32;
+        let capturable = (0..board.cell_count())
+            .filter(|cell| board.cell_get(*cell) == CellState::Free && board.free_edges(*cell) == 1)
+            .count() as i32;
+        let capturable = match board.current_turn() {
+            Player::Red => capturable,
+            Player::Blue => -capturable,
+        };
+        self.sign() * (balance + capturable)
+    }
+
+    fn terminal_value(&mut self, state: &mut Self::State) -> i32 {
+        let board = &state.board;
+        let balance = board.score(Player::Red) as i32 - board.score(Player::Blue) as i32;
+        self.sign() * balance
+    }
+
+    fn is_maximizing(&self, state: &Self::State) -> bool {
+        state.board.current_turn() == self.player
+    }
+
+    /// Captures, so the search does not stop in the middle of taking a
+    /// chain.
+    fn is_forcing(&self, state: &Self::State, edge: u32) -> bool {
+        state.board.completes_cell(edge)
+    }
+
+    /// Captures first, then safe moves, then moves giving cells away.
+    fn order_moves(&mut self, state: &Self::State, moves: &mut [u32]) {
+        moves.sort_by_cached_key(|edge| {
+            if state.board.completes_cell(*edge) {
+                0
+            } else if !state.board.gives_cell(*edge) {
+                1
+            } else {
+                2
+            }
+        });
+    }
+
+    fn choose_index(&mut self, count: usize) -> usize {
+        self.rng.gen_range(0..count)
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.rng.gen_bool(probability.clamp(0.0, 1.0))
+    }
+}
+
+/// The edge the AI draws for the side to move, searched as deep and
+/// played as well as on the square board at `difficulty`. `None` once the
+/// board is full.
+pub(crate) fn best_move(board: &LatticeBoard, difficulty: Difficulty) -> Option<u32> {
+    let interface = LatticeInterface {
+        player: board.current_turn(),
+        rng: SmallRng::seed_from_u64(entropy_seed()),
+    };
+    let state = LatticeState {
+        board: board.clone(),
+        played: vec![],
+    };
+    let mut minmax = MinMax::new(interface, state);
+    minmax.set_options(AIOptions::from(difficulty).search);
+    let scored = minmax.score_moves();
+    minmax.pick_move(scored)
+}
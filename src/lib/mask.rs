@@ -0,0 +1,177 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{BarDirection, BarId, BoardState, CellState};
+
+/// Which boxes of a board of `width` by `height` dots are played, for
+/// boards that are not rectangles: L-shapes, crosses, boards with holes.
+/// Written row by row, the rows separated by `/`, with `#` for a box that
+/// is played and `.` for one that is not, e.g. `##./##./###` for an L on a
+/// board of 4 by 4 dots.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CellMask {
+    width: u32,
+    height: u32,
+    /// Whether each box is played, row by row.
+    cells: Vec<bool>,
+}
+
+impl CellMask {
+    /// Every box of a board of `width` by `height` dots. The size is
+    /// checked when a game is set up with the mask.
+    pub fn full(width: u32, height: u32) -> Self {
+        let len = width.saturating_sub(1) * height.saturating_sub(1);
+        Self {
+            width,
+            height,
+            cells: vec![true; len as usize],
+        }
+    }
+
+    /// Width in dots.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in dots.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Whether the box at `col`, `row` is played.
+    pub fn contains(&self, col: u32, row: u32) -> bool {
+        self.cells[self.index(col, row)]
+    }
+
+    pub fn set(&mut self, col: u32, row: u32, played: bool) {
+        let index = self.index(col, row);
+        self.cells[index] = played;
+    }
+
+    /// Whether no box is played.
+    pub fn is_empty(&self) -> bool {
+        !self.cells.contains(&true)
+    }
+
+    fn index(&self, col: u32, row: u32) -> usize {
+        assert!(
+            col + 1 < self.width && row + 1 < self.height,
+            "box ({},{}) outside a {}x{} mask",
+            col,
+            row,
+            self.width,
+            self.height
+        );
+        (row * (self.width - 1) + col) as usize
+    }
+}
+
+impl FromStr for CellMask {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s.split('/').collect::<Vec<_>>();
+        let cell_width = rows[0].len();
+        if cell_width == 0 || rows.iter().any(|row| row.len() != cell_width) {
+            return Err(());
+        }
+        let mut cells = vec![];
+        for c in rows.concat().chars() {
+            match c {
+                '#' => cells.push(true),
+                '.' => cells.push(false),
+                _ => return Err(()),
+            }
+        }
+        Ok(Self {
+            width: cell_width as u32 + 1,
+            height: rows.len() as u32 + 1,
+            cells,
+        })
+    }
+}
+
+impl fmt::Display for CellMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        for (index, row) in self.cells.chunks((self.width - 1) as usize).enumerate() {
+            if index > 0 {
+                write!(f, "/")?;
+            }
+            for played in row {
+                write!(f, "{}", if *played { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Boxes left out of the board are neutral, as are the bars with no box of
+/// the board on either side. Neither player can take or draw them, so the
+/// moves and the AI skip them like the border of a Swedish board.
+impl BoardState {
+    /// The boxes of the board.
+    pub fn mask(&self) -> CellMask {
+        CellMask {
+            width: self.width,
+            height: self.height,
+            cells: (self.cellstates.iter())
+                .map(|state| *state != CellState::Neutral)
+                .collect(),
+        }
+    }
+
+    /// Whether every box of the rectangle is played.
+    pub fn is_rectangular(&self) -> bool {
+        !self.cellstates.contains(&CellState::Neutral)
+    }
+
+    /// Whether the box at `col`, `row` is part of the board.
+    pub fn has_box(&self, col: u32, row: u32) -> bool {
+        self.cell_get(col, row) != CellState::Neutral
+    }
+
+    /// Whether `bar` lies on the board: a box of the board is on either
+    /// side of it.
+    pub fn has_bar(&self, bar: BarId) -> bool {
+        self.bar_neighbors(bar).next().is_some()
+    }
+
+    /// Whether the dot at `col`, `row` is a corner of a box of the board.
+    pub fn has_dot(&self, col: u32, row: u32) -> bool {
+        // The horizontal bars on either side of the dot touch its boxes.
+        [col.checked_sub(1), Some(col)]
+            .into_iter()
+            .flatten()
+            .filter(|col| *col < self.width - 1)
+            .any(|col| {
+                self.has_bar(self.wrap_bar(BarId {
+                    direction: BarDirection::Horizontal,
+                    col,
+                    row,
+                }))
+            })
+    }
+
+    /// Leaves the boxes `mask` does not contain out of an empty board, with
+    /// the bars only they have.
+    pub(crate) fn set_mask(&mut self, mask: &CellMask) {
+        for (col, row, _) in self.cells().collect::<Vec<_>>() {
+            if !mask.contains(col, row) {
+                self.cell_set(col, row, CellState::Neutral);
+            }
+        }
+        for bar in self.fixed_bars().collect::<Vec<_>>() {
+            self.bar_set(bar, CellState::Neutral);
+        }
+    }
+
+    /// Key of the boxes left out of the board, see `zobrist.rs`.
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    pub(crate) fn mask_key(&self) -> u64 {
+        (self.cells())
+            .filter(|(_, _, state)| *state == CellState::Neutral)
+            .fold(0, |key, (col, row, state)| {
+                key ^ self.cell_key(col, row, state)
+            })
+    }
+}
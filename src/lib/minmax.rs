@@ -1,11 +1,14 @@
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
-use web_sys::console;
+use std::cmp::Reverse;
 use std::fmt::Debug;
 
+use crate::stats::{self, SearchStats};
+
 pub trait PossibleMovesIterator<S: MinMaxState, M> {
-    fn new<'a>(state: &'a S) -> Self;
-    fn next<'a>(&mut self, state: &'a S) -> Option<M>;
+    fn new(state: &S) -> Self;
+    fn next(&mut self, state: &S) -> Option<M>;
 }
 
 pub struct PossibleMovesWrapper<'a, S: MinMaxState, M, I: PossibleMovesIterator<S, M>> {
@@ -24,8 +27,23 @@ impl<'a, S: MinMaxState, M, I: PossibleMovesIterator<S, M>> Iterator
     }
 }
 
-pub trait MinMaxState: Sized {
-    type Move: Copy + Debug;
+/// What the parallel search needs to hand the interface, its states and
+/// moves to other threads: copies it can send there. Without the `parallel`
+/// feature every type qualifies.
+#[cfg(feature = "parallel")]
+pub trait ThreadSafe: Clone + Send {}
+
+#[cfg(feature = "parallel")]
+impl<T: Clone + Send> ThreadSafe for T {}
+
+#[cfg(not(feature = "parallel"))]
+pub trait ThreadSafe {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> ThreadSafe for T {}
+
+pub trait MinMaxState: Sized + ThreadSafe {
+    type Move: Copy + Debug + PartialEq + ThreadSafe;
     type PossibleMovesIterator: PossibleMovesIterator<Self, Self::Move>;
 
     fn possible_moves<'a>(
@@ -34,7 +52,7 @@ pub trait MinMaxState: Sized {
         PossibleMovesWrapper {
             state: self,
             iter: Self::PossibleMovesIterator::new(self),
-            phantom: PhantomData::default(),
+            phantom: PhantomData,
         }
     }
 
@@ -48,6 +66,23 @@ pub trait MinMaxState: Sized {
     fn _apply_move(&mut self, mv: Self::Move) -> bool;
 
     fn _undo_moves(&mut self, nr_moves: u32) -> bool;
+
+    /// Hash identifying the position, for the transposition table. Equal
+    /// positions must hash equally, whatever moves led to them.
+    fn position_hash(&self) -> u64;
+
+    /// `mv`, a move of this position, as a move of the position that
+    /// stands for it in the transposition table, for states whose
+    /// `position_hash` is shared by symmetric positions. The table stores
+    /// moves in that orientation. The default leaves `mv` as it is.
+    fn canonical_move(&self, mv: Self::Move) -> Self::Move {
+        mv
+    }
+
+    /// The move of this position that `canonical_move` turns into `mv`.
+    fn move_from_canonical(&self, mv: Self::Move) -> Self::Move {
+        mv
+    }
 }
 
 pub struct MinMaxStateCheckpoint<'a, S: MinMaxState> {
@@ -83,24 +118,134 @@ impl<'a, S: MinMaxState> MinMaxStateCheckpoint<'a, S> {
     }
 }
 
-pub trait MinMaxInterface {
+pub trait MinMaxInterface: ThreadSafe {
     type State: MinMaxState;
 
+    /// Estimated value of `state`, from the point of view of the
+    /// maximizing side.
     fn heuristic(&mut self, state: &mut Self::State) -> i32;
+
+    /// Value of a finished game, on the same scale as the heuristic. The
+    /// default asks the heuristic.
+    fn terminal_value(&mut self, state: &mut Self::State) -> i32 {
+        self.heuristic(state)
+    }
+
+    /// Whether the side to move in `state` is the one the heuristic scores
+    /// for. Players do not simply alternate, e.g. completing a box in dots
+    /// and boxes earns another move.
+    fn is_maximizing(&self, state: &Self::State) -> bool;
+
+    /// Whether `mv` is forcing, e.g. a capture, which changes the position
+    /// too much for the heuristic to judge it before it is played. Forcing
+    /// moves are searched beyond the depth limit. The default has none.
+    fn is_forcing(&self, _state: &Self::State, _mv: <Self::State as MinMaxState>::Move) -> bool {
+        false
+    }
+
+    /// Reorders the moves of `state` so the likely best ones come first,
+    /// which lets alpha-beta cut off more subtrees. The default keeps the
+    /// order of `possible_moves`.
+    fn order_moves(
+        &mut self,
+        _state: &Self::State,
+        _moves: &mut [<Self::State as MinMaxState>::Move],
+    ) {
+    }
+
+    /// Picks one of `count` equally good moves. Used for tie-breaking, the
+    /// default always picks the first one.
+    fn choose_index(&mut self, _count: usize) -> usize {
+        0
+    }
+
+    /// Returns true with the given probability. The default never does.
+    fn chance(&mut self, _probability: f64) -> bool {
+        false
+    }
+
+    /// Called on each copy a parallel search makes, with the number of its
+    /// share, so the copies draw different random numbers. The default has
+    /// none to draw.
+    fn reseed(&mut self, _share: u64) {}
 }
 
-pub struct MinMaxOptions {}
+pub struct MinMaxOptions {
+    /// Plies searched below the root, at least 1. Leaves are scored by the
+    /// heuristic.
+    pub depth: u32,
+    /// When set, the best move is picked at random among all moves whose
+    /// score is at most this far below the best score, instead of always
+    /// playing the same one.
+    pub tie_epsilon: Option<i32>,
+    /// Probability of deliberately playing the second or third best move
+    /// instead of the best one, for a weaker but still human-like opponent.
+    pub mistake_probability: f64,
+    /// Stops scoring moves once a search has taken this long. The moves are
+    /// scored in random order, so on large boards the best move is picked
+    /// from a random sample of at least one move.
+    pub time_budget_ms: Option<f64>,
+}
 
 impl Default for MinMaxOptions {
     fn default() -> Self {
-        Self {}
+        Self {
+            depth: 1,
+            tie_epsilon: None,
+            mistake_probability: 0.0,
+            time_budget_ms: None,
+        }
     }
 }
 
+/// Moves of a state with their scores, from the point of view of the
+/// maximizing side.
+type ScoredMoves<S> = Vec<(<S as MinMaxState>::Move, i32)>;
+
+/// When the transposition table grows beyond this many positions, the
+/// entries not used by the last `TABLE_KEPT_SEARCHES` searches are dropped.
+pub const TABLE_CAPACITY: usize = 1 << 18;
+const TABLE_KEPT_SEARCHES: u32 = 2;
+
+/// Bound of the search window. Not `i32::MIN`, which cannot be negated.
+const INFINITY: i32 = i32::MAX;
+
+/// Searched positions by hash.
+type Table<S> = HashMap<u64, TableEntry<<S as MinMaxState>::Move>>;
+
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// Result of searching a position `depth` plies deep, from the point of
+/// view of the side to move. Searches cut off by the alpha-beta window only
+/// give a bound on the real value. Finished games are stored with the
+/// largest depth, as their value is exact.
+#[derive(Clone, Copy)]
+struct TableEntry<M> {
+    depth: u32,
+    value: i32,
+    bound: Bound,
+    /// Move that scored `value`, `None` for leaves of the search. Stored
+    /// as `MinMaxState::canonical_move`.
+    best_move: Option<M>,
+    /// Number of the last search that stored or used the entry.
+    generation: u32,
+}
+
 pub struct MinMax<I: MinMaxInterface> {
     game: I,
     root_state: I::State,
     options: MinMaxOptions,
+    /// Searched positions by hash, kept between searches and moves, so later
+    /// searches reuse the work of earlier ones.
+    table: Table<I::State>,
+    /// Number of the current search, for aging the table.
+    generation: u32,
+    last_stats: Option<SearchStats>,
 }
 
 impl<I: MinMaxInterface> MinMax<I> {
@@ -109,6 +254,9 @@ impl<I: MinMaxInterface> MinMax<I> {
             game,
             root_state,
             options: Default::default(),
+            table: HashMap::new(),
+            generation: 0,
+            last_stats: None,
         }
     }
 
@@ -120,20 +268,475 @@ impl<I: MinMaxInterface> MinMax<I> {
         self.options = options;
     }
 
-    pub fn best_move(&mut self) -> Option<<I::State as MinMaxState>::Move> {
-        let possible_moves = self.root_state.possible_moves().collect::<Vec<_>>();
-        possible_moves.into_iter().map(|mv| {
-            let mut state = self.root_state.checkpoint();
+    pub fn game_mut(&mut self) -> &mut I {
+        &mut self.game
+    }
+
+    /// Statistics of the last call to `score_moves` or
+    /// `score_moves_with_progress`.
+    pub fn last_stats(&self) -> Option<&SearchStats> {
+        self.last_stats.as_ref()
+    }
+
+    /// Replaces the statistics, for moves picked without searching.
+    pub fn set_last_stats(&mut self, stats: SearchStats) {
+        self.last_stats = Some(stats);
+    }
+
+    /// Legal moves from the root state with their minimax scores. With a
+    /// time budget, only the moves that were scored in time are included,
+    /// and subtrees still being searched when it runs out are cut short.
+    pub fn score_moves(&mut self) -> Vec<(<I::State as MinMaxState>::Move, i32)> {
+        self.score_moves_with_progress(&mut |_| {})
+    }
+
+    /// Like `score_moves`, and passes the best move scored so far to
+    /// `on_progress` whenever it changes.
+    pub fn score_moves_with_progress(
+        &mut self,
+        on_progress: &mut dyn FnMut(<I::State as MinMaxState>::Move),
+    ) -> Vec<(<I::State as MinMaxState>::Move, i32)> {
+        let start_ms = stats::now_ms();
+        let deadline_ms = self.options.time_budget_ms.map(|budget_ms| start_ms + budget_ms);
+        let mut possible_moves = self.root_state.possible_moves().collect::<Vec<_>>();
+        if deadline_ms.is_some() {
+            for i in (1..possible_moves.len()).rev() {
+                let j = self.game.choose_index(i + 1).min(i);
+                possible_moves.swap(i, j);
+            }
+        }
+        let depth = self.options.depth.max(1);
+        self.generation = self.generation.wrapping_add(1);
+        if self.table.len() > TABLE_CAPACITY {
+            // Positions of earlier moves are unlikely to come up again.
+            let generation = self.generation;
+            self.table.retain(|_, entry| {
+                generation.wrapping_sub(entry.generation) <= TABLE_KEPT_SEARCHES
+            });
+            if self.table.len() > TABLE_CAPACITY {
+                self.table.clear();
+            }
+        }
+        #[cfg(feature = "parallel")]
+        let (scored_moves, stats) =
+            self.search_root_parallel(possible_moves, depth, deadline_ms, on_progress);
+        #[cfg(not(feature = "parallel"))]
+        let (scored_moves, stats) =
+            self.search_root(possible_moves, depth, deadline_ms, on_progress);
+        self.last_stats = Some(SearchStats {
+            duration_ms: stats::now_ms() - start_ms,
+            ..stats
+        });
+        scored_moves
+    }
+
+    /// Score of `mv`, a legal move from the root state, as `score_moves`
+    /// would give it, but searched to the end whatever the time budget.
+    pub fn score_move(&mut self, mv: <I::State as MinMaxState>::Move) -> i32 {
+        let depth = self.options.depth.max(1);
+        let mut search = Search::new(&mut self.game, &mut self.table, self.generation, None, depth);
+        let scored_moves = search.score_root_moves(&mut self.root_state, vec![mv], &mut |_, _| {});
+        scored_moves[0].1
+    }
+
+    /// The line of play the last search of the root state expects after
+    /// `first`, a legal move from it: `first`, then the best replies stored
+    /// in the transposition table, for as long as their values are exact.
+    pub fn variation(
+        &mut self,
+        first: <I::State as MinMaxState>::Move,
+    ) -> Vec<<I::State as MinMaxState>::Move> {
+        let mut line = Vec::new();
+        let mut state = self.root_state.checkpoint();
+        state.apply(first);
+        line.push(first);
+        let mut seen = HashSet::new();
+        loop {
+            let hash = state.position_hash();
+            let entry = self.table.get(&hash).filter(|entry| matches!(entry.bound, Bound::Exact));
+            let Some(mv) = entry.and_then(|entry| entry.best_move) else {
+                break;
+            };
+            // Stored for whichever symmetric position was searched first.
+            let mv = state.move_from_canonical(mv);
+            // A hash collision could name a move of another position.
+            if !seen.insert(hash) || !state.possible_moves().any(|legal| legal == mv) {
+                break;
+            }
             state.apply(mv);
-            let real_state: &mut I::State = &mut state;
-            let heuristic = self.game.heuristic(real_state);
-            (mv, heuristic)
-        }).max_by_key(|tup| {
+            line.push(mv);
+        }
+        line
+    }
+
+    /// Scores `moves` from the root state one after the other.
+    fn search_root(
+        &mut self,
+        moves: Vec<<I::State as MinMaxState>::Move>,
+        depth: u32,
+        deadline_ms: Option<f64>,
+        on_progress: &mut dyn FnMut(<I::State as MinMaxState>::Move),
+    ) -> (ScoredMoves<I::State>, SearchStats) {
+        let mut search =
+            Search::new(&mut self.game, &mut self.table, self.generation, deadline_ms, depth);
+        let scored_moves =
+            search.score_root_moves(&mut self.root_state, moves, &mut |mv, _| on_progress(mv));
+        let stats = search.stats();
+        (scored_moves, stats)
+    }
+
+    /// Splits `moves` into one share per thread of rayon's pool, and scores
+    /// each share with its own copy of the interface and root state, and a
+    /// transposition table that starts empty. The entries the shares stored
+    /// are merged into the table afterwards. The current thread scores the
+    /// first share, and passes the best move of all shares so far to
+    /// `on_progress` as their scores come in.
+    #[cfg(feature = "parallel")]
+    fn search_root_parallel(
+        &mut self,
+        moves: Vec<<I::State as MinMaxState>::Move>,
+        depth: u32,
+        deadline_ms: Option<f64>,
+        on_progress: &mut dyn FnMut(<I::State as MinMaxState>::Move),
+    ) -> (ScoredMoves<I::State>, SearchStats) {
+        use std::sync::mpsc;
+
+        let threads = rayon::current_num_threads();
+        if threads < 2 || moves.len() < 2 {
+            return self.search_root(moves, depth, deadline_ms, on_progress);
+        }
+        let generation = self.generation;
+        let shares = (moves.chunks(moves.len().div_ceil(threads)).enumerate())
+            .map(|(share, moves)| {
+                let mut game = self.game.clone();
+                // Copies of one generator would play the same playouts.
+                game.reseed(share as u64);
+                (game, self.root_state.clone(), moves.to_vec())
+            })
+            .collect::<Vec<_>>();
+        let search_share = |(mut game, mut root_state, moves): (I, I::State, Vec<_>),
+                            on_progress: &mut dyn FnMut(_, i32)| {
+            let mut table = HashMap::new();
+            let mut search = Search::new(&mut game, &mut table, generation, deadline_ms, depth);
+            let scored_moves = search.score_root_moves(&mut root_state, moves, on_progress);
+            let stats = search.stats();
+            (scored_moves, stats, table)
+        };
+        let mut results = shares.iter().map(|_| None).collect::<Vec<_>>();
+        let mut best_score = None;
+        let mut report = |mv, score| {
+            if best_score.is_none_or(|best_score| score > best_score) {
+                best_score = Some(score);
+                on_progress(mv);
+            }
+        };
+        let (sender, receiver) = mpsc::channel();
+        rayon::in_place_scope(|scope| {
+            let mut shares = shares.into_iter().zip(results.iter_mut());
+            let (own_share, own_result) = shares.next().expect("at least one share");
+            for (share, result) in shares {
+                let sender = sender.clone();
+                scope.spawn(move |_| {
+                    let mut send = |mv, score| sender.send((mv, score)).unwrap_or(());
+                    *result = Some(search_share(share, &mut send));
+                });
+            }
+            *own_result = Some(search_share(own_share, &mut |mv, score| {
+                report(mv, score);
+                receiver.try_iter().for_each(|(mv, score)| report(mv, score));
+            }));
+        });
+        receiver.try_iter().for_each(|(mv, score)| report(mv, score));
+        let mut scored_moves = Vec::new();
+        let mut stats = SearchStats {
+            depth,
+            ..Default::default()
+        };
+        for (share_moves, share_stats, table) in results.into_iter().flatten() {
+            scored_moves.extend(share_moves);
+            stats.nodes += share_stats.nodes;
+            stats.table_hits += share_stats.table_hits;
+            stats.max_depth = stats.max_depth.max(share_stats.max_depth);
+            self.table.extend(table);
+        }
+        (scored_moves, stats)
+    }
+
+    /// Picks a move from the scores of `score_moves`, usually the best one.
+    /// It can differ, e.g. because of `MinMaxOptions::tie_epsilon` or
+    /// `MinMaxOptions::mistake_probability`.
+    pub fn pick_move(
+        &mut self,
+        mut scored_moves: ScoredMoves<I::State>,
+    ) -> Option<<I::State as MinMaxState>::Move> {
+        let mistake_probability = self.options.mistake_probability;
+        if mistake_probability > 0.0
+            && scored_moves.len() > 1
+            && self.game.chance(mistake_probability)
+        {
+            scored_moves.sort_by_key(|tup| {
+                let (_mv, heuristic) = *tup;
+                Reverse(heuristic)
+            });
+            let runners_up = &scored_moves[1..scored_moves.len().min(3)];
+            let index = self.game.choose_index(runners_up.len()).min(runners_up.len() - 1);
+            return Some(runners_up[index].0);
+        }
+        let epsilon = match self.options.tie_epsilon {
+            Some(epsilon) => epsilon,
+            None => {
+                return scored_moves.into_iter().max_by_key(|tup| {
+                    let (_mv, heuristic) = *tup;
+                    heuristic
+                }).map(|tup| {
+                    let (mv, _heuristic) = tup;
+                    mv
+                });
+            }
+        };
+        let best_heuristic = scored_moves.iter().map(|(_mv, heuristic)| *heuristic).max()?;
+        let candidates = scored_moves.into_iter().filter(|tup| {
             let (_mv, heuristic) = *tup;
-            heuristic
+            heuristic >= best_heuristic.saturating_sub(epsilon)
         }).map(|tup| {
             let (mv, _heuristic) = tup;
             mv
-        })
+        }).collect::<Vec<_>>();
+        let index = self.game.choose_index(candidates.len()).min(candidates.len() - 1);
+        Some(candidates[index])
+    }
+}
+
+/// What a value found with the window `alpha`..`beta` says about the real
+/// value.
+fn bound(value: i32, alpha: i32, beta: i32) -> Bound {
+    if value <= alpha {
+        Bound::Upper
+    } else if value >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    }
+}
+
+struct Search<'a, I: MinMaxInterface> {
+    game: &'a mut I,
+    table: &'a mut Table<I::State>,
+    generation: u32,
+    deadline_ms: Option<f64>,
+    /// Set once the time budget ran out. Values found after that come from
+    /// cut short subtrees and are not stored in the table.
+    timed_out: bool,
+    /// Plies searched at full width below the root.
+    depth: u32,
+    /// Deepest ply reached so far.
+    max_depth: u32,
+    nodes: u64,
+    table_hits: u64,
+}
+
+impl<'a, I: MinMaxInterface> Search<'a, I> {
+    fn new(
+        game: &'a mut I,
+        table: &'a mut Table<I::State>,
+        generation: u32,
+        deadline_ms: Option<f64>,
+        depth: u32,
+    ) -> Self {
+        Self {
+            game,
+            table,
+            generation,
+            deadline_ms,
+            timed_out: false,
+            depth,
+            max_depth: 0,
+            nodes: 0,
+            table_hits: 0,
+        }
+    }
+
+    /// Scores `moves` from `root_state` in order, from the point of view of
+    /// the maximizing side, and passes each move that beats the ones before
+    /// it to `on_progress` with its score. Stops early when the time budget runs out, once
+    /// at least one move is scored.
+    fn score_root_moves(
+        &mut self,
+        root_state: &mut I::State,
+        moves: Vec<<I::State as MinMaxState>::Move>,
+        on_progress: &mut dyn FnMut(<I::State as MinMaxState>::Move, i32),
+    ) -> Vec<(<I::State as MinMaxState>::Move, i32)> {
+        let mut scored_moves = Vec::with_capacity(moves.len());
+        let mut best_score = None;
+        for mv in moves {
+            let out_of_time =
+                self.deadline_ms.is_some_and(|deadline_ms| stats::now_ms() >= deadline_ms);
+            if out_of_time && !scored_moves.is_empty() {
+                break;
+            }
+            let mut state = root_state.checkpoint();
+            state.apply(mv);
+            let real_state: &mut I::State = &mut state;
+            // Each root move gets the full window, so that its score is
+            // exact and can be compared with the others.
+            let value = self.negamax(real_state, self.depth - 1, -INFINITY, INFINITY);
+            let score = if self.game.is_maximizing(real_state) {
+                value
+            } else {
+                -value
+            };
+            scored_moves.push((mv, score));
+            if best_score.is_none_or(|best_score| score > best_score) {
+                best_score = Some(score);
+                on_progress(mv, score);
+            }
+        }
+        scored_moves
+    }
+
+    /// Counters of the search so far, without its duration.
+    fn stats(&self) -> SearchStats {
+        SearchStats {
+            nodes: self.nodes,
+            table_hits: self.table_hits,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            duration_ms: 0.0,
+            solved: false,
+            principal_variation: Vec::new(),
+        }
+    }
+
+    /// Negamax value of `state` searched `depth` plies deep, from the
+    /// point of view of the side to move. A move only flips the point of
+    /// view if it passes the turn, as completing a box in dots and boxes
+    /// earns another move. Subtrees that cannot change the result given the
+    /// `alpha`..`beta` window are skipped, and so are positions already
+    /// searched deep enough.
+    fn negamax(
+        &mut self,
+        state: &mut I::State,
+        depth: u32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        self.max_depth = self.max_depth.max(self.depth - depth);
+        let hash = state.position_hash();
+        if let Some(entry) = self.table.get_mut(&hash).filter(|entry| entry.depth >= depth) {
+            self.table_hits += 1;
+            entry.generation = self.generation;
+            let entry = *entry;
+            match entry.bound {
+                Bound::Exact => return entry.value,
+                Bound::Lower => alpha = alpha.max(entry.value),
+                Bound::Upper => beta = beta.min(entry.value),
+            }
+            if alpha >= beta {
+                return entry.value;
+            }
+        }
+        self.check_deadline();
+        let maximizing = self.game.is_maximizing(state);
+        let to_move_view = |value: i32| if maximizing { value } else { -value };
+        let mut possible_moves = state.possible_moves().collect::<Vec<_>>();
+        if possible_moves.is_empty() {
+            let value = to_move_view(self.game.terminal_value(state));
+            self.store(hash, u32::MAX, value, Bound::Exact, None);
+            return value;
+        }
+        if self.timed_out {
+            return to_move_view(self.game.heuristic(state));
+        }
+        let (alpha_orig, beta_orig) = (alpha, beta);
+        if depth == 0 {
+            let value = self.quiescence(state, self.depth);
+            self.store(hash, depth, value, Bound::Exact, None);
+            return value;
+        }
+        self.game.order_moves(state, &mut possible_moves);
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        for mv in possible_moves {
+            let mut child = state.checkpoint();
+            child.apply(mv);
+            let real_child: &mut I::State = &mut child;
+            let score = if self.game.is_maximizing(real_child) == maximizing {
+                self.negamax(real_child, depth - 1, alpha, beta)
+            } else {
+                -self.negamax(real_child, depth - 1, -beta, -alpha)
+            };
+            if best_move.is_none() || score > best {
+                best = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(best);
+            if alpha >= beta {
+                break;
+            }
+        }
+        let best_move = best_move.map(|mv| state.canonical_move(mv));
+        self.store(hash, depth, best, bound(best, alpha_orig, beta_orig), best_move);
+        best
+    }
+
+    /// Value of `state` at the depth limit, from the point of view of the
+    /// side to move. The heuristic cannot judge a position in the middle
+    /// of a forcing sequence, e.g. half way through taking a chain, so
+    /// forcing moves are played out first. They usually commute and are
+    /// good for the side playing them, so only the first one is followed in
+    /// each position, instead of branching over all of them.
+    fn quiescence(&mut self, state: &mut I::State, ply: u32) -> i32 {
+        self.nodes += 1;
+        self.max_depth = self.max_depth.max(ply);
+        let maximizing = self.game.is_maximizing(state);
+        let to_move_view = |value: i32| if maximizing { value } else { -value };
+        let mut possible_moves = state.possible_moves().peekable();
+        if possible_moves.peek().is_none() {
+            return to_move_view(self.game.terminal_value(state));
+        }
+        let forcing = possible_moves.find(|mv| self.game.is_forcing(state, *mv));
+        let mv = match forcing {
+            Some(mv) if !self.check_deadline() => mv,
+            _ => return to_move_view(self.game.heuristic(state)),
+        };
+        let mut child = state.checkpoint();
+        child.apply(mv);
+        let real_child: &mut I::State = &mut child;
+        let value = self.quiescence(real_child, ply + 1);
+        if self.game.is_maximizing(real_child) == maximizing {
+            value
+        } else {
+            -value
+        }
+    }
+
+    /// Whether the time budget has run out.
+    fn check_deadline(&mut self) -> bool {
+        self.timed_out = self.timed_out
+            || self
+                .deadline_ms
+                .is_some_and(|deadline_ms| stats::now_ms() >= deadline_ms);
+        self.timed_out
+    }
+
+    fn store(
+        &mut self,
+        hash: u64,
+        depth: u32,
+        value: i32,
+        bound: Bound,
+        best_move: Option<<I::State as MinMaxState>::Move>,
+    ) {
+        if !self.timed_out {
+            let entry = TableEntry {
+                depth,
+                value,
+                bound,
+                best_move,
+                generation: self.generation,
+            };
+            self.table.insert(hash, entry);
+        }
     }
 }
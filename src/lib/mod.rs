@@ -1,33 +1,165 @@
+#[cfg(feature = "ai")]
 use web_sys::console;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::str::FromStr;
 use yew::html::IntoPropValue;
 
+use symmetry::MAX_SYMMETRIES;
+use worker::SearchRequest;
+use zobrist::toggle_keys;
+
+#[cfg(feature = "ai")]
 mod ai;
+#[cfg(feature = "ai")]
+mod arena;
+mod animation;
+mod audio;
+#[cfg(feature = "ai")]
+mod book;
+mod chains;
+mod clipboard;
+mod coins;
+mod config;
+#[cfg(feature = "debug")]
+mod dev_console;
+mod encoding;
+mod error_boundary;
+mod events;
+#[cfg(feature = "ai")]
+mod external;
+mod fen;
+#[cfg(feature = "ai")]
+mod fuzz;
+mod handicap;
+#[cfg(feature = "debug")]
+mod hot_reload;
+mod input;
+mod lattice;
+#[cfg(feature = "ai")]
+mod lattice_ai;
+mod mask;
+#[cfg(feature = "ai")]
 mod minmax;
+#[cfg(feature = "ai")]
+mod network;
+mod perft;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "ai")]
+mod protocol;
+mod record;
+mod scramble;
+mod share;
+mod stats;
+mod symmetry;
+mod teams;
+mod theme;
+#[cfg(feature = "ai")]
+mod tune;
+mod values;
+mod wake_lock;
+mod worker;
+mod zobrist;
 mod frontend;
 
-pub use frontend::AppComp;
+pub use chains::{Chain, ChainCell, ChainKind, ChainMistake, ChainParity};
+pub use coins::{CoinString, StringEnd};
+pub use config::{Config, Engine};
+pub use encoding::{decode_moves, encode_moves, DecodeError};
+pub use events::{EventSink, GameEvent, GameObserver, NoopEventSink};
+pub use fen::FenError;
+pub use handicap::Handicap;
+pub use lattice::{LatticeBoard, LatticeGame, LatticeKind, MAX_LAYERS};
+pub use mask::CellMask;
+pub use frontend::{AppComp, AppProps};
+pub use record::{GameRecord, RecordError, RecordedMove};
+pub use scramble::Scramble;
+pub use stats::SearchStats;
+pub use teams::{Participant, Teams};
+pub use theme::Theme;
+pub use values::CellValues;
+#[cfg(feature = "ai")]
+pub use arena::run_arena;
+#[cfg(feature = "ai")]
+pub use external::register_bot;
+#[cfg(feature = "ai")]
+pub use fuzz::{check_undo_exhaustive, fuzz_undo};
+#[cfg(feature = "ai")]
+pub use minmax::{
+    MinMax, MinMaxInterface, MinMaxOptions, MinMaxState, PossibleMovesIterator, TABLE_CAPACITY,
+};
+#[cfg(feature = "ai")]
+pub use network::Network;
+#[cfg(feature = "ai")]
+pub use protocol::run_engine;
+#[cfg(feature = "ai")]
+pub use tune::run_tune;
+#[cfg(feature = "ai")]
+pub use worker::run_ai_worker;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BarDirection {
     Vertical,
     Horizontal,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BarId {
     pub direction: BarDirection,
     pub col: u32,
     pub row: u32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Short notation for people, e.g. `h2.1` for the horizontal bar in
+/// column 2 and row 1.
+impl fmt::Display for BarId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let direction = match self.direction {
+            BarDirection::Horizontal => 'h',
+            BarDirection::Vertical => 'v',
+        };
+        write!(f, "{}{}.{}", direction, self.col, self.row)
+    }
+}
+
+impl FromStr for BarId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (direction, rest) = if let Some(rest) = s.strip_prefix('h') {
+            (BarDirection::Horizontal, rest)
+        } else {
+            (BarDirection::Vertical, s.strip_prefix('v').ok_or(())?)
+        };
+        let (col, row) = rest.split_once('.').ok_or(())?;
+        Ok(BarId {
+            direction,
+            col: col.parse().map_err(|_| ())?,
+            row: row.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CellState {
     Free,
     Player(Player),
+    /// Owned by neither player from the start: a bar drawn before the game,
+    /// see `Ruleset::swedish`, or a box left out of the board by its
+    /// `CellMask`, along with the bars only it has.
+    Neutral,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Player {
     Red,
     Blue,
@@ -59,6 +191,165 @@ impl fmt::Display for CellState {
         match self {
             CellState::Free => write!(f, "Free"),
             CellState::Player(player) => write!(f, "{}", player),
+            CellState::Neutral => write!(f, "Neutral"),
+        }
+    }
+}
+
+/// Strength of the AI opponent. See `From<Difficulty> for ai::AIOptions`
+/// for what each level means.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    #[default]
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string() == s)
+            .ok_or(())
+    }
+}
+
+/// Which sides the AI plays.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameMode {
+    /// Red is played by a human, Blue by the AI.
+    #[default]
+    HumanVsAI,
+    /// The AI plays both sides, for watching it play itself.
+    AIVsAI,
+    /// Two humans take turns on the same device, without any AI.
+    HotSeat,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::HumanVsAI, GameMode::AIVsAI, GameMode::HotSeat];
+
+    pub fn ai_plays(&self, player: Player) -> bool {
+        match self {
+            GameMode::HumanVsAI => player == Player::Blue,
+            GameMode::AIVsAI => true,
+            GameMode::HotSeat => false,
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            GameMode::HumanVsAI => write!(f, "Human vs AI"),
+            GameMode::AIVsAI => write!(f, "AI vs AI"),
+            GameMode::HotSeat => write!(f, "Human vs human"),
+        }
+    }
+}
+
+/// Variations on the standard rules a game can be played by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct Ruleset {
+    /// Misère play: the player with the fewest boxes wins.
+    pub misere: bool,
+    /// Swedish board: the whole border is drawn when the game starts, by
+    /// neither player.
+    pub swedish: bool,
+    /// Turns always alternate: completing a box does not earn another
+    /// move.
+    pub alternate_turns: bool,
+    /// The board wraps around like a torus: the boxes of the first and last
+    /// columns share a bar, as do those of the first and last rows. The
+    /// last column and row of bars are the first ones again, see
+    /// `BoardState::wrap_bar`.
+    pub torus: bool,
+}
+
+impl Ruleset {
+    /// Whether a board of `width` by `height` dots can be played by these
+    /// rules. A Swedish board needs a bar inside the border, and a torus
+    /// two boxes each way, so no box wraps around onto itself.
+    pub fn is_playable(&self, width: u32, height: u32) -> bool {
+        let single_box = self.swedish && width == 2 && height == 2;
+        let wraps_onto_itself = self.torus && width.min(height) < 3;
+        !single_box && !wraps_onto_itself
+    }
+
+    /// What a box is worth to its owner: 1, or -1 in misère play.
+    pub fn box_value(&self) -> i32 {
+        if self.misere {
+            -1
+        } else {
+            1
+        }
+    }
+}
+
+/// Everything a game is set up with, for starting one with
+/// `GameTrait::reconfigure`. Games without an AI only use the board and the
+/// starting player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// Width in dots.
+    pub width: u32,
+    /// Height in dots.
+    pub height: u32,
+    /// The boxes played, of the same size, `None` for the whole rectangle.
+    pub mask: Option<CellMask>,
+    /// Points of each box, of the same size, `None` for a point each.
+    pub values: Option<CellValues>,
+    pub starting_player: Player,
+    pub rules: Ruleset,
+    /// Head start of the weaker player, `None` for an even game.
+    pub handicap: Option<Handicap>,
+    /// Random bars played before the game, `None` for an empty board.
+    pub scramble: Option<Scramble>,
+    /// Four participants playing 2v2, `None` for a game between two
+    /// players.
+    pub teams: Option<Teams>,
+    /// Which sides the AI plays.
+    pub mode: GameMode,
+    pub engine: Engine,
+    /// Strength of the AI on each side, Red first.
+    pub difficulties: [Difficulty; 2],
+}
+
+impl GameConfig {
+    /// A game on a board of `width` by `height` dots with the defaults of
+    /// `GameBuilder`.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            mask: None,
+            values: None,
+            starting_player: Player::Red,
+            rules: Ruleset::default(),
+            handicap: None,
+            scramble: None,
+            teams: None,
+            mode: GameMode::default(),
+            engine: Engine::default(),
+            difficulties: [Difficulty::default(); 2],
         }
     }
 }
@@ -72,129 +363,963 @@ impl fmt::Display for Player {
     }
 }
 
+/// Why a bar cannot be played, see `BoardState::validate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BarError {
+    /// The bar does not lie on the board.
+    OutOfBounds,
+    /// The bar is already drawn.
+    Taken,
+}
+
+impl fmt::Display for BarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            BarError::OutOfBounds => write!(f, "bar outside the board"),
+            BarError::Taken => write!(f, "bar already drawn"),
+        }
+    }
+}
+
+/// Why a game cannot be set up as asked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameError {
+    /// The width or height is outside `MIN_BOARD_SIZE..=MAX_BOARD_SIZE`.
+    InvalidSize(u32, u32),
+    /// The move with this index cannot be played, for this reason.
+    IllegalMove(usize, BarError),
+    /// The rules cannot be played on a board of this width and height,
+    /// see `Ruleset::is_playable`, or on its boxes.
+    UnplayableRules(u32, u32),
+    /// The mask does not fit the board size, or leaves no box to play.
+    InvalidMask,
+    /// The box values do not fit the board size.
+    InvalidValues,
+    /// The handicap gives more boxes or bars than the board has.
+    InvalidHandicap,
+    /// A 3D lattice board stacks fewer than 2 or more than `MAX_LAYERS`
+    /// grids.
+    InvalidLayers(u32),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            GameError::InvalidSize(width, height) => {
+                write!(f, "unsupported board size {}x{}", width, height)
+            }
+            GameError::IllegalMove(index, err) => write!(f, "move {}: {}", index + 1, err),
+            GameError::UnplayableRules(width, height) => {
+                write!(f, "rules not playable on a {}x{} board", width, height)
+            }
+            GameError::InvalidMask => write!(f, "mask does not fit the board"),
+            GameError::InvalidValues => write!(f, "box values do not fit the board"),
+            GameError::InvalidHandicap => write!(f, "handicap larger than the board"),
+            GameError::InvalidLayers(layers) => write!(f, "unsupported layer count {}", layers),
+        }
+    }
+}
+
+/// A move of a game's history, see `GameTrait::history`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayedMove {
+    pub player: Player,
+    pub bar: BarId,
+    /// Boxes the move completed, as column and row.
+    pub completed: Vec<(u32, u32)>,
+    /// When the move was played, in milliseconds since the Unix epoch.
+    pub timestamp_ms: f64,
+}
+
+/// What playing a bar did to the game.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveOutcome {
+    /// The bar cannot be played. Nothing changed.
+    Illegal(BarError),
+    Played {
+        /// Boxes the bar completed, as column and row, at most two. They
+        /// earn the player another move.
+        completed: Vec<(u32, u32)>,
+        /// Whether the other side moves next.
+        turn_changed: bool,
+        /// Whether the bar took the last box.
+        game_over: bool,
+    },
+}
+
+impl MoveOutcome {
+    pub fn is_legal(&self) -> bool {
+        !matches!(self, MoveOutcome::Illegal(_))
+    }
+
+    /// Boxes the bar completed, none if it was illegal.
+    pub fn completed(&self) -> &[(u32, u32)] {
+        match self {
+            MoveOutcome::Illegal(_) => &[],
+            MoveOutcome::Played { completed, .. } => completed,
+        }
+    }
+
+    pub fn game_over(&self) -> bool {
+        matches!(self, MoveOutcome::Played { game_over: true, .. })
+    }
+}
+
+/// Supported number of dots along each side of the board.
+pub const MIN_BOARD_SIZE: u32 = 2;
+pub const MAX_BOARD_SIZE: u32 = 50;
+
+/// How long the AI may think about one move. Small boards finish well
+/// within it, large ones score a random sample of the moves.
+#[cfg(feature = "ai")]
+const AI_TIME_BUDGET_MS: f64 = 100.0;
+/// Plies the AI looks ahead on `Difficulty::Hard`. Three plies take about
+/// 30 ms per move on a 4x4 board.
+#[cfg(feature = "ai")]
+const AI_SEARCH_DEPTH: u32 = 3;
+
+/// The AI's assessment of a position, see `GameTrait::evaluate`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Evaluation {
+    /// Red's expected lead at the end of the game, counting the boxes
+    /// taken so far: its boxes minus Blue's, or the other way around in
+    /// misère play, see `Ruleset::box_value`.
+    pub margin: i32,
+    /// Whether `margin` is the result with perfect play, the rest of the
+    /// game having been solved.
+    pub exact: bool,
+}
+
+/// Spread of the final margin around an estimated one, per square root of
+/// the boxes still open. Fitted on self-play between AIs on boards of 4x4
+/// and 5x5 boxes.
+const MARGIN_SPREAD: f64 = 2.0;
+
+impl Evaluation {
+    /// Chances of `player` winning the game in `board`, of which this is
+    /// the evaluation, counting a draw as half a win. Exact evaluations
+    /// give certainties; otherwise the margin is mapped through a logistic
+    /// curve that flattens with the boxes still open, as more of them
+    /// leave more room for the estimate to be off.
+    pub fn win_probability(&self, board: &BoardState, player: Player) -> f64 {
+        let margin = match player {
+            Player::Red => self.margin,
+            Player::Blue => -self.margin,
+        };
+        let open = board.cellstates.iter().filter(|state| **state == CellState::Free).count();
+        if self.exact || open == 0 {
+            return match margin.cmp(&0) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+        }
+        let spread = MARGIN_SPREAD * (open as f64).sqrt();
+        1.0 / (1.0 + (-margin as f64 / spread).exp())
+    }
+}
+
 pub trait GameTrait {
+    /// Panics if a size is outside `MIN_BOARD_SIZE..=MAX_BOARD_SIZE`.
     fn new(width: u32, height: u32) -> Self;
-    fn do_move(&mut self, bar: BarId) -> bool;
+
+    /// Plays `bar` for the side to move and lets the AI respond. Returns
+    /// the outcome of `bar` itself.
+    fn do_move(&mut self, bar: BarId) -> MoveOutcome {
+        let outcome = self.do_single_move(bar);
+        if outcome.is_legal() {
+            self.play_ai_moves();
+        }
+        outcome
+    }
+
+    /// Plays `bar` for the side to move, without letting the AI respond.
+    fn do_single_move(&mut self, bar: BarId) -> MoveOutcome;
+
+    /// Lets the AI play on this thread until it is no longer its turn.
+    fn play_ai_moves(&mut self) {
+        while self.play_ai_move().is_some() {}
+    }
+
+    /// Plays one move for the side to move if the AI plays it. Returns its
+    /// outcome, `None` if the AI did not move.
+    fn play_ai_move(&mut self) -> Option<MoveOutcome> {
+        None
+    }
+
+    /// Whether the side to move is played by the AI, and the game is not
+    /// over yet.
+    fn ai_to_move(&self) -> bool {
+        false
+    }
+
+    /// The search the AI has to run if it is its turn, for running it
+    /// elsewhere and playing the result with `do_single_move`.
+    fn pending_search(&self) -> Option<SearchRequest> {
+        None
+    }
+
+    /// Registers `observer` to be told about every move played from now on,
+    /// see `GameObserver`.
+    fn add_observer(&mut self, observer: Rc<dyn GameObserver>);
+
+    /// Takes back the last move, giving back the boxes it took and the
+    /// turn. Against the AI, also takes back the AI's moves since, so the
+    /// human is to move again. Returns whether there was a move to take
+    /// back.
+    fn undo(&mut self) -> bool;
+
+    /// Plays again the moves the last `undo` took back. Returns whether
+    /// there were any.
+    fn redo(&mut self) -> bool;
+
+    fn can_undo(&self) -> bool {
+        !self.history().is_empty()
+    }
+
+    fn can_redo(&self) -> bool;
+
+    /// The moves played since the game started, or since the position was
+    /// replaced, without the moves taken back.
+    fn history(&self) -> &[PlayedMove];
+
     fn restart(&mut self, starting_player: Player);
-    fn get_width(&self) -> u32;
-    fn get_height(&self) -> u32;
-    fn vertical_get(&self, col: u32, row: u32) -> CellState;
-    fn horizontal_get(&self, col: u32, row: u32) -> CellState;
-    fn cell_get(&self, col: u32, row: u32) -> CellState;
+
+    /// Starts a new game as `config` says, possibly on a board of another
+    /// size or against other AIs. The observers stay. Fails, leaving the
+    /// game as it was, if the size is not supported.
+    fn reconfigure(&mut self, config: GameConfig) -> Result<(), GameError>;
+
+    fn board(&self) -> &BoardState;
+    /// Replaces the current position, e.g. with one loaded from a FEN
+    /// string. The AI does not move until the next `do_move`.
+    fn set_board(&mut self, board: BoardState);
+
+    /// Sets which sides the AI plays. Games without an AI ignore it, and
+    /// team games go by their `Teams` instead.
+    fn set_mode(&mut self, _mode: GameMode) {}
+
+    /// The setup of a 2v2 game, `None` for a game between two players.
+    fn teams(&self) -> Option<Teams>;
+
+    /// Turns the game into a 2v2 game, or back into one between two
+    /// players, from the next move on.
+    fn set_teams(&mut self, teams: Option<Teams>);
+
+    /// The participant to move in a team game, `None` in a game between
+    /// two players. Counted from the `history`, so a position set with
+    /// `set_board` starts with the first member of the side to move.
+    fn participant_to_move(&self) -> Option<Participant> {
+        self.teams()?;
+        Some(teams::participant_to_move(self.history(), self.current_turn()))
+    }
+
+    /// Sets the engine of the AIs. Games without an AI ignore it.
+    fn set_engine(&mut self, _engine: Engine) {}
+
+    /// Sets the strength of the AI playing `player`, from its next move on.
+    /// Games without an AI ignore it.
+    fn set_difficulty(&mut self, _player: Player, _difficulty: Difficulty) {}
+
+    /// Checks `bar` before it is played by the side to move, and returns a
+    /// clearly better move if there is one. Games without an AI have
+    /// nothing to compare against.
+    fn review_move(&mut self, _bar: BarId) -> Option<BarId> {
+        None
+    }
+
+    /// Statistics of the AI's most recent search, if there was one.
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        None
+    }
+
+    /// Keeps the statistics of a search run elsewhere, e.g. in the AI
+    /// worker, for `last_search_stats`. Games without an AI ignore them.
+    fn record_search_stats(&mut self, _stats: Option<SearchStats>) {}
+
+    /// A good move for the side to move when a human plays it, from the
+    /// AI's point of view. Games without an AI have no suggestions.
+    fn suggest_move(&mut self) -> Option<BarId> {
+        None
+    }
+
+    /// The AI's assessment of the position, from Red's perspective. Games
+    /// without an AI only count the boxes taken, which is exact once the
+    /// game is over.
+    fn evaluate(&mut self) -> Evaluation {
+        let board = self.board();
+        Evaluation {
+            margin: score_balance(board),
+            exact: board.is_finished(),
+        }
+    }
+
+    /// Chances of `player` winning from the current position, counting a
+    /// draw as half a win, see `Evaluation::win_probability`.
+    fn win_probability(&mut self, player: Player) -> f64 {
+        self.evaluate().win_probability(self.board(), player)
+    }
+
+    /// Whether the AI has solved the rest of the game and plays a proven
+    /// line, see `SearchStats::solved`.
+    fn endgame_solved(&self) -> bool {
+        self.last_search_stats().is_some_and(|stats| stats.solved)
+    }
+
+    /// Points `player` has: the boxes they own, each counting its value,
+    /// see `BoardState::cell_value`.
+    fn score(&self, player: Player) -> u32 {
+        self.board().points(player)
+    }
+
+    /// The side to move.
+    fn current_turn(&self) -> Player {
+        self.board().cur_turn
+    }
+
+    /// The result once every box is taken: `Some(Some(player))` if
+    /// `player` won, `Some(None)` for a draw, and `None` while the game
+    /// goes on. In misère play, the player with fewer boxes wins.
+    fn winner(&self) -> Option<Option<Player>> {
+        if !self.board().is_finished() {
+            return None;
+        }
+        Some(match score_balance(self.board()).cmp(&0) {
+            Ordering::Greater => Some(Player::Red),
+            Ordering::Less => Some(Player::Blue),
+            Ordering::Equal => None,
+        })
+    }
+
+    fn get_width(&self) -> u32 {
+        self.board().width
+    }
+
+    fn get_height(&self) -> u32 {
+        self.board().height
+    }
+
+    fn vertical_get(&self, col: u32, row: u32) -> CellState {
+        self.board().vstates.get(col, row)
+    }
+
+    fn horizontal_get(&self, col: u32, row: u32) -> CellState {
+        self.board().hstates.get(col, row)
+    }
+
+    fn cell_get(&self, col: u32, row: u32) -> CellState {
+        self.board().cell_get(col, row)
+    }
 }
 
-pub struct Game<AI: ai::AI> {
+/// Game between two humans sharing one device, without any AI.
+pub struct HotSeatGame {
     board: BoardState,
-    ai: AI,
-    ai_player: Player,
+    teams: Option<Teams>,
+    log: MoveLog,
 }
 
-impl<AI: ai::AI> GameTrait for Game<AI> {
+impl GameTrait for HotSeatGame {
     fn new(width: u32, height: u32) -> Self {
-        let board = BoardState::new(width, height);
-        let ai_player = Player::Blue;
-        let ai = AI::new(&board, ai_player);
-        Self { board , ai, ai_player }
+        Self {
+            board: BoardState::new(width, height),
+            teams: None,
+            log: MoveLog::default(),
+        }
     }
 
-    fn do_move(&mut self, bar: BarId) -> bool {
-        let player_move_success = self.board.do_move(bar);
-        if !player_move_success {
+    fn do_single_move(&mut self, bar: BarId) -> MoveOutcome {
+        self.log.play(&mut self.board, bar)
+    }
+
+    fn add_observer(&mut self, observer: Rc<dyn GameObserver>) {
+        self.log.observers.push(observer);
+    }
+
+    fn undo(&mut self) -> bool {
+        self.log.undo(&mut self.board).is_some()
+    }
+
+    fn redo(&mut self) -> bool {
+        self.log.redo(&mut self.board).is_some()
+    }
+
+    fn can_redo(&self) -> bool {
+        !self.log.undone.is_empty()
+    }
+
+    fn history(&self) -> &[PlayedMove] {
+        &self.log.played
+    }
+
+    fn restart(&mut self, starting_player: Player) {
+        self.board.restart(starting_player);
+        self.log.clear();
+    }
+
+    fn reconfigure(&mut self, config: GameConfig) -> Result<(), GameError> {
+        let board = BoardState::new_game(&config)?;
+        self.set_board(board);
+        self.teams = config.teams;
+        Ok(())
+    }
+
+    fn board(&self) -> &BoardState {
+        &self.board
+    }
+
+    fn set_board(&mut self, board: BoardState) {
+        self.board = board;
+        self.log.clear();
+    }
+
+    fn teams(&self) -> Option<Teams> {
+        self.teams
+    }
+
+    fn set_teams(&mut self, teams: Option<Teams>) {
+        self.teams = teams;
+    }
+}
+
+#[cfg(feature = "ai")]
+pub struct Game {
+    board: BoardState,
+    /// Engine of the AIs, which can change in the middle of a game.
+    engine: Engine,
+    /// The AI playing each side, Red first, or `None` for a human. In a
+    /// team game, the AI of a side plays the members `teams` gives it.
+    ais: [Option<Box<dyn ai::AI>>; 2],
+    teams: Option<Teams>,
+    difficulties: [Difficulty; 2],
+    /// Statistics of the AI's most recent search.
+    last_stats: Option<SearchStats>,
+    /// AIs suggesting moves to the human on each side, made on the first
+    /// request and kept so later ones reuse their searches.
+    advisors: [Option<Box<dyn ai::AI>>; 2],
+    log: MoveLog,
+}
+
+#[cfg(feature = "ai")]
+impl GameTrait for Game {
+    /// A game with the defaults of `GameBuilder`.
+    fn new(width: u32, height: u32) -> Self {
+        GameBuilder::new(width, height)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn do_single_move(&mut self, bar: BarId) -> MoveOutcome {
+        self.log.play(&mut self.board, bar)
+    }
+
+    fn play_ai_move(&mut self) -> Option<MoveOutcome> {
+        if !self.ai_to_move() {
+            return None;
+        }
+        let player = self.board.cur_turn;
+        let ai = self.ais[seat(player)].as_mut().expect("no AI to move");
+        let ai_move = ai.next_move(&self.board);
+        self.last_stats = ai.last_search_stats().cloned();
+        let outcome = ai_move.map(|ai_move| self.log.play(&mut self.board, ai_move));
+        if !outcome.as_ref().is_some_and(MoveOutcome::is_legal) {
+            console::error_1(&format!("AI move failed: {:?}", ai_move).into());
+            return None;
+        }
+        outcome
+    }
+
+    fn ai_to_move(&self) -> bool {
+        let ai_plays = match (self.teams, self.participant_to_move()) {
+            (Some(teams), Some(participant)) => teams.ai_plays(participant),
+            _ => self.ais[seat(self.board.cur_turn)].is_some(),
+        };
+        ai_plays && !self.board.is_finished()
+    }
+
+    fn pending_search(&self) -> Option<SearchRequest> {
+        self.ai_to_move().then(|| SearchRequest {
+            engine: self.engine,
+            difficulty: self.difficulties[seat(self.board.cur_turn)],
+            board: self.board.clone(),
+        })
+    }
+
+    fn add_observer(&mut self, observer: Rc<dyn GameObserver>) {
+        self.log.observers.push(observer);
+    }
+
+    /// Stops after a human's move, or after one move if the AI plays both
+    /// sides.
+    fn undo(&mut self) -> bool {
+        let mut undone = false;
+        while self.log.undo(&mut self.board).is_some() {
+            undone = true;
+            self.last_stats = None;
+            if !self.ai_to_move() || self.ai_plays_all() {
+                break;
+            }
+        }
+        undone
+    }
+
+    /// Replays the AI's moves after the human's too, as they were played.
+    fn redo(&mut self) -> bool {
+        if self.log.redo(&mut self.board).is_none() {
             return false;
         }
-        while self.board.cur_turn == self.ai_player {
-            let ai_move = self.ai.next_move(&self.board);
-            let ai_move_success = if let Some(ai_move) = ai_move {
-                self.board.do_move(ai_move)
-            } else {
-                false
-            };
-            if !ai_move_success {
-                console::error_1(&format!("AI move failed: {:?}", ai_move).into());
+        self.last_stats = None;
+        while self.ai_to_move() && !self.ai_plays_all() {
+            if self.log.redo(&mut self.board).is_none() {
                 break;
             }
         }
         true
     }
 
+    fn can_redo(&self) -> bool {
+        !self.log.undone.is_empty()
+    }
+
+    fn history(&self) -> &[PlayedMove] {
+        &self.log.played
+    }
+
     fn restart(&mut self, starting_player: Player) {
-        self.board.restart(starting_player)
+        self.board.restart(starting_player);
+        self.last_stats = None;
+        self.log.clear();
     }
 
-    fn get_width(&self) -> u32 {
-        self.board.width
+    /// Replaces the AIs and the advisors, which know the old board.
+    fn reconfigure(&mut self, config: GameConfig) -> Result<(), GameError> {
+        let mut game = GameBuilder::from(config).build()?;
+        game.log.observers = std::mem::take(&mut self.log.observers);
+        *self = game;
+        Ok(())
     }
 
-    fn get_height(&self) -> u32 {
-        self.board.height
+    fn board(&self) -> &BoardState {
+        &self.board
     }
-    fn vertical_get(&self, col: u32, row: u32) -> CellState {
-        self.board.vstates.get(col, row)
+
+    /// Forgets the last search, which was of another position, and the
+    /// moves to undo.
+    fn set_board(&mut self, board: BoardState) {
+        self.board = board;
+        self.last_stats = None;
+        self.log.clear();
     }
 
-    fn horizontal_get(&self, col: u32, row: u32) -> CellState {
-        self.board.hstates.get(col, row)
+    fn set_mode(&mut self, mode: GameMode) {
+        for player in [Player::Red, Player::Blue] {
+            let ai_plays = match self.teams {
+                Some(teams) => teams.ai_plays_in(player),
+                None => mode.ai_plays(player),
+            };
+            let ai = &mut self.ais[seat(player)];
+            if !ai_plays {
+                *ai = None;
+            } else if ai.is_none() {
+                self.ais[seat(player)] = Some(self.new_ai(player));
+            }
+        }
     }
 
-    fn cell_get(&self, col: u32, row: u32) -> CellState {
-        self.board.cell_get(col, row)
+    fn teams(&self) -> Option<Teams> {
+        self.teams
+    }
+
+    /// The AIs of the sides are kept until the next `set_mode`, which
+    /// gives the AI the participants of `teams`.
+    fn set_teams(&mut self, teams: Option<Teams>) {
+        self.teams = teams;
+    }
+
+    /// The new AIs take over from the current position, with the same
+    /// difficulties. The advisors are replaced too.
+    fn set_engine(&mut self, engine: Engine) {
+        if engine == self.engine {
+            return;
+        }
+        self.engine = engine;
+        self.advisors = [None, None];
+        for player in [Player::Red, Player::Blue] {
+            if self.ais[seat(player)].is_some() {
+                self.ais[seat(player)] = Some(self.new_ai(player));
+            }
+        }
+    }
+
+    fn set_difficulty(&mut self, player: Player, difficulty: Difficulty) {
+        self.difficulties[seat(player)] = difficulty;
+        if let Some(ai) = &mut self.ais[seat(player)] {
+            ai.set_options(difficulty.into());
+        }
+    }
+
+    /// Reviews with the hardest difficulty, like `suggest_move`, so moves
+    /// are judged the same against any opponent.
+    fn review_move(&mut self, bar: BarId) -> Option<BarId> {
+        if self.ai_to_move() || self.board.validate(bar).is_err() {
+            return None;
+        }
+        Self::advisor(&mut self.advisors, self.engine, &self.board).review_move(&self.board, bar)
+    }
+
+    /// Searches with the hardest difficulty, whatever the opponent's. The
+    /// statistics of the search are not kept.
+    fn suggest_move(&mut self) -> Option<BarId> {
+        if self.ai_to_move() || self.board.is_finished() {
+            return None;
+        }
+        Self::advisor(&mut self.advisors, self.engine, &self.board).next_move(&self.board)
+    }
+
+    /// Asks the AI for the side to move, like `suggest_move`.
+    fn evaluate(&mut self) -> Evaluation {
+        Self::advisor(&mut self.advisors, self.engine, &self.board).evaluate(&self.board)
+    }
+
+    /// Asks the AI for the side to move, like `suggest_move`.
+    fn win_probability(&mut self, player: Player) -> f64 {
+        Self::advisor(&mut self.advisors, self.engine, &self.board)
+            .win_probability(&self.board, player)
+    }
+
+    fn last_search_stats(&self) -> Option<&SearchStats> {
+        self.last_stats.as_ref()
+    }
+
+    fn record_search_stats(&mut self, stats: Option<SearchStats>) {
+        self.last_stats = stats;
+    }
+}
+
+#[cfg(feature = "ai")]
+impl Game {
+    /// Whether the AI plays every side, or in a team game every
+    /// participant, so no human is waiting for their turn.
+    fn ai_plays_all(&self) -> bool {
+        match self.teams {
+            Some(teams) => teams.ai_plays_all(),
+            None => self.ais.iter().all(Option::is_some),
+        }
+    }
+
+    /// An AI of the game's engine playing `player`, with its difficulty.
+    fn new_ai(&self, player: Player) -> Box<dyn ai::AI> {
+        let mut ai = ai::new_ai(self.engine, &self.board, player);
+        ai.set_options(self.difficulties[seat(player)].into());
+        ai
+    }
+
+    /// The AI among `advisors` advising the side to move in `board`, made
+    /// on first use with `engine`.
+    fn advisor<'a>(
+        advisors: &'a mut [Option<Box<dyn ai::AI>>; 2],
+        engine: Engine,
+        board: &BoardState,
+    ) -> &'a mut dyn ai::AI {
+        let player = board.cur_turn;
+        let advisor = advisors[seat(player)].get_or_insert_with(|| {
+            let mut advisor = ai::new_ai(engine, board, player);
+            advisor.set_options(Difficulty::Hard.into());
+            advisor
+        });
+        advisor.as_mut()
     }
 }
 
-#[derive(Clone)]
+/// Sets up a `Game`, e.g.
+/// `GameBuilder::new(5, 5).mode(GameMode::AIvsAI).engine(Engine::Mcts).build()`.
+/// Unless told otherwise, Red moves first and a human plays Red against the
+/// default engine on the default difficulty.
+#[cfg(feature = "ai")]
+#[derive(Clone, Debug)]
+pub struct GameBuilder {
+    config: GameConfig,
+}
+
+#[cfg(feature = "ai")]
+impl GameBuilder {
+    /// A game on a board of `width` by `height` dots.
+    pub fn new(width: u32, height: u32) -> Self {
+        GameConfig::new(width, height).into()
+    }
+
+    pub fn starting_player(mut self, player: Player) -> Self {
+        self.config.starting_player = player;
+        self
+    }
+
+    pub fn rules(mut self, rules: Ruleset) -> Self {
+        self.config.rules = rules;
+        self
+    }
+
+    /// Gives the weaker player a head start.
+    pub fn handicap(mut self, handicap: Handicap) -> Self {
+        self.config.handicap = Some(handicap);
+        self
+    }
+
+    /// Starts from a position with random bars played, see
+    /// `BoardState::scramble`.
+    pub fn scramble(mut self, scramble: Scramble) -> Self {
+        self.config.scramble = Some(scramble);
+        self
+    }
+
+    /// Plays a 2v2 game, the AI playing the participants `teams` gives it
+    /// instead of the sides of the mode.
+    pub fn teams(mut self, teams: Teams) -> Self {
+        self.config.teams = Some(teams);
+        self
+    }
+
+    /// Plays only the boxes in `mask`, which has the size of the board.
+    pub fn mask(mut self, mask: CellMask) -> Self {
+        self.config.mask = Some(mask);
+        self
+    }
+
+    /// Counts each box for the points in `values`, which has the size of the
+    /// board.
+    pub fn values(mut self, values: CellValues) -> Self {
+        self.config.values = Some(values);
+        self
+    }
+
+    /// Which sides the AI plays.
+    pub fn mode(mut self, mode: GameMode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.config.engine = engine;
+        self
+    }
+
+    /// Strength of the AI if it plays `player`.
+    pub fn difficulty(mut self, player: Player, difficulty: Difficulty) -> Self {
+        self.config.difficulties[seat(player)] = difficulty;
+        self
+    }
+
+    /// Fails if the board size is not supported, or the mask, the values,
+    /// the rules or the handicap do not fit it.
+    pub fn build(self) -> Result<Game, GameError> {
+        let board = BoardState::new_game(&self.config)?;
+        let GameConfig {
+            mode,
+            teams,
+            engine,
+            difficulties,
+            ..
+        } = self.config;
+        let mut game = Game {
+            board,
+            engine,
+            ais: [None, None],
+            teams,
+            difficulties,
+            last_stats: None,
+            advisors: [None, None],
+            log: MoveLog::default(),
+        };
+        game.set_mode(mode);
+        Ok(game)
+    }
+}
+
+/// A builder starting from `config`, to change some of it.
+#[cfg(feature = "ai")]
+impl From<GameConfig> for GameBuilder {
+    fn from(config: GameConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// What the games keep besides the board: the moves played since the game
+/// or position started, the moves taken back since, and the observers of
+/// the moves.
+#[derive(Default)]
+struct MoveLog {
+    played: Vec<PlayedMove>,
+    /// The last move taken back last.
+    undone: Vec<BarId>,
+    observers: Vec<Rc<dyn GameObserver>>,
+}
+
+impl MoveLog {
+    /// Plays `bar` on `board` for the side to move. A new move forgets the
+    /// moves taken back.
+    fn play(&mut self, board: &mut BoardState, bar: BarId) -> MoveOutcome {
+        let outcome = self.play_observed(board, bar);
+        if outcome.is_legal() {
+            self.undone.clear();
+        }
+        outcome
+    }
+
+    /// Takes back the last move. Returns the player who had played it.
+    fn undo(&mut self, board: &mut BoardState) -> Option<Player> {
+        let bar = self.played.pop()?.bar;
+        let player = board.unplay(bar)?;
+        self.undone.push(bar);
+        Some(player)
+    }
+
+    /// Plays the last move taken back again.
+    fn redo(&mut self, board: &mut BoardState) -> Option<MoveOutcome> {
+        let bar = self.undone.pop()?;
+        Some(self.play_observed(board, bar))
+    }
+
+    /// Forgets the moves, when the game starts over from another position.
+    fn clear(&mut self) {
+        self.played.clear();
+        self.undone.clear();
+    }
+
+    /// Plays `bar` and tells the observers what it did.
+    fn play_observed(&mut self, board: &mut BoardState, bar: BarId) -> MoveOutcome {
+        let player = board.cur_turn;
+        let outcome = board.play(bar);
+        let MoveOutcome::Played {
+            completed,
+            turn_changed,
+            game_over,
+        } = &outcome
+        else {
+            return outcome;
+        };
+        self.played.push(PlayedMove {
+            player,
+            bar,
+            completed: completed.clone(),
+            timestamp_ms: stats::now_ms(),
+        });
+        let winner = match score_balance(board).cmp(&0) {
+            Ordering::Greater => Some(Player::Red),
+            Ordering::Less => Some(Player::Blue),
+            Ordering::Equal => None,
+        };
+        for observer in &self.observers {
+            observer.on_move_played(player, bar);
+            for (col, row) in completed {
+                observer.on_box_completed(player, *col, *row);
+            }
+            if *turn_changed {
+                observer.on_turn_changed(board.cur_turn);
+            }
+            if *game_over {
+                observer.on_game_over(winner);
+            }
+        }
+        outcome
+    }
+}
+
+fn seat(player: Player) -> usize {
+    match player {
+        Player::Red => 0,
+        Player::Blue => 1,
+    }
+}
+
+/// The bars of one direction, row by row, as three bit planes: one bit
+/// per bar for whether it is drawn, one for whether Blue drew it, and one
+/// for whether it is neutral. `words` interleaves them, each word of the
+/// first plane followed by the same word of the others. Copying a board,
+/// which the AI's playouts do a lot, copies a few words rather than a byte
+/// per bar, and free bars are found a word at a time.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "serialization::BarVecFields", try_from = "serialization::BarVecFields")
+)]
 struct BarVec {
     width: u32,
     length: u32,
     direction: BarDirection,
-    vec: Vec<CellState>,
+    words: Vec<u64>,
 }
 
+/// Bit planes of a `BarVec`.
+const PLANES: usize = 3;
+
 struct BarVecIdIterator<'a> {
-    direction: BarDirection,
-    width: u32,
-    length: u32,
     cur_index: u32,
-    vec: &'a [CellState],
+    bars: &'a BarVec,
 }
 
 impl Iterator for BarVecIdIterator<'_> {
     type Item = (BarId, CellState);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.cur_index >= self.length {
+        if self.cur_index >= self.bars.length {
             None
         } else {
-            let id = BarId {
-                direction: self.direction,
-                col: self.cur_index % self.width,
-                row: self.cur_index / self.width,
-            };
-            let state = self.vec[self.cur_index as usize];
+            let item = (
+                self.bars.index_to_id(self.cur_index),
+                self.bars.get_index(self.cur_index),
+            );
             self.cur_index += 1;
-            Some((id, state))
+            Some(item)
         }
     }
 }
 
 impl BarVec {
     fn new(width: u32, height: u32, direction: BarDirection) -> Self {
-        let vec = vec![CellState::Free; (width * height) as usize];
+        let length = width * height;
         Self {
             width,
-            length: width * height,
+            length,
             direction,
-            vec,
+            words: vec![0; PLANES * length.div_ceil(64) as usize],
         }
     }
 
     fn get(&self, col: u32, row: u32) -> CellState {
-        self.vec[(row * self.width + col) as usize]
+        self.get_index(row * self.width + col)
+    }
+
+    fn get_index(&self, index: u32) -> CellState {
+        let (word, bit) = (PLANES * (index / 64) as usize, 1 << (index % 64));
+        if self.words[word] & bit == 0 {
+            CellState::Free
+        } else if self.words[word + 2] & bit != 0 {
+            CellState::Neutral
+        } else if self.words[word + 1] & bit == 0 {
+            CellState::Player(Player::Red)
+        } else {
+            CellState::Player(Player::Blue)
+        }
     }
 
     fn set(&mut self, col: u32, row: u32, state: CellState) {
-        self.vec[(row * self.width + col) as usize] = state;
+        self.set_index(row * self.width + col, state);
     }
 
+    fn set_index(&mut self, index: u32, state: CellState) {
+        let (word, bit) = (PLANES * (index / 64) as usize, 1 << (index % 64));
+        let planes = match state {
+            CellState::Free => [0, 0, 0],
+            CellState::Player(Player::Red) => [bit, 0, 0],
+            CellState::Player(Player::Blue) => [bit, bit, 0],
+            CellState::Neutral => [bit, 0, bit],
+        };
+        for (word, plane) in self.words[word..word + PLANES].iter_mut().zip(planes) {
+            *word = (*word & !bit) | plane;
+        }
+    }
+
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
     fn index_to_id(&self, index: u32) -> BarId {
         BarId {
             direction: self.direction,
@@ -203,42 +1328,138 @@ impl BarVec {
         }
     }
 
-    fn clear(&mut self) {
-        for state in self.vec.iter_mut() {
-            *state = CellState::Free;
+    /// Index of the first free bar from `start` on.
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    fn first_free(&self, start: u32) -> Option<u32> {
+        let mut word = (start / 64) as usize;
+        let mut free = !*self.words.get(PLANES * word)? & (!0 << (start % 64));
+        loop {
+            if free != 0 {
+                let index = word as u32 * 64 + free.trailing_zeros();
+                return (index < self.length).then_some(index);
+            }
+            word += 1;
+            free = !*self.words.get(PLANES * word)?;
         }
     }
 
-    fn iter(&self) -> BarVecIdIterator {
+    fn free_count(&self) -> u32 {
+        let drawn: u32 = (self.words.iter().step_by(PLANES))
+            .map(|word| word.count_ones())
+            .sum();
+        self.length - drawn
+    }
+
+    fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn iter(&self) -> BarVecIdIterator<'_> {
         BarVecIdIterator {
-            direction: self.direction,
-            width: self.width,
-            length: self.length,
             cur_index: 0,
-            vec: &self.vec,
+            bars: self,
         }
     }
+
+    /// Every bar's state, row by row.
+    fn states(&self) -> impl Iterator<Item = CellState> + '_ {
+        (0..self.length).map(|index| self.get_index(index))
+    }
+
+    /// Replaces the bars' states by `states`, listed row by row.
+    fn load(&mut self, states: &[CellState]) {
+        for (index, state) in states.iter().enumerate() {
+            self.set_index(index as u32, *state);
+        }
+    }
+}
+
+/// Red's lead under the board's rules: Red's points minus Blue's, turned
+/// around in misère play.
+fn score_balance(board: &BoardState) -> i32 {
+    board.rules.box_value() * box_balance(board)
 }
 
-#[derive(Clone)]
+/// Red's points minus Blue's, each box counting its value, head starts
+/// included.
+fn box_balance(board: &BoardState) -> i32 {
+    (board.cells())
+        .map(|(col, row, cell_state)| match cell_state {
+            CellState::Free | CellState::Neutral => 0,
+            CellState::Player(Player::Red) => board.cell_value(col, row) as i32,
+            CellState::Player(Player::Blue) => -(board.cell_value(col, row) as i32),
+        })
+        .sum::<i32>()
+        + board.head_start_balance()
+}
+
+pub fn is_valid_size(size: u32) -> bool {
+    (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size)
+}
+
+/// Parses a board size written `<width>x<height>` in dots, e.g. `5x5`, for
+/// the command-line tools.
+#[cfg(feature = "ai")]
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once('x')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    (is_valid_size(width) && is_valid_size(height)).then_some((width, height))
+}
+
+/// Positions are equal when their boards, bar colors included, and the
+/// player to move are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialization::BoardStateFields"))]
 pub struct BoardState {
     width: u32,
     height: u32,
     cur_turn: Player,
+    #[cfg_attr(feature = "serde", serde(default))]
+    rules: Ruleset,
     vstates: BarVec,
     hstates: BarVec,
     cellstates: Vec<CellState>,
+    /// Points of each box, `None` when every box is worth one, see
+    /// `values.rs`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    values: Option<CellValues>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    handicap: Option<Handicap>,
+    /// Zobrist hashes of the position under each symmetry of the board,
+    /// in the order of `Symmetry::all`, see `zobrist.rs`. The first one is
+    /// of the position as it is.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hashes: [u64; MAX_SYMMETRIES],
+}
+
+/// Hashes the Zobrist hash, which equal positions share, rather than every
+/// bar and box again.
+impl Hash for BoardState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hashes[0]);
+    }
 }
 
 impl BoardState {
     fn new(width: u32, height: u32) -> Self {
+        assert!(
+            is_valid_size(width) && is_valid_size(height),
+            "unsupported board size {}x{}",
+            width,
+            height
+        );
         Self {
             width,
             height,
             cur_turn: Player::Red,
+            rules: Ruleset::default(),
             vstates: BarVec::new(width, height - 1, BarDirection::Vertical),
             hstates: BarVec::new(width - 1, height, BarDirection::Horizontal),
             cellstates: vec![CellState::Free; ((width - 1) * (height - 1)) as usize],
+            values: None,
+            handicap: None,
+            hashes: [Self::turn_key(Player::Red); MAX_SYMMETRIES],
         }
     }
 
@@ -254,8 +1475,8 @@ impl BoardState {
                     self.cell_set(neighbor_col, neighbor_row, cur_turn.into());
                 }
             }
-            if !point_gained {
-                self.cur_turn = self.cur_turn.other();
+            if !point_gained || self.rules.alternate_turns {
+                self.set_turn(self.cur_turn.other());
             }
             true
         } else {
@@ -263,13 +1484,206 @@ impl BoardState {
         }
     }
 
+    /// Takes back `bar`, which has to be the last bar played: frees it and
+    /// the boxes it completed, and gives the move back to the player who
+    /// drew it. Returns that player, `None` if `bar` is free.
+    fn unplay(&mut self, bar: BarId) -> Option<Player> {
+        let CellState::Player(player) = self.bar_get(bar) else {
+            return None;
+        };
+        self.bar_set(bar, CellState::Free);
+        for (col, row) in self.bar_neighbors(bar) {
+            self.cell_set(col, row, CellState::Free);
+        }
+        self.set_turn(player);
+        Some(player)
+    }
+
+    /// The position after playing `moves` in order on an empty board, with
+    /// `starting_player` to move first. Fails on an unsupported size or at
+    /// the first move that cannot be played.
+    pub fn from_moves(
+        width: u32,
+        height: u32,
+        starting_player: Player,
+        moves: &[BarId],
+    ) -> Result<Self, GameError> {
+        if !is_valid_size(width) || !is_valid_size(height) {
+            return Err(GameError::InvalidSize(width, height));
+        }
+        let mut board = BoardState::new(width, height);
+        board.restart(starting_player);
+        for (index, bar) in moves.iter().enumerate() {
+            (board.validate(*bar)).map_err(|err| GameError::IllegalMove(index, err))?;
+            board.do_move(*bar);
+        }
+        Ok(board)
+    }
+
+    /// An empty board as `config` sets it up: of the boxes in its mask, or
+    /// all of them, worth its values, or one each, played by its rules, with
+    /// the bars of its handicap drawn and its starting player to move first.
+    /// Fails on an unsupported size, a mask, values or a handicap that do
+    /// not fit it, or rules that cannot be played on it.
+    fn new_game(config: &GameConfig) -> Result<Self, GameError> {
+        let GameConfig {
+            width,
+            height,
+            starting_player,
+            rules,
+            ..
+        } = *config;
+        if !rules.is_playable(width, height) {
+            return Err(GameError::UnplayableRules(width, height));
+        }
+        let mut board = BoardState::from_moves(width, height, starting_player, &[])?;
+        if let Some(mask) = &config.mask {
+            if mask.width() != width || mask.height() != height || mask.is_empty() {
+                return Err(GameError::InvalidMask);
+            }
+            board.set_mask(mask);
+        }
+        if let Some(values) = &config.values {
+            if values.width() != width || values.height() != height {
+                return Err(GameError::InvalidValues);
+            }
+            board.set_values(values);
+        }
+        board.set_rules(rules);
+        // A box the Swedish border closes on its own would have no owner.
+        if board.cells().any(|(col, row, _)| board.cell_is_full(col, row)) {
+            return Err(GameError::UnplayableRules(width, height));
+        }
+        if let Some(handicap) = config.handicap {
+            let fits = handicap.boxes as usize <= board.cellstates.len()
+                && handicap.bars as usize <= board.bars().count();
+            if !fits {
+                return Err(GameError::InvalidHandicap);
+            }
+            board.set_handicap(Some(handicap));
+        }
+        if let Some(scramble) = config.scramble {
+            board.scramble(scramble);
+        }
+        Ok(board)
+    }
+
+    /// Draws `bar` for the side to move, and reports what it did.
+    fn play(&mut self, bar: BarId) -> MoveOutcome {
+        if let Err(err) = self.validate(bar) {
+            return MoveOutcome::Illegal(err);
+        }
+        let mover = self.cur_turn;
+        self.do_move(bar);
+        let completed = (self.bar_neighbors(bar))
+            .filter(|(col, row)| self.cell_get(*col, *row) != CellState::Free)
+            .collect();
+        MoveOutcome::Played {
+            completed,
+            turn_changed: self.cur_turn != mover,
+            game_over: self.is_finished(),
+        }
+    }
+
+    /// Width in dots.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in dots.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Every box, row by row, with its column, row and owner.
+    pub fn cells(&self) -> impl Iterator<Item = (u32, u32, CellState)> + '_ {
+        let cell_width = self.width - 1;
+        (self.cellstates.iter().enumerate()).map(move |(index, state)| {
+            let index = index as u32;
+            (index % cell_width, index / cell_width, *state)
+        })
+    }
+
+    /// Every bar, the vertical ones first, each row by row, with the
+    /// player who drew it.
+    pub fn bars(&self) -> impl Iterator<Item = (BarId, CellState)> + '_ {
+        self.vstates.iter().chain(self.hstates.iter())
+    }
+
+    fn free_bar_count(&self) -> u32 {
+        self.vstates.free_count() + self.hstates.free_count()
+    }
+
+    /// The rules the game is played by.
+    pub fn rules(&self) -> Ruleset {
+        self.rules
+    }
+
+    /// Plays the rest of the game by `rules`. Turning the Swedish rules or
+    /// the torus on or off draws or frees the bars that become fixed or
+    /// stop being, so it is meant for boards that have not started: boxes
+    /// the border completes would have no owner.
+    pub fn set_rules(&mut self, rules: Ruleset) {
+        self.rules = rules;
+        for (bar, state) in self.bars().collect::<Vec<_>>() {
+            match (state, self.is_fixed(bar)) {
+                (CellState::Neutral, false) => self.bar_set(bar, CellState::Free),
+                (CellState::Free, true) => self.bar_set(bar, CellState::Neutral),
+                _ => (),
+            }
+        }
+        // The torus changes the symmetries the position is hashed under.
+        self.hashes = self.full_hashes();
+    }
+
+    /// The bars neither player draws: the ones off the board, see
+    /// `mask.rs`, the border of a Swedish board, and the last column and row
+    /// of bars of a torus, which are the first ones again.
+    fn fixed_bars(&self) -> impl Iterator<Item = BarId> + '_ {
+        self.bars().map(|(bar, _)| bar).filter(|bar| self.is_fixed(*bar))
+    }
+
+    fn is_fixed(&self, bar: BarId) -> bool {
+        match self.bar_neighbors(bar).count() {
+            0 => true,
+            1 => self.rules.swedish,
+            _ => false,
+        }
+    }
+
+    /// Whether the neutral bars are the fixed ones, see `fixed_bars`.
+    /// Checked on positions read from outside.
+    fn neutral_is_consistent(&self) -> bool {
+        (self.bars()).all(|(bar, state)| (state == CellState::Neutral) == self.is_fixed(bar))
+    }
+
+    /// Whether every box is taken, which is when every bar is drawn.
+    pub fn is_finished(&self) -> bool {
+        !self.cellstates.contains(&CellState::Free)
+    }
+
+    /// Empties the board, keeping its shape, and draws the border again on
+    /// Swedish boards and the bars of the handicap.
     fn restart(&mut self, starting_player: Player) {
         for state in &mut self.cellstates {
-            *state = CellState::Free;
+            if *state != CellState::Neutral {
+                *state = CellState::Free;
+            }
         }
         self.vstates.clear();
         self.hstates.clear();
         self.cur_turn = starting_player;
+        for bar in self.fixed_bars().collect::<Vec<_>>() {
+            self.bar_set(bar, CellState::Neutral);
+        }
+        self.draw_handicap_bars();
+        self.hashes = self.full_hashes();
+    }
+
+    fn set_turn(&mut self, player: Player) {
+        let key = Self::turn_key(self.cur_turn) ^ Self::turn_key(player);
+        toggle_keys(&mut self.hashes, [key; MAX_SYMMETRIES]);
+        self.cur_turn = player;
     }
 
     fn cell_get(&self, col: u32, row: u32) -> CellState {
@@ -277,14 +1691,73 @@ impl BoardState {
     }
 
     fn cell_set(&mut self, col: u32, row: u32, state: CellState) {
+        let old_keys = self.cell_keys(col, row, self.cell_get(col, row));
+        let new_keys = self.cell_keys(col, row, state);
+        toggle_keys(&mut self.hashes, old_keys);
+        toggle_keys(&mut self.hashes, new_keys);
         self.cellstates[(row * (self.width - 1) + col) as usize] = state;
     }
 
+    /// Whether the box is on the board and all its bars are drawn.
     fn cell_is_full(&self, col: u32, row: u32) -> bool {
-        self.vstates.get(col, row) != CellState::Free
-            && self.vstates.get(col + 1, row) != CellState::Free
-            && self.hstates.get(col, row) != CellState::Free
-            && self.hstates.get(col, row + 1) != CellState::Free
+        self.cell_get(col, row) != CellState::Neutral
+            && (self.cell_bars(col, row).iter()).all(|bar| self.bar_get(*bar) != CellState::Free)
+    }
+
+    /// The bars around the box at `col`, `row`: left, right, top and
+    /// bottom. On a torus, the boxes of the last column and row have the
+    /// bars of the first ones on their right and bottom.
+    pub(crate) fn cell_bars(&self, col: u32, row: u32) -> [BarId; 4] {
+        let bar = |direction, col, row| BarId {
+            direction,
+            col,
+            row,
+        };
+        let (right, bottom) = if self.rules.torus {
+            ((col + 1) % (self.width - 1), (row + 1) % (self.height - 1))
+        } else {
+            (col + 1, row + 1)
+        };
+        [
+            bar(BarDirection::Vertical, col, row),
+            bar(BarDirection::Vertical, right, row),
+            bar(BarDirection::Horizontal, col, row),
+            bar(BarDirection::Horizontal, col, bottom),
+        ]
+    }
+
+    /// The bar that `bar` is: on a torus, the last column of vertical bars
+    /// is the first one again, and the last row of horizontal bars the
+    /// first one. Any other bar is itself.
+    pub fn wrap_bar(&self, bar: BarId) -> BarId {
+        let wrapped = |last, index| if self.rules.torus && index == last { 0 } else { index };
+        match bar.direction {
+            BarDirection::Vertical => BarId {
+                col: wrapped(self.width - 1, bar.col),
+                ..bar
+            },
+            BarDirection::Horizontal => BarId {
+                row: wrapped(self.height - 1, bar.row),
+                ..bar
+            },
+        }
+    }
+
+    /// Checks that `bar` lies on the board and is not drawn yet, before it
+    /// is played. The other methods taking a bar expect one within the
+    /// board's rectangle.
+    pub fn validate(&self, bar: BarId) -> Result<(), BarError> {
+        let in_bounds = match bar.direction {
+            BarDirection::Horizontal => bar.col + 1 < self.width && bar.row < self.height,
+            BarDirection::Vertical => bar.col < self.width && bar.row + 1 < self.height,
+        };
+        if !in_bounds || !self.has_bar(bar) {
+            Err(BarError::OutOfBounds)
+        } else if self.bar_get(bar) != CellState::Free {
+            Err(BarError::Taken)
+        } else {
+            Ok(())
+        }
     }
 
     fn bar_get(&self, bar: BarId) -> CellState {
@@ -296,6 +1769,10 @@ impl BoardState {
     }
 
     fn bar_set(&mut self, bar: BarId, state: CellState) {
+        if (self.bar_get(bar) == CellState::Free) != (state == CellState::Free) {
+            let keys = self.bar_keys(bar);
+            toggle_keys(&mut self.hashes, keys);
+        }
         let bar_vec = match bar.direction {
             BarDirection::Vertical => &mut self.vstates,
             BarDirection::Horizontal => &mut self.hstates,
@@ -303,28 +1780,27 @@ impl BoardState {
         bar_vec.set(bar.col, bar.row, state);
     }
 
-    fn bar_neighbors(&self, bar: BarId) -> Vec<(u32, u32)> {
-        match bar.direction {
-            BarDirection::Vertical => {
-                let mut res = vec![];
-                if bar.col != 0 {
-                    res.push((bar.col - 1, bar.row));
-                }
-                if bar.col < self.width - 1 {
-                    res.push((bar.col, bar.row));
-                }
-                res
-            }
-            BarDirection::Horizontal => {
-                let mut res = vec![];
-                if bar.row != 0 {
-                    res.push((bar.col, bar.row - 1));
-                }
-                if bar.row < self.height - 1 {
-                    res.push((bar.col, bar.row));
-                }
-                res
-            }
-        }
+    /// The boxes of the board on either side of `bar`, none to two. On a
+    /// torus, the first column and row of bars have the last boxes on their
+    /// other side, and the last ones none, see `wrap_bar`. Called for every
+    /// move the AI tries, so it does not allocate.
+    fn bar_neighbors(&self, bar: BarId) -> impl Iterator<Item = (u32, u32)> + Clone {
+        let torus = self.rules.torus;
+        let (last_col, last_row) = (self.width - 1, self.height - 1);
+        let neighbors = match bar.direction {
+            _ if self.wrap_bar(bar) != bar => [None, None],
+            BarDirection::Vertical => [
+                (bar.col.checked_sub(1).or(torus.then_some(last_col - 1)))
+                    .map(|col| (col, bar.row)),
+                (bar.col < last_col).then_some((bar.col, bar.row)),
+            ],
+            BarDirection::Horizontal => [
+                (bar.row.checked_sub(1).or(torus.then_some(last_row - 1)))
+                    .map(|row| (bar.col, row)),
+                (bar.row < last_row).then_some((bar.col, bar.row)),
+            ],
+        };
+        let on_board = neighbors.map(|cell| cell.filter(|(col, row)| self.has_box(*col, *row)));
+        on_board.into_iter().flatten()
     }
 }
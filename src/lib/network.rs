@@ -0,0 +1,149 @@
+use crate::{BoardState, CellState, Player, Ruleset};
+
+/// First bytes of a network file.
+const MAGIC: &[u8; 4] = b"DABN";
+const VERSION: u32 = 1;
+
+/// A small fully connected neural network that estimates the final box
+/// difference, Red's boxes minus Blue's, of a position on a board of the
+/// size it was trained for.
+///
+/// Its inputs are, in order: each vertical bar, then each horizontal bar,
+/// row by row, 1 if drawn and 0 if not; each box, row by row, 1 if Red's,
+/// -1 if Blue's and 0 if free; and the side to move, 1 for Red and -1 for
+/// Blue. Hidden layers use ReLU, the last layer has a single linear output.
+pub struct Network {
+    inputs: usize,
+    layers: Vec<Layer>,
+}
+
+struct Layer {
+    outputs: usize,
+    /// `outputs` rows of one weight per input of the layer.
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+impl Network {
+    /// Reads a network in the format written by exporting the weights of
+    /// the trained model, all numbers little-endian:
+    ///
+    /// - `DABN`, then the version 1 and the number of inputs as `u32`s;
+    /// - the number of layers as a `u32`;
+    /// - for each layer, its number of outputs as a `u32`, its weights as
+    ///   `f32`s output by output, then one bias per output as `f32`s.
+    ///
+    /// Returns `None` if the data is malformed, a layer has no outputs or
+    /// the last one has more than one.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != VERSION {
+            return None;
+        }
+        let inputs = reader.u32()? as usize;
+        let layer_count = reader.u32()?;
+        let mut layers = Vec::new();
+        let mut layer_inputs = inputs;
+        for _ in 0..layer_count {
+            let outputs = reader.u32()? as usize;
+            if outputs == 0 {
+                return None;
+            }
+            let weights = reader.f32s(outputs.checked_mul(layer_inputs)?)?;
+            let biases = reader.f32s(outputs)?;
+            layers.push(Layer {
+                outputs,
+                weights,
+                biases,
+            });
+            layer_inputs = outputs;
+        }
+        if layer_inputs != 1 || !reader.0.is_empty() {
+            return None;
+        }
+        Some(Self { inputs, layers })
+    }
+
+    /// Whether the network was trained for boards like `board`: full
+    /// rectangles of its size, of boxes worth a point each, played by the
+    /// standard rules without a handicap.
+    pub fn accepts(&self, board: &BoardState) -> bool {
+        self.inputs == input_count(board)
+            && board.rules == Ruleset::default()
+            && board.is_rectangular()
+            && board.values.is_none()
+            && board.handicap.is_none()
+    }
+
+    /// Estimated final box difference of `board`, Red's boxes minus Blue's.
+    /// The board must be `accepts`ed.
+    pub fn evaluate(&self, board: &BoardState) -> f32 {
+        let mut values = encode(board);
+        for (index, layer) in self.layers.iter().enumerate() {
+            let hidden = index + 1 < self.layers.len();
+            values = (layer.weights.chunks(values.len()))
+                .zip(&layer.biases)
+                .map(|(weights, bias)| {
+                    let sum = bias + weights.iter().zip(&values).map(|(w, v)| w * v).sum::<f32>();
+                    if hidden {
+                        sum.max(0.0)
+                    } else {
+                        sum
+                    }
+                })
+                .collect();
+            debug_assert_eq!(values.len(), layer.outputs);
+        }
+        values[0]
+    }
+}
+
+fn input_count(board: &BoardState) -> usize {
+    (board.vstates.length + board.hstates.length) as usize + board.cellstates.len() + 1
+}
+
+/// The inputs of the network for `board`, see `Network`.
+fn encode(board: &BoardState) -> Vec<f32> {
+    let mut inputs = Vec::with_capacity(input_count(board));
+    inputs.extend(
+        board
+            .bars()
+            .map(|(_, state)| if state == CellState::Free { 0.0 } else { 1.0 }),
+    );
+    inputs.extend(board.cellstates.iter().map(|state| match *state {
+        CellState::Free | CellState::Neutral => 0.0,
+        CellState::Player(Player::Red) => 1.0,
+        CellState::Player(Player::Blue) => -1.0,
+    }));
+    inputs.push(match board.cur_turn {
+        Player::Red => 1.0,
+        Player::Blue => -1.0,
+    });
+    inputs
+}
+
+/// The unread rest of a network file.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if count > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn f32s(&mut self, count: usize) -> Option<Vec<f32>> {
+        let bytes = self.take(count.checked_mul(4)?)?;
+        let values = bytes
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().expect("chunks of four bytes")));
+        Some(values.collect())
+    }
+}
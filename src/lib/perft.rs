@@ -0,0 +1,31 @@
+use crate::{BoardState, CellState};
+
+impl BoardState {
+    /// Number of move sequences of `depth` turns from this position, in the
+    /// spirit of chess perft, to check move generation and `do_move`
+    /// against known counts. A turn is every bar its player draws: the
+    /// bars completing a box and the one that passes the turn on. Games
+    /// over before `depth` turns are not counted; the turn that ends a game
+    /// counts as a full turn.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let free_bars = self
+            .bars()
+            .filter(|(_, state)| *state == CellState::Free)
+            .map(|(bar, _)| bar);
+        let mut leaves = 0;
+        for bar in free_bars {
+            let mut child = self.clone();
+            child.do_move(bar);
+            leaves += if child.cur_turn != self.cur_turn || child.is_finished() {
+                child.perft(depth - 1)
+            } else {
+                // The box earns another bar in the same turn.
+                child.perft(depth)
+            };
+        }
+        leaves
+    }
+}
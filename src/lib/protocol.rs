@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use crate::ai::{self, AIOptions, AI};
+use crate::{parse_size, BarId, BoardState, Difficulty, Engine, Player};
+
+/// Board the engine starts from, and `position startpos` without a size.
+const DEFAULT_SIZE: (u32, u32) = (4, 4);
+
+/// State of the engine between commands.
+struct Session {
+    engine: Engine,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    board: BoardState,
+    /// The AIs of earlier searches by engine, side and board size, kept so
+    /// the next search of the same game reuses their transposition tables.
+    ais: HashMap<(Engine, Player, u32, u32), Box<dyn AI>>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            engine: Engine::default(),
+            difficulty: Difficulty::Hard,
+            seed: None,
+            board: BoardState::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1),
+            ais: HashMap::new(),
+        }
+    }
+}
+
+/// Entry point of the `engine` binary: a headless engine reading commands
+/// from stdin, one per line, in the spirit of the UCI protocol of chess
+/// engines. Bars are written like `h2.1` for the horizontal bar in column 2
+/// and row 1.
+///
+/// - `uci` lists the options and ends with `uciok`.
+/// - `isready` answers `readyok`.
+/// - `setoption name <Engine|Difficulty|Seed> value <value>`.
+/// - `ucinewgame` forgets the searches of earlier games.
+/// - `position startpos [<width>x<height>] [moves <bar>...]` or
+///   `position fen <fen> [moves <bar>...]` sets the position.
+/// - `go [depth <plies>] [movetime <ms>]` searches it, and answers
+///   `info ...` with the statistics of the search, then `bestmove <bar>`,
+///   or `bestmove (none)` once the game is over. The search goes to the
+///   difficulty's depth, or `depth` if given, which drops the difficulty's
+///   time budget. `movetime` only caps it: once it runs out, the best of
+///   the moves scored so far is played, and time left over is not spent
+///   searching deeper.
+/// - `quit` exits.
+///
+/// Invalid commands are answered with `info string <error>`.
+pub fn run_engine() {
+    let mut session = Session::default();
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let mut words = line.split_whitespace();
+        let result = match words.next() {
+            Some("quit") => break,
+            Some(command) => session.run(command, words),
+            None => continue,
+        };
+        if let Err(err) = result {
+            println!("info string {}", err);
+        }
+    }
+}
+
+impl Session {
+    fn run<'a>(
+        &mut self,
+        command: &str,
+        words: impl Iterator<Item = &'a str>,
+    ) -> Result<(), String> {
+        match command {
+            "uci" => {
+                let engines = (Engine::ALL.iter())
+                    .filter(|engine| **engine != Engine::External)
+                    .map(|engine| format!(" var {}", engine))
+                    .collect::<String>();
+                let difficulties = (Difficulty::ALL.iter())
+                    .map(|difficulty| format!(" var {}", difficulty))
+                    .collect::<String>();
+                println!("id name dots-and-boxes {}", env!("CARGO_PKG_VERSION"));
+                println!(
+                    "option name Engine type combo default {}{}",
+                    self.engine, engines
+                );
+                println!(
+                    "option name Difficulty type combo default {}{}",
+                    self.difficulty, difficulties
+                );
+                println!("option name Seed type string default random");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => self.ais.clear(),
+            "setoption" => self.set_option(words)?,
+            "position" => self.board = parse_position(words)?,
+            "go" => self.go(words)?,
+            _ => return Err(format!("unknown command {}", command)),
+        }
+        Ok(())
+    }
+
+    fn set_option<'a>(&mut self, mut words: impl Iterator<Item = &'a str>) -> Result<(), String> {
+        if words.next() != Some("name") {
+            return Err("expected setoption name <name> value <value>".to_string());
+        }
+        let name = words.next().unwrap_or_default();
+        let value = match (words.next(), words.next()) {
+            (Some("value"), Some(value)) => value,
+            _ => return Err("expected setoption name <name> value <value>".to_string()),
+        };
+        let invalid = || format!("invalid {}: {}", name, value);
+        match name {
+            "Engine" => {
+                // Bots written in JavaScript only play in the browser.
+                self.engine = (value.parse().ok())
+                    .filter(|engine| *engine != Engine::External)
+                    .ok_or_else(invalid)?
+            }
+            "Difficulty" => self.difficulty = value.parse().map_err(|_| invalid())?,
+            "Seed" if value == "random" => self.seed = None,
+            "Seed" => self.seed = Some(value.parse().map_err(|_| invalid())?),
+            _ => return Err(format!("unknown option {}", name)),
+        }
+        Ok(())
+    }
+
+    fn go<'a>(&mut self, mut words: impl Iterator<Item = &'a str>) -> Result<(), String> {
+        let mut options = AIOptions::from(self.difficulty);
+        options.seed = self.seed;
+        let (mut depth, mut movetime) = (None, None);
+        while let Some(word) = words.next() {
+            let value = words.next().and_then(|value| value.parse().ok());
+            match (word, value) {
+                ("depth", Some(plies)) if plies > 0 => depth = Some(plies),
+                ("movetime", Some(ms)) => movetime = Some(f64::from(ms)),
+                _ => return Err(format!("invalid go parameter {}", word)),
+            }
+        }
+        if let Some(depth) = depth {
+            options.search.depth = depth;
+            options.search.time_budget_ms = None;
+        }
+        if movetime.is_some() {
+            options.search.time_budget_ms = movetime;
+        }
+        let board = &self.board;
+        let (engine, player) = (self.engine, board.cur_turn);
+        let ai = (self.ais)
+            .entry((engine, player, board.width, board.height))
+            .or_insert_with(|| ai::new_ai(engine, board, player));
+        ai.set_options(options);
+        let bar = ai.next_move(board);
+        if let Some(stats) = ai.last_search_stats() {
+            let pv = (stats.principal_variation.iter())
+                .map(|bar| bar.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            println!(
+                "info depth {} seldepth {} nodes {} time {} pv {}",
+                stats.depth,
+                stats.max_depth,
+                stats.nodes,
+                stats.duration_ms.round(),
+                pv
+            );
+        }
+        match bar {
+            Some(bar) => println!("bestmove {}", bar),
+            None => println!("bestmove (none)"),
+        }
+        Ok(())
+    }
+}
+
+/// The position of a `position` command, after its moves.
+fn parse_position<'a>(words: impl Iterator<Item = &'a str>) -> Result<BoardState, String> {
+    let mut words = words.peekable();
+    let mut board = match words.next() {
+        Some("startpos") => {
+            let (width, height) = match words.next_if(|word| *word != "moves") {
+                Some(size) => parse_size(size).ok_or_else(|| format!("invalid size {}", size))?,
+                None => DEFAULT_SIZE,
+            };
+            BoardState::new(width, height)
+        }
+        Some("fen") => {
+            let fen = words.next().unwrap_or_default();
+            BoardState::from_fen(fen).map_err(|err| format!("invalid fen {}: {}", fen, err))?
+        }
+        _ => return Err("expected position startpos or position fen".to_string()),
+    };
+    match words.next() {
+        None => return Ok(board),
+        Some("moves") => {}
+        Some(word) => return Err(format!("unexpected {}", word)),
+    }
+    for word in words {
+        let bar: BarId = word.parse().map_err(|_| format!("invalid move {}", word))?;
+        (board.validate(bar)).map_err(|err| format!("illegal move {}: {}", word, err))?;
+        board.do_move(bar);
+    }
+    Ok(board)
+}
@@ -0,0 +1,259 @@
+use std::fmt;
+use std::str::Chars;
+
+use crate::{is_valid_size, BarId, BoardState, GameError, Player};
+
+/// Game type in the `GM` property of records written by this app.
+const GAME_NAME: &str = "dots-and-boxes";
+
+/// A game from its first move, in a text format in the style of SGF:
+///
+/// ```text
+/// (;GM[dots-and-boxes]SZ[3x3]PL[R]
+/// ;R[h0.0]
+/// ;B[v1.0]C[Leaves the corner alone]
+/// )
+/// ```
+///
+/// The first node holds the size in dots and the player who starts, each
+/// following node one move, `R` for Red's or `B` for Blue's, with an
+/// optional comment. Comments escape `]` and `\` with a `\`. Unknown
+/// properties are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    pub width: u32,
+    pub height: u32,
+    pub first_player: Player,
+    pub moves: Vec<RecordedMove>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedMove {
+    pub bar: BarId,
+    pub comment: Option<String>,
+}
+
+/// Why a game record was rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordError {
+    /// The text is not a record, with what was wrong.
+    Syntax(String),
+    /// The size or a move is invalid.
+    Game(GameError),
+    /// The move with this index is marked for the player who is not to
+    /// move.
+    WrongPlayer(usize),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            RecordError::Syntax(err) => write!(f, "{}", err),
+            RecordError::Game(err) => write!(f, "{}", err),
+            RecordError::WrongPlayer(index) => {
+                write!(f, "move {}: not the player to move", index + 1)
+            }
+        }
+    }
+}
+
+impl GameRecord {
+    /// A record of a game without moves yet.
+    pub fn new(width: u32, height: u32, first_player: Player) -> Self {
+        Self {
+            width,
+            height,
+            first_player,
+            moves: vec![],
+        }
+    }
+
+    /// Appends a move.
+    pub fn push(&mut self, bar: BarId, comment: Option<String>) {
+        self.moves.push(RecordedMove { bar, comment });
+    }
+
+    /// Reads a record written by `to_string`, and checks that its moves
+    /// can be played in order.
+    pub fn parse(text: &str) -> Result<Self, RecordError> {
+        let mut nodes = parse_nodes(text)?.into_iter();
+        let root = nodes.next().ok_or_else(|| syntax("missing first node"))?;
+        if property(&root, "GM").is_some_and(|game| game != GAME_NAME) {
+            return Err(syntax("not a dots and boxes game"));
+        }
+        let size = property(&root, "SZ").ok_or_else(|| syntax("missing size"))?;
+        let (width, height) = (size.split_once('x'))
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .filter(|(width, height)| is_valid_size(*width) && is_valid_size(*height))
+            .ok_or_else(|| syntax(&format!("invalid size {}", size)))?;
+        let first_player = match property(&root, "PL") {
+            None => Player::Red,
+            Some(player) => {
+                parse_player(player).ok_or_else(|| syntax(&format!("invalid player {}", player)))?
+            }
+        };
+        let mut record = Self::new(width, height, first_player);
+        let mut players = vec![];
+        for node in nodes {
+            let mut moves =
+                (node.iter()).filter_map(|(name, value)| Some((parse_player(name)?, value)));
+            let (player, bar) = match (moves.next(), moves.next()) {
+                (Some(only), None) => only,
+                _ => return Err(syntax("expected one move per node")),
+            };
+            let bar = bar
+                .parse()
+                .map_err(|_| syntax(&format!("invalid move {}", bar)))?;
+            players.push(player);
+            record.push(bar, property(&node, "C").map(str::to_string));
+        }
+        record.check_players(&players)?;
+        Ok(record)
+    }
+
+    /// The position after every move, and the one before the first.
+    pub fn positions(&self) -> Result<Vec<BoardState>, RecordError> {
+        let mut board = self.replay(0)?;
+        let mut positions = vec![board.clone()];
+        for (index, recorded) in self.moves.iter().enumerate() {
+            (board.validate(recorded.bar))
+                .map_err(|err| RecordError::Game(GameError::IllegalMove(index, err)))?;
+            board.do_move(recorded.bar);
+            positions.push(board.clone());
+        }
+        Ok(positions)
+    }
+
+    /// The position after the last move.
+    pub fn final_position(&self) -> Result<BoardState, RecordError> {
+        self.replay(self.moves.len())
+    }
+
+    /// The position after the first `count` moves.
+    fn replay(&self, count: usize) -> Result<BoardState, RecordError> {
+        let bars = (self.moves.iter().take(count))
+            .map(|recorded| recorded.bar)
+            .collect::<Vec<_>>();
+        BoardState::from_moves(self.width, self.height, self.first_player, &bars)
+            .map_err(RecordError::Game)
+    }
+
+    /// Checks that each move is `players`' and legal.
+    fn check_players(&self, players: &[Player]) -> Result<(), RecordError> {
+        let positions = self.positions()?;
+        for (index, (board, player)) in positions.iter().zip(players).enumerate() {
+            if board.cur_turn != *player {
+                return Err(RecordError::WrongPlayer(index));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "(;GM[{}]SZ[{}x{}]PL[{}]",
+            GAME_NAME,
+            self.width,
+            self.height,
+            player_letter(self.first_player)
+        )?;
+        // Illegal moves are written for the side to move when they come up,
+        // and every move for the first player on an unsupported board.
+        let mut board = self.replay(0).ok();
+        for recorded in &self.moves {
+            let player = board
+                .as_ref()
+                .map_or(self.first_player, |board| board.cur_turn);
+            write!(f, "\n;{}[{}]", player_letter(player), recorded.bar)?;
+            if let Some(comment) = &recorded.comment {
+                write!(
+                    f,
+                    "C[{}]",
+                    comment.replace('\\', "\\\\").replace(']', "\\]")
+                )?;
+            }
+            if let Some(board) = board
+                .as_mut()
+                .filter(|board| board.validate(recorded.bar).is_ok())
+            {
+                board.do_move(recorded.bar);
+            }
+        }
+        write!(f, "\n)")
+    }
+}
+
+/// A node's properties, by name and value.
+type Node = Vec<(String, String)>;
+
+fn syntax(err: &str) -> RecordError {
+    RecordError::Syntax(err.to_string())
+}
+
+fn property<'a>(node: &'a Node, name: &str) -> Option<&'a str> {
+    (node.iter())
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn player_letter(player: Player) -> char {
+    match player {
+        Player::Red => 'R',
+        Player::Blue => 'B',
+    }
+}
+
+fn parse_player(letter: &str) -> Option<Player> {
+    match letter {
+        "R" => Some(Player::Red),
+        "B" => Some(Player::Blue),
+        _ => None,
+    }
+}
+
+/// Splits the text of a record into its nodes.
+fn parse_nodes(text: &str) -> Result<Vec<Node>, RecordError> {
+    let mut chars = text.trim().chars();
+    if chars.next() != Some('(') {
+        return Err(syntax("expected ( at the start"));
+    }
+    let mut nodes: Vec<Node> = vec![];
+    loop {
+        match chars.find(|c| !c.is_whitespace()) {
+            Some(';') => nodes.push(vec![]),
+            Some(')') if chars.as_str().trim().is_empty() => return Ok(nodes),
+            Some(c) if c.is_ascii_uppercase() => {
+                let node = nodes
+                    .last_mut()
+                    .ok_or_else(|| syntax("expected ; before the first property"))?;
+                let mut name = c.to_string();
+                let value = loop {
+                    match chars.next() {
+                        Some(c) if c.is_ascii_uppercase() => name.push(c),
+                        Some('[') => break parse_value(&mut chars)?,
+                        _ => return Err(syntax(&format!("expected [ after {}", name))),
+                    }
+                };
+                node.push((name, value));
+            }
+            Some(c) => return Err(syntax(&format!("unexpected {}", c))),
+            None => return Err(syntax("expected ) at the end")),
+        }
+    }
+}
+
+/// Reads a property value up to its closing `]`.
+fn parse_value(chars: &mut Chars) -> Result<String, RecordError> {
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some(']') => return Ok(value),
+            Some('\\') => value.extend(chars.next()),
+            Some(c) => value.push(c),
+            None => return Err(syntax("unterminated value")),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{BarId, BoardState, CellState};
+
+/// Attempts at each number of bars before `BoardState::scramble` tries one
+/// bar fewer.
+const SCRAMBLE_TRIES: u32 = 8;
+
+/// A "scramble start": random bars played before the game, so games set up
+/// the same way start from a fresh position each time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Scramble {
+    /// Bars played, the sides taking turns, starting with the starting
+    /// player. Fewer may be played, see `BoardState::scramble`.
+    pub bars: u32,
+    /// Picks the bars: the same seed scrambles a board the same way.
+    pub seed: u64,
+}
+
+impl BoardState {
+    /// Plays up to `scramble.bars` random bars that neither take a box nor
+    /// leave one to take, then checks that the position is still fair: a
+    /// bar like that is left, so the side to move is not forced to give
+    /// boxes away, and with the AI, its estimate gives neither side the
+    /// game, see `Evaluation::win_probability`. Unfair positions are tried
+    /// again, with fewer bars after a few tries. Returns the bars played,
+    /// 0 if no fair position was found.
+    pub fn scramble(&mut self, scramble: Scramble) -> u32 {
+        let mut rng = scramble.seed;
+        for bars in (1..=scramble.bars).rev() {
+            for _ in 0..SCRAMBLE_TRIES {
+                let mut board = self.clone();
+                if board.play_safe_bars(bars, &mut rng) == bars && board.is_fair() {
+                    *self = board;
+                    return bars;
+                }
+            }
+        }
+        0
+    }
+
+    /// Free bars whose boxes all keep at least two free bars once it is
+    /// drawn, so it neither takes a box nor gives one away.
+    fn safe_bars(&self) -> Vec<BarId> {
+        (self.bars())
+            .filter(|(bar, state)| {
+                *state == CellState::Free
+                    && (self.bar_neighbors(*bar)).all(|(col, row)| self.open_edges(col, row) > 2)
+            })
+            .map(|(bar, _)| bar)
+            .collect()
+    }
+
+    /// Plays up to `bars` random safe bars, see `safe_bars`. Returns how
+    /// many there were.
+    fn play_safe_bars(&mut self, bars: u32, rng: &mut u64) -> u32 {
+        for played in 0..bars {
+            let safe = self.safe_bars();
+            if safe.is_empty() {
+                return played;
+            }
+            self.do_move(safe[(next_random(rng) % safe.len() as u64) as usize]);
+        }
+        bars
+    }
+
+    /// Whether a scrambled position leaves neither side lost from the
+    /// start, see `scramble`.
+    fn is_fair(&self) -> bool {
+        if self.safe_bars().is_empty() {
+            return false;
+        }
+        #[cfg(feature = "ai")]
+        {
+            let evaluation = crate::ai::static_evaluation(self);
+            let red_wins = evaluation.win_probability(self, crate::Player::Red);
+            (0.25..=0.75).contains(&red_wins)
+        }
+        #[cfg(not(feature = "ai"))]
+        {
+            true
+        }
+    }
+}
+
+/// Advances `state` and returns the next number of a SplitMix64 sequence,
+/// the mix `zobrist.rs` makes its keys with. No `rand` needed, so builds
+/// without the AI scramble too.
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
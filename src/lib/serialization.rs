@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    is_valid_size, BarDirection, BarVec, BoardState, CellState, CellValues, Handicap, Player,
+    Ruleset,
+};
+
+/// `BoardState` as it is serialized. Deserializing checks that its parts
+/// fit the dimensions it declares and that the neutral bars fit its boxes
+/// and rules, and computes the hashes it leaves out.
+#[derive(Deserialize)]
+pub(crate) struct BoardStateFields {
+    width: u32,
+    height: u32,
+    cur_turn: Player,
+    /// Missing before there were variations of the rules.
+    #[serde(default)]
+    rules: Ruleset,
+    vstates: BarVec,
+    hstates: BarVec,
+    cellstates: Vec<CellState>,
+    /// Missing before boxes had values.
+    #[serde(default)]
+    values: Option<CellValues>,
+    /// Missing before there were handicaps.
+    #[serde(default)]
+    handicap: Option<Handicap>,
+}
+
+impl TryFrom<BoardStateFields> for BoardState {
+    type Error = String;
+
+    fn try_from(fields: BoardStateFields) -> Result<Self, Self::Error> {
+        let (width, height) = (fields.width, fields.height);
+        if !is_valid_size(width) || !is_valid_size(height) {
+            return Err(format!("unsupported board size {}x{}", width, height));
+        }
+        let mut board = BoardState::new(width, height);
+        for (bars, expected) in [
+            (&fields.vstates, &board.vstates),
+            (&fields.hstates, &board.hstates),
+        ] {
+            let fits = bars.direction == expected.direction
+                && bars.width == expected.width
+                && bars.length == expected.length;
+            if !fits {
+                return Err(format!("bars do not fit a {}x{} board", width, height));
+            }
+        }
+        if fields.cellstates.len() != board.cellstates.len() {
+            return Err(format!("boxes do not fit a {}x{} board", width, height));
+        }
+        if let Some(values) = &fields.values {
+            if values.width() != width || values.height() != height {
+                return Err(format!("box values do not fit a {}x{} board", width, height));
+            }
+        }
+        board.cur_turn = fields.cur_turn;
+        board.rules = fields.rules;
+        board.vstates = fields.vstates;
+        board.hstates = fields.hstates;
+        board.cellstates = fields.cellstates;
+        board.values = fields.values.filter(|values| !values.is_uniform());
+        board.handicap = fields.handicap;
+        if !board.neutral_is_consistent() {
+            return Err("neutral bars that do not fit the board's boxes".to_string());
+        }
+        board.hashes = board.full_hashes();
+        Ok(board)
+    }
+}
+
+/// `BarVec` as it is serialized, with a state per bar rather than its bit
+/// planes.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BarVecFields {
+    width: u32,
+    length: u32,
+    direction: BarDirection,
+    vec: Vec<CellState>,
+}
+
+impl From<BarVec> for BarVecFields {
+    fn from(bars: BarVec) -> Self {
+        Self {
+            width: bars.width,
+            length: bars.length,
+            direction: bars.direction,
+            vec: bars.states().collect(),
+        }
+    }
+}
+
+impl TryFrom<BarVecFields> for BarVec {
+    type Error = String;
+
+    fn try_from(fields: BarVecFields) -> Result<Self, Self::Error> {
+        if fields.width == 0 || fields.vec.len() != fields.length as usize {
+            return Err(format!(
+                "{} bar states for {} bars",
+                fields.vec.len(),
+                fields.length
+            ));
+        }
+        let height = fields.length / fields.width;
+        let mut bars = BarVec::new(fields.width, height, fields.direction);
+        if bars.length != fields.length {
+            return Err(format!(
+                "{} bars in rows of {}",
+                fields.length, fields.width
+            ));
+        }
+        bars.load(&fields.vec);
+        Ok(bars)
+    }
+}
+
+impl From<CellValues> for String {
+    fn from(values: CellValues) -> Self {
+        values.to_string()
+    }
+}
+
+impl TryFrom<String> for CellValues {
+    type Error = String;
+
+    fn try_from(values: String) -> Result<Self, Self::Error> {
+        values.parse().map_err(|()| format!("invalid box values {:?}", values))
+    }
+}
@@ -0,0 +1,59 @@
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::ShareData;
+use yew::Callback;
+
+use crate::clipboard;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShareOutcome {
+    Shared,
+    Copied,
+    Cancelled,
+    Failed,
+}
+
+/// Shares `text` together with a link to the page through the Web Share
+/// API. Browsers without it, or that refuse the data, get the text copied
+/// to the clipboard instead.
+pub fn share_text(title: &'static str, text: String, on_done: Callback<ShareOutcome>) {
+    spawn_local(async move {
+        match share(title, &text).await {
+            Ok(()) => on_done.emit(ShareOutcome::Shared),
+            Err(err) if is_abort(&err) => on_done.emit(ShareOutcome::Cancelled),
+            Err(_) => clipboard::copy_text(
+                text,
+                on_done.reform(|copied| {
+                    if copied {
+                        ShareOutcome::Copied
+                    } else {
+                        ShareOutcome::Failed
+                    }
+                }),
+            ),
+        }
+    });
+}
+
+async fn share(title: &str, text: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let navigator = window.navigator();
+    if !Reflect::has(&navigator, &JsValue::from_str("share"))? {
+        return Err(JsValue::from_str("Web Share API not supported"));
+    }
+    let data = ShareData::new();
+    data.set_title(title);
+    data.set_text(text);
+    data.set_url(&window.location().href()?);
+    JsFuture::from(navigator.share_with_data(&data)).await?;
+    Ok(())
+}
+
+/// The user dismissing the share sheet is reported as an `AbortError`.
+fn is_abort(err: &JsValue) -> bool {
+    Reflect::get(err, &JsValue::from_str("name"))
+        .ok()
+        .and_then(|name| name.as_string())
+        .is_some_and(|name| name == "AbortError")
+}
@@ -0,0 +1,61 @@
+use crate::BarId;
+
+/// What the AI's search did to pick its last move.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    /// Positions visited by the search.
+    pub nodes: u64,
+    /// Positions whose value was taken from the transposition table.
+    pub table_hits: u64,
+    /// Plies searched at full width below the root.
+    pub depth: u32,
+    /// Deepest ply reached below the root, counting the captures played
+    /// out beyond `depth`.
+    pub max_depth: u32,
+    pub duration_ms: f64,
+    /// Whether the rest of the game was solved exactly instead of searched,
+    /// so the AI plays a proven line from here on.
+    pub solved: bool,
+    /// The line of play the search expects with best play by both sides,
+    /// starting with its best move. Empty if the engine does not tell.
+    pub principal_variation: Vec<BarId>,
+}
+
+impl SearchStats {
+    /// Flat JSON object with the same field names, for comparing engine
+    /// configurations outside the app.
+    pub fn to_json(&self) -> String {
+        format!(
+            concat!(
+                r#"{{"nodes":{},"table_hits":{},"depth":{},"max_depth":{},"#,
+                r#""duration_ms":{},"solved":{},"principal_variation":[{}]}}"#
+            ),
+            self.nodes,
+            self.table_hits,
+            self.depth,
+            self.max_depth,
+            self.duration_ms,
+            self.solved,
+            (self.principal_variation.iter())
+                .map(|bar| format!(r#""{}""#, bar))
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+}
+
+/// Milliseconds since the Unix epoch, for timing searches and moves.
+/// `Instant` is not available in the browser.
+pub(crate) fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::zobrist::toggle_keys;
+use crate::{BarDirection, BarId, BoardState, CellValues};
+
+/// Most symmetries a board has: a square one has eight, others four.
+pub(crate) const MAX_SYMMETRIES: usize = 8;
+
+/// A symmetry of the board: mirroring its columns, its rows or both, then
+/// on square boards possibly swapping columns and rows. Bars and boxes are
+/// mapped through the dots at their ends and corners.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Symmetry {
+    flip_cols: bool,
+    flip_rows: bool,
+    transpose: bool,
+}
+
+impl Symmetry {
+    /// Symmetries of a board of `width` by `height` dots, the identity
+    /// first.
+    pub(crate) fn all(width: u32, height: u32) -> impl Iterator<Item = Self> {
+        let count = if width == height { MAX_SYMMETRIES } else { 4 };
+        (0..count).map(|index| Self {
+            flip_cols: index & 1 != 0,
+            flip_rows: index & 2 != 0,
+            transpose: index & 4 != 0,
+        })
+    }
+
+    /// Position in `all`, which is also the position of the hash under
+    /// this symmetry in `BoardState`.
+    pub(crate) fn index(self) -> usize {
+        self.flip_cols as usize | (self.flip_rows as usize) << 1 | (self.transpose as usize) << 2
+    }
+
+    /// The symmetry undoing this one. Mirroring columns before swapping
+    /// columns and rows is the same as swapping first and mirroring rows.
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    pub(crate) fn inverse(self) -> Self {
+        if self.transpose {
+            Self {
+                flip_cols: self.flip_rows,
+                flip_rows: self.flip_cols,
+                transpose: true,
+            }
+        } else {
+            self
+        }
+    }
+}
+
+impl BoardState {
+    /// The position turned clockwise by `quarter_turns` quarter turns. An
+    /// odd number of them swaps the board's width and height.
+    pub fn rotated(&self, quarter_turns: u32) -> Self {
+        let (flip_cols, flip_rows) = match quarter_turns % 4 {
+            0 => (false, false),
+            1 => (false, true),
+            2 => (true, true),
+            _ => (true, false),
+        };
+        self.transformed(Symmetry {
+            flip_cols,
+            flip_rows,
+            transpose: quarter_turns % 2 == 1,
+        })
+    }
+
+    /// The position mirrored left to right.
+    pub fn mirrored_h(&self) -> Self {
+        self.transformed(Symmetry {
+            flip_cols: true,
+            flip_rows: false,
+            transpose: false,
+        })
+    }
+
+    /// The position mirrored top to bottom.
+    pub fn mirrored_v(&self) -> Self {
+        self.transformed(Symmetry {
+            flip_cols: false,
+            flip_rows: true,
+            transpose: false,
+        })
+    }
+
+    /// The position mapped by `symmetry`, which may swap the columns and
+    /// rows of a board that is not square too. On a torus, the bars of the
+    /// last column and row are the first ones again: they are left to the
+    /// rules, and bars mapped onto them are wrapped.
+    fn transformed(&self, symmetry: Symmetry) -> Self {
+        let (width, height) = if symmetry.transpose {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+        let mut board = Self::new(width, height);
+        board.set_turn(self.cur_turn);
+        board.set_rules(self.rules);
+        for (bar, state) in self.bars() {
+            if self.wrap_bar(bar) == bar {
+                board.bar_set(board.wrap_bar(self.transform_bar(symmetry, bar)), state);
+            }
+        }
+        for (col, row, state) in self.cells() {
+            let (col, row) = self.transform_cell(symmetry, col, row);
+            board.cell_set(col, row, state);
+        }
+        if self.has_values() {
+            let mut values = CellValues::uniform(width, height);
+            for (col, row, _) in self.cells() {
+                let (to_col, to_row) = self.transform_cell(symmetry, col, row);
+                values.set(to_col, to_row, self.cell_value(col, row));
+            }
+            board.set_values(&values);
+        }
+        // The bars of the handicap are among the bars mapped already.
+        let key = Self::handicap_key(self.handicap);
+        toggle_keys(&mut board.hashes, [key; MAX_SYMMETRIES]);
+        board.handicap = self.handicap;
+        board
+    }
+
+    /// Where `bar` ends up when the board is mapped by `symmetry`.
+    pub(crate) fn transform_bar(&self, symmetry: Symmetry, bar: BarId) -> BarId {
+        let end = match bar.direction {
+            BarDirection::Vertical => (bar.col, bar.row + 1),
+            BarDirection::Horizontal => (bar.col + 1, bar.row),
+        };
+        let (col1, row1) = self.transform_dot(symmetry, bar.col, bar.row);
+        let (col2, row2) = self.transform_dot(symmetry, end.0, end.1);
+        BarId {
+            direction: if col1 == col2 {
+                BarDirection::Vertical
+            } else {
+                BarDirection::Horizontal
+            },
+            col: col1.min(col2),
+            row: row1.min(row2),
+        }
+    }
+
+    /// Where the box at `col`, `row` ends up when the board is mapped by
+    /// `symmetry`.
+    pub(crate) fn transform_cell(&self, symmetry: Symmetry, col: u32, row: u32) -> (u32, u32) {
+        let (col1, row1) = self.transform_dot(symmetry, col, row);
+        let (col2, row2) = self.transform_dot(symmetry, col + 1, row + 1);
+        (col1.min(col2), row1.min(row2))
+    }
+
+    fn transform_dot(&self, symmetry: Symmetry, col: u32, row: u32) -> (u32, u32) {
+        let col = if symmetry.flip_cols {
+            self.width - 1 - col
+        } else {
+            col
+        };
+        let row = if symmetry.flip_rows {
+            self.height - 1 - row
+        } else {
+            row
+        };
+        if symmetry.transpose {
+            (row, col)
+        } else {
+            (col, row)
+        }
+    }
+
+    /// The symmetries the position is hashed under, see `zobrist.rs`. Only
+    /// the identity on a torus: its mirror images keep the bars that wrap
+    /// around at other indices, and its shifts are not symmetries of
+    /// `Symmetry` at all, so symmetric positions would not be found anyway.
+    pub(crate) fn symmetries(&self) -> impl Iterator<Item = Symmetry> {
+        let count = if self.rules.torus { 1 } else { MAX_SYMMETRIES };
+        Symmetry::all(self.width, self.height).take(count)
+    }
+
+    /// Smallest hash of the position under its symmetries, which symmetric
+    /// positions share.
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    pub(crate) fn canonical_hash(&self) -> u64 {
+        self.hashes[self.canonical_symmetry().index()]
+    }
+
+    /// A symmetry mapping the position to the one `canonical_hash` is the
+    /// hash of.
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    pub(crate) fn canonical_symmetry(&self) -> Symmetry {
+        self.symmetries()
+            .min_by_key(|symmetry| self.hashes[symmetry.index()])
+            .expect("every board has the identity symmetry")
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+use crate::{seat, PlayedMove, Player};
+
+/// One of the four participants of a team game: a member of Red's or
+/// Blue's team. The boxes they take score for their team.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Participant {
+    pub team: Player,
+    /// 0 for the team's first member, 1 for its second.
+    pub member: u32,
+}
+
+impl Participant {
+    /// The participants in the order they play: Red's first member, Blue's
+    /// first, Red's second, then Blue's second.
+    pub const ALL: [Participant; 4] = [
+        Participant::new(Player::Red, 0),
+        Participant::new(Player::Blue, 0),
+        Participant::new(Player::Red, 1),
+        Participant::new(Player::Blue, 1),
+    ];
+
+    const fn new(team: Player, member: u32) -> Self {
+        Self { team, member }
+    }
+
+    /// Position in `ALL`.
+    pub fn index(&self) -> usize {
+        2 * self.member as usize + seat(self.team)
+    }
+}
+
+impl fmt::Display for Participant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} {}", self.team, self.member + 1)
+    }
+}
+
+impl Player {
+    /// The two participants of this side's team in a team game.
+    pub fn members(&self) -> [Participant; 2] {
+        [Participant::new(*self, 0), Participant::new(*self, 1)]
+    }
+}
+
+/// The setup of a 2v2 game. The sides take turns as usual, and each side's
+/// turns go to its two members in turn, so the four participants play in
+/// the order of `Participant::ALL`, keeping the move after taking a box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Teams {
+    /// Whether the AI plays each participant, in the order of
+    /// `Participant::ALL`.
+    pub ai: [bool; 4],
+}
+
+impl Teams {
+    pub fn ai_plays(&self, participant: Participant) -> bool {
+        self.ai[participant.index()]
+    }
+
+    /// Whether the AI plays a member of `team`.
+    pub fn ai_plays_in(&self, team: Player) -> bool {
+        team.members().iter().any(|member| self.ai_plays(*member))
+    }
+
+    /// Whether the AI plays every participant.
+    pub fn ai_plays_all(&self) -> bool {
+        self.ai.iter().all(|ai| *ai)
+    }
+}
+
+/// The participant to move after `history`, with `current_turn`'s side to
+/// move. Every change of side starts a new turn of that side, and a side's
+/// turns alternate between its members, its first member starting.
+pub(crate) fn participant_to_move(history: &[PlayedMove], current_turn: Player) -> Participant {
+    let sides = history.iter().map(|played| played.player).chain([current_turn]);
+    let changes = sides
+        .clone()
+        .zip(sides.skip(1))
+        .filter(|(side, next)| side != next)
+        .count() as u32;
+    // The side to move had every other turn so far.
+    Participant::new(current_turn, changes / 2 % 2)
+}
@@ -0,0 +1,53 @@
+/// Visual settings of the board. The values are written as CSS custom
+/// properties on the board root, so the stylesheet only refers to them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub red: String,
+    pub blue: String,
+    pub board: String,
+    pub bar: String,
+    pub bar_hover: String,
+    pub dot: String,
+    /// Size of the board along its longest side, as a CSS length.
+    pub board_size: String,
+    pub border_px: u32,
+    pub radius_px: u32,
+    /// Length of a bar relative to the size of a dot.
+    pub span: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            red: "red".into(),
+            blue: "blue".into(),
+            board: "#909090".into(),
+            bar: "#808080".into(),
+            bar_hover: "#707070".into(),
+            dot: "black".into(),
+            board_size: "80vmin".into(),
+            border_px: 10,
+            radius_px: 5,
+            span: 4,
+        }
+    }
+}
+
+impl Theme {
+    pub fn css_vars(&self) -> String {
+        format!(
+            "--color-red: {}; --color-blue: {}; --color-board: {}; --color-bar: {}; \
+             --color-bar-hover: {}; --color-dot: {}; --board-size: {}; --border: {}px; \
+             --radius: {}px;",
+            self.red,
+            self.blue,
+            self.board,
+            self.bar,
+            self.bar_hover,
+            self.dot,
+            self.board_size,
+            self.border_px,
+            self.radius_px,
+        )
+    }
+}
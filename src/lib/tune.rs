@@ -0,0 +1,163 @@
+use std::{env, fs, process};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::ai::{AIMinMax, AIOptions, HeuristicWeights, AI};
+use crate::minmax::MinMaxOptions;
+use crate::{box_balance, parse_size, seat, BoardState, Player};
+
+const USAGE: &str = "usage: tune [--iterations N] [--games N] [--size WxH] [--depth N] \
+                     [--seed N] [--output PATH]";
+
+/// Scale of the steps of the weights, per box won by the perturbed weights.
+const STEP: f64 = 0.5;
+
+struct Settings {
+    /// Rounds of matches between perturbed weights.
+    iterations: u32,
+    /// Games per match, each side playing first in half of them.
+    games: u32,
+    width: u32,
+    height: u32,
+    /// Search depth of both sides.
+    depth: u32,
+    seed: u64,
+    /// Where the weights are written, as a `DAB_CONFIG` preset.
+    output: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            games: 8,
+            width: 5,
+            height: 5,
+            depth: 2,
+            seed: 0,
+            output: "tuned.json".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Option<Self> {
+        let mut settings = Self::default();
+        while let Some(flag) = args.next() {
+            let value = args.next()?;
+            match flag.as_str() {
+                "--iterations" => settings.iterations = value.parse().ok()?,
+                "--games" => settings.games = value.parse().ok().filter(|games| *games > 0)?,
+                "--size" => (settings.width, settings.height) = parse_size(&value)?,
+                "--depth" => settings.depth = value.parse().ok()?,
+                "--seed" => settings.seed = value.parse().ok()?,
+                "--output" => settings.output = value,
+                _ => return None,
+            }
+        }
+        Some(settings)
+    }
+}
+
+/// Entry point of the `tune` binary: refines the `HeuristicWeights` of the
+/// minimax AI by self-play with SPSA. Each iteration perturbs every weight
+/// by one point up or down at random, plays the weights perturbed one way
+/// against those perturbed the other way, and moves the weights towards
+/// the winner in proportion to its margin. The weights are written after
+/// every iteration, so the tuning can be stopped at any time.
+pub fn run_tune() {
+    let settings = Settings::from_args(env::args().skip(1)).unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    let mut rng = SmallRng::seed_from_u64(settings.seed);
+    let default = HeuristicWeights::default();
+    let mut theta = [default.box_value, default.chain_value, default.parity_bonus].map(f64::from);
+    for iteration in 0..settings.iterations {
+        let gain = STEP / (iteration as f64 + 1.0 + settings.iterations as f64 / 10.0).powf(0.602);
+        let delta = [(); 3].map(|_| if rng.gen_bool(0.5) { 1.0 } else { -1.0 });
+        let plus = weights(std::array::from_fn(|i| theta[i].round() + delta[i]));
+        let minus = weights(std::array::from_fn(|i| theta[i].round() - delta[i]));
+        let margin = play_match(&settings, plus, minus, &mut rng);
+        for i in 0..theta.len() {
+            theta[i] += gain * margin * delta[i];
+        }
+        let tuned = weights(theta);
+        println!(
+            "iteration {}/{}: {:+.2} boxes per game for {:?} against {:?}, now {:?}",
+            iteration + 1,
+            settings.iterations,
+            margin,
+            plus,
+            minus,
+            tuned
+        );
+        if let Err(err) = write_preset(&settings.output, &tuned) {
+            eprintln!("failed to write {}: {}", settings.output, err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Rounds `theta` to weights. Boxes must keep a positive value.
+fn weights(theta: [f64; 3]) -> HeuristicWeights {
+    HeuristicWeights {
+        box_value: (theta[0].round() as i32).max(1),
+        chain_value: (theta[1].round() as i32).max(0),
+        parity_bonus: theta[2].round() as i32,
+    }
+}
+
+/// Average margin in boxes of `first` over `second`, each side playing
+/// first in half of the games.
+fn play_match(
+    settings: &Settings,
+    first: HeuristicWeights,
+    second: HeuristicWeights,
+    rng: &mut SmallRng,
+) -> f64 {
+    let mut margin = 0;
+    for game in 0..settings.games {
+        let seed = rng.gen();
+        margin += if game % 2 == 0 {
+            play_game(settings, [first, second], seed)
+        } else {
+            -play_game(settings, [second, first], seed)
+        };
+    }
+    margin as f64 / settings.games as f64
+}
+
+/// Plays one game between Red and Blue using `weights` in that order, and
+/// returns Red's boxes minus Blue's.
+fn play_game(settings: &Settings, weights: [HeuristicWeights; 2], seed: u64) -> i32 {
+    let mut board = BoardState::new(settings.width, settings.height);
+    let mut ais = [Player::Red, Player::Blue].map(|player| {
+        let mut ai = <AIMinMax as AI>::new(&board, player);
+        let options = AIOptions {
+            search: MinMaxOptions {
+                depth: settings.depth,
+                tie_epsilon: Some(0),
+                ..Default::default()
+            },
+            seed: Some(seed ^ seat(player) as u64),
+            weights: weights[seat(player)],
+            ..Default::default()
+        };
+        AI::set_options(&mut ai, options);
+        ai
+    });
+    while let Some(bar) = ais[seat(board.cur_turn)].next_move(&board) {
+        board.do_move(bar);
+    }
+    box_balance(&board)
+}
+
+/// Writes `weights` as a `DAB_CONFIG` preset.
+fn write_preset(path: &str, weights: &HeuristicWeights) -> std::io::Result<()> {
+    let preset = format!(
+        "{{\n  \"ai_box_value\": {},\n  \"ai_chain_value\": {},\n  \"ai_parity_bonus\": {}\n}}\n",
+        weights.box_value, weights.chain_value, weights.parity_bonus
+    );
+    fs::write(path, preset)
+}
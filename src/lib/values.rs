@@ -0,0 +1,158 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{BoardState, CellState, Player};
+
+/// Points each box of a board of `width` by `height` dots is worth, for
+/// games where some boxes count more than others, e.g. the center ones.
+/// Written like a `CellMask`, row by row, the rows separated by `/`, with a
+/// digit from 1 to 9 per box, e.g. `111/121/111` for a board of 4 by 4 dots
+/// whose center box is worth 2. Serialized in that form.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
+pub struct CellValues {
+    width: u32,
+    height: u32,
+    /// The value of each box, row by row.
+    values: Vec<u8>,
+}
+
+/// Highest value a box can have, so each fits in a digit.
+const MAX_VALUE: u32 = 9;
+
+impl CellValues {
+    /// Every box of a board of `width` by `height` dots worth 1 point, as
+    /// in the standard game.
+    pub fn uniform(width: u32, height: u32) -> Self {
+        let len = width.saturating_sub(1) * height.saturating_sub(1);
+        Self {
+            width,
+            height,
+            values: vec![1; len as usize],
+        }
+    }
+
+    /// Width in dots.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in dots.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Points the box at `col`, `row` is worth.
+    pub fn get(&self, col: u32, row: u32) -> u32 {
+        self.values[self.index(col, row)] as u32
+    }
+
+    /// Sets the points of the box at `col`, `row`, clamped to 1..=9.
+    pub fn set(&mut self, col: u32, row: u32, value: u32) {
+        let index = self.index(col, row);
+        self.values[index] = value.clamp(1, MAX_VALUE) as u8;
+    }
+
+    /// Whether every box is worth 1 point.
+    pub fn is_uniform(&self) -> bool {
+        self.values.iter().all(|value| *value == 1)
+    }
+
+    fn index(&self, col: u32, row: u32) -> usize {
+        assert!(
+            col + 1 < self.width && row + 1 < self.height,
+            "box ({},{}) outside {}x{} values",
+            col,
+            row,
+            self.width,
+            self.height
+        );
+        (row * (self.width - 1) + col) as usize
+    }
+}
+
+impl FromStr for CellValues {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s.split('/').collect::<Vec<_>>();
+        let cell_width = rows[0].len();
+        if cell_width == 0 || rows.iter().any(|row| row.len() != cell_width) {
+            return Err(());
+        }
+        let mut values = vec![];
+        for c in rows.concat().chars() {
+            match c.to_digit(10) {
+                Some(value @ 1..=MAX_VALUE) => values.push(value as u8),
+                _ => return Err(()),
+            }
+        }
+        Ok(Self {
+            width: cell_width as u32 + 1,
+            height: rows.len() as u32 + 1,
+            values,
+        })
+    }
+}
+
+impl fmt::Display for CellValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        for (index, row) in self.values.chunks((self.width - 1) as usize).enumerate() {
+            if index > 0 {
+                write!(f, "/")?;
+            }
+            for value in row {
+                write!(f, "{}", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Boxes worth more than a point count that many times in the score, the
+/// winner and the AI's evaluations.
+impl BoardState {
+    /// Points each box is worth.
+    pub fn values(&self) -> CellValues {
+        self.values
+            .clone()
+            .unwrap_or_else(|| CellValues::uniform(self.width, self.height))
+    }
+
+    /// Whether some boxes are worth more than a point.
+    pub fn has_values(&self) -> bool {
+        self.values.is_some()
+    }
+
+    /// Points the box at `col`, `row` is worth, 1 unless the game was set
+    /// up with other values.
+    pub fn cell_value(&self, col: u32, row: u32) -> u32 {
+        self.values.as_ref().map_or(1, |values| values.get(col, row))
+    }
+
+    /// Points `player` has: the values of the boxes they own, plus their
+    /// head start, see `Handicap`.
+    pub fn points(&self, player: Player) -> u32 {
+        (self.cells())
+            .filter(|(_, _, state)| *state == CellState::Player(player))
+            .map(|(col, row, _)| self.cell_value(col, row))
+            .sum::<u32>()
+            + self.head_start(player)
+    }
+
+    /// Sets the points of the boxes of a board. The values have the board's
+    /// size.
+    pub(crate) fn set_values(&mut self, values: &CellValues) {
+        self.values = (!values.is_uniform()).then(|| values.clone());
+        self.hashes = self.full_hashes();
+    }
+
+    /// Key of the values of the boxes, see `zobrist.rs`.
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    pub(crate) fn values_key(&self) -> u64 {
+        (self.cells()).fold(0, |key, (col, row, _)| {
+            key ^ self.value_key(col, row, self.cell_value(col, row))
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use gloo::events::EventListener;
+use js_sys::{Function, Reflect};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{console, VisibilityState};
+
+/// Keeps the screen from dimming while active, through the Screen Wake Lock
+/// API. Does nothing in browsers without that API. Browsers drop the lock
+/// whenever the page is hidden, so it is requested again once the page is
+/// visible while still active.
+pub struct WakeLock {
+    active: Rc<Cell<bool>>,
+    sentinel: Rc<RefCell<Option<JsValue>>>,
+    _visibility_listener: Option<EventListener>,
+}
+
+impl WakeLock {
+    pub fn new() -> Self {
+        let active = Rc::new(Cell::new(false));
+        let sentinel = Rc::new(RefCell::new(None));
+        let visibility_listener = web_sys::window()
+            .and_then(|window| window.document())
+            .filter(|_| is_supported())
+            .map(|document| {
+                let active = active.clone();
+                let sentinel = sentinel.clone();
+                let target = document.clone();
+                EventListener::new(&target, "visibilitychange", move |_| {
+                    if active.get() && document.visibility_state() == VisibilityState::Visible {
+                        request(sentinel.clone(), active.clone());
+                    }
+                })
+            });
+        Self {
+            active,
+            sentinel,
+            _visibility_listener: visibility_listener,
+        }
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        if self.active.replace(active) == active || !is_supported() {
+            return;
+        }
+        if active {
+            request(self.sentinel.clone(), self.active.clone());
+        } else {
+            release(&self.sentinel);
+        }
+    }
+}
+
+impl Drop for WakeLock {
+    fn drop(&mut self) {
+        release(&self.sentinel);
+    }
+}
+
+fn is_supported() -> bool {
+    web_sys::window()
+        .map(|window| Reflect::has(&window.navigator(), &"wakeLock".into()).unwrap_or(false))
+        .unwrap_or(false)
+}
+
+fn request(sentinel: Rc<RefCell<Option<JsValue>>>, active: Rc<Cell<bool>>) {
+    spawn_local(async move {
+        match request_screen_lock().await {
+            // The lock may have been given up while the request was pending.
+            Ok(lock) if active.get() => *sentinel.borrow_mut() = Some(lock),
+            Ok(lock) => {
+                let _ = method(&lock, "release").and_then(|release| release.call0(&lock));
+            }
+            Err(err) => console::warn_2(&"wake lock request failed:".into(), &err),
+        }
+    });
+}
+
+fn release(sentinel: &RefCell<Option<JsValue>>) {
+    if let Some(lock) = sentinel.borrow_mut().take() {
+        let _ = method(&lock, "release").and_then(|release| release.call0(&lock));
+    }
+}
+
+async fn request_screen_lock() -> Result<JsValue, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let wake_lock = Reflect::get(&window.navigator(), &"wakeLock".into())?;
+    let promise = method(&wake_lock, "request")?.call1(&wake_lock, &"screen".into())?;
+    JsFuture::from(js_sys::Promise::from(promise)).await
+}
+
+fn method(target: &JsValue, name: &str) -> Result<Function, JsValue> {
+    Reflect::get(target, &name.into())?.dyn_into()
+}
@@ -0,0 +1,233 @@
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{console, MessageEvent, Worker};
+use yew::Callback;
+
+#[cfg(feature = "ai")]
+use std::cell::RefCell;
+#[cfg(feature = "ai")]
+use std::collections::HashMap;
+
+#[cfg(feature = "ai")]
+use crate::ai::{self, AI};
+#[cfg(feature = "ai")]
+use crate::Player;
+use crate::{BarDirection, BarId, BoardState, Difficulty, Engine, SearchStats};
+
+/// Script of the worker, built by trunk from `src/bin/worker.rs`.
+const WORKER_URL: &str = "./worker.js";
+
+/// One AI search to run off the main thread: the AI plays the side to move
+/// in `board`.
+pub struct SearchRequest {
+    pub engine: Engine,
+    pub difficulty: Difficulty,
+    pub board: BoardState,
+}
+
+/// Messages from the worker to the component that started it.
+pub enum WorkerEvent {
+    /// Best move found so far by the search with the given id, which is
+    /// still running.
+    Progress(u32, BarId),
+    /// Result of the search with the given id, `None` if the AI found no
+    /// move, and the statistics of the search if the AI keeps any.
+    Move(u32, Option<BarId>, Option<SearchStats>),
+    /// The worker could not be started or crashed, e.g. because the
+    /// deployment lacks its script. Searches must run on the main thread.
+    Failed,
+}
+
+/// Runs the AI's searches in a Web Worker, so the page stays responsive
+/// while the AI thinks. Requests and replies are plain strings:
+/// `"<id> <engine> <difficulty> <fen>"` and `"<id> <bar> [<stats>]"`, where
+/// the bar is written as `H 2 3` or `-` for no move, and the statistics as
+/// `<nodes> <table_hits> <depth> <max_depth> <duration_ms> <solved>`
+/// followed by the bars of the principal variation. While searching, the
+/// worker also posts the best move so far as `"<id> + <bar>"`.
+pub struct AIWorker {
+    worker: Worker,
+    _onmessage: Closure<dyn Fn(MessageEvent)>,
+    _onerror: Closure<dyn Fn()>,
+}
+
+impl AIWorker {
+    /// Returns `None` if the browser cannot start the worker.
+    pub fn new(on_event: Callback<WorkerEvent>) -> Option<Self> {
+        let worker = match Worker::new(WORKER_URL) {
+            Ok(worker) => worker,
+            Err(err) => {
+                console::error_2(&"failed to start AI worker:".into(), &err);
+                return None;
+            }
+        };
+        let onmessage = {
+            let on_event = on_event.clone();
+            Closure::wrap(Box::new(move |e: MessageEvent| {
+                match e.data().as_string().as_deref().and_then(parse_reply) {
+                    Some(event) => on_event.emit(event),
+                    None => console::error_1(&"invalid reply from AI worker".into()),
+                }
+            }) as Box<dyn Fn(MessageEvent)>)
+        };
+        let onerror = Closure::wrap(Box::new(move || {
+            console::error_1(&"AI worker failed, searching on the main thread".into());
+            on_event.emit(WorkerEvent::Failed);
+        }) as Box<dyn Fn()>);
+        worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        worker.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+        Some(Self {
+            worker,
+            _onmessage: onmessage,
+            _onerror: onerror,
+        })
+    }
+
+    /// Starts a search, whose events carry `id`.
+    pub fn search(&mut self, id: u32, request: &SearchRequest) {
+        let message = format!(
+            "{} {} {} {}",
+            id,
+            request.engine,
+            request.difficulty,
+            request.board.to_fen()
+        );
+        if let Err(err) = self.worker.post_message(&message.into()) {
+            console::error_2(&"failed to send search to AI worker:".into(), &err);
+        }
+    }
+}
+
+impl Drop for AIWorker {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+fn parse_reply(reply: &str) -> Option<WorkerEvent> {
+    let mut parts = reply.split(' ').peekable();
+    let id = parts.next()?.parse().ok()?;
+    if parts.next_if_eq(&"+").is_some() {
+        return Some(WorkerEvent::Progress(id, parse_bar(&mut parts)??));
+    }
+    let bar = parse_bar(&mut parts)?;
+    let stats = match parts.next() {
+        Some(nodes) => Some(SearchStats {
+            nodes: nodes.parse().ok()?,
+            table_hits: parts.next()?.parse().ok()?,
+            depth: parts.next()?.parse().ok()?,
+            max_depth: parts.next()?.parse().ok()?,
+            duration_ms: parts.next()?.parse().ok()?,
+            solved: parts.next()?.parse().ok()?,
+            principal_variation: parse_line(&mut parts)?,
+        }),
+        None => None,
+    };
+    Some(WorkerEvent::Move(id, bar, stats))
+}
+
+/// Reads a bar written by `write_bar`. Returns `Some(None)` for no move.
+pub(crate) fn parse_bar<'a>(parts: &mut impl Iterator<Item = &'a str>) -> Option<Option<BarId>> {
+    let direction = match parts.next()? {
+        "-" => return Some(None),
+        "H" => BarDirection::Horizontal,
+        "V" => BarDirection::Vertical,
+        _ => return None,
+    };
+    Some(Some(BarId {
+        direction,
+        col: parts.next()?.parse().ok()?,
+        row: parts.next()?.parse().ok()?,
+    }))
+}
+
+/// Reads the bars written by `write_bar` up to the end of the reply.
+fn parse_line<'a>(parts: &mut impl Iterator<Item = &'a str>) -> Option<Vec<BarId>> {
+    let mut parts = parts.peekable();
+    let mut line = Vec::new();
+    while parts.peek().is_some() {
+        line.push(parse_bar(&mut parts)??);
+    }
+    Some(line)
+}
+
+#[cfg(feature = "ai")]
+fn write_bar(bar: Option<BarId>) -> String {
+    match bar {
+        Some(bar) => {
+            let direction = match bar.direction {
+                BarDirection::Horizontal => 'H',
+                BarDirection::Vertical => 'V',
+            };
+            format!("{} {} {}", direction, bar.col, bar.row)
+        }
+        None => "-".to_string(),
+    }
+}
+
+/// Entry point of the worker: answers the searches posted by `AIWorker`.
+#[cfg(feature = "ai")]
+pub fn run_ai_worker() {
+    use web_sys::DedicatedWorkerGlobalScope;
+
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    let reply_scope = scope.clone();
+    let ais = RefCell::new(WorkerAIs::default());
+    let onmessage = Closure::wrap(Box::new(move |e: MessageEvent| {
+        let mut post = |reply: String| {
+            if let Err(err) = reply_scope.post_message(&reply.into()) {
+                console::error_2(&"failed to send AI move:".into(), &err);
+            }
+        };
+        let mut ais = ais.borrow_mut();
+        let request = e.data().as_string();
+        if request
+            .and_then(|request| answer(&mut ais, &request, &mut post))
+            .is_none()
+        {
+            console::error_1(&"invalid search request".into());
+        }
+    }) as Box<dyn Fn(MessageEvent)>);
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+}
+
+/// The AIs of earlier searches by engine, side and board size, kept so the
+/// next search of the same game reuses their transposition tables.
+#[cfg(feature = "ai")]
+type WorkerAIs = HashMap<(Engine, Player, u32, u32), Box<dyn AI>>;
+
+/// Runs the search in `request` and posts its progress and result.
+#[cfg(feature = "ai")]
+fn answer(ais: &mut WorkerAIs, request: &str, post: &mut dyn FnMut(String)) -> Option<()> {
+    let mut parts = request.splitn(4, ' ');
+    let id: u32 = parts.next()?.parse().ok()?;
+    let engine = parts.next()?.parse().ok()?;
+    let difficulty: Difficulty = parts.next()?.parse().ok()?;
+    let board = BoardState::from_fen(parts.next()?).ok()?;
+    let mut on_progress = |bar| post(format!("{} + {}", id, write_bar(Some(bar))));
+    let key = (engine, board.cur_turn, board.width, board.height);
+    let ai = ais
+        .entry(key)
+        .or_insert_with(|| ai::new_ai(engine, &board, board.cur_turn));
+    ai.set_options(difficulty.into());
+    let bar = ai.next_move_with_progress(&board, &mut on_progress);
+    let stats = ai.last_search_stats().cloned();
+    let mut reply = format!("{} {}", id, write_bar(bar));
+    if let Some(stats) = stats {
+        reply += &format!(
+            " {} {} {} {} {} {}",
+            stats.nodes,
+            stats.table_hits,
+            stats.depth,
+            stats.max_depth,
+            stats.duration_ms,
+            stats.solved
+        );
+        for bar in stats.principal_variation {
+            reply += &format!(" {}", write_bar(Some(bar)));
+        }
+    }
+    post(reply);
+    Some(())
+}
@@ -0,0 +1,167 @@
+use crate::symmetry::MAX_SYMMETRIES;
+use crate::{BarDirection, BarId, BoardState, CellState, Handicap, Player, Ruleset};
+
+const BAR_KEYS: u64 = 1;
+const RED_CELL_KEYS: u64 = 2;
+const BLUE_CELL_KEYS: u64 = 3;
+const TURN_KEYS: u64 = 4;
+const RULES_KEYS: u64 = 5;
+const MASK_KEYS: u64 = 6;
+const VALUE_KEYS: u64 = 7;
+const HANDICAP_KEYS: u64 = 8;
+
+/// Zobrist hashing: every bar, box owner, box left out of the board, box
+/// worth more than a point, the side to move, the rules and the head start
+/// of a handicap have a fixed pseudo-random key, and a
+/// position hashes to the XOR of the keys of its features. Moves and their
+/// undo toggle single keys, so the hash is kept up to date in
+/// `BoardState`'s setters.
+///
+/// Bar owners are left out: who drew a bar does not change how the game
+/// can continue, so positions differing only in bar colors share a hash.
+///
+/// The position is hashed under each of the board's symmetries as well, by
+/// taking the keys of the mapped features, so symmetric positions can be
+/// recognized, see `symmetry.rs`.
+impl BoardState {
+    /// Zobrist hash of the position, kept up to date by every move, so
+    /// positions can be keyed on cheaply. Positions that differ only in who
+    /// drew the bars share a hash, and the hash of a given position does not
+    /// change between builds.
+    pub fn hash(&self) -> u64 {
+        // The identity is the first symmetry.
+        self.hashes[0]
+    }
+
+    pub(crate) fn bar_key(&self, bar: BarId) -> u64 {
+        let index = match bar.direction {
+            BarDirection::Vertical => bar.row * self.vstates.width + bar.col,
+            BarDirection::Horizontal => {
+                self.vstates.length + bar.row * self.hstates.width + bar.col
+            }
+        };
+        key(BAR_KEYS, index)
+    }
+
+    pub(crate) fn cell_key(&self, col: u32, row: u32, state: CellState) -> u64 {
+        let index = row * (self.width - 1) + col;
+        match state {
+            CellState::Free => 0,
+            CellState::Neutral => key(MASK_KEYS, index),
+            CellState::Player(Player::Red) => key(RED_CELL_KEYS, index),
+            CellState::Player(Player::Blue) => key(BLUE_CELL_KEYS, index),
+        }
+    }
+
+    /// Zero for boxes worth a point, so boards without values hash as they
+    /// did before there were values.
+    pub(crate) fn value_key(&self, col: u32, row: u32, value: u32) -> u64 {
+        match value {
+            1 => 0,
+            _ => key(VALUE_KEYS, (row * (self.width - 1) + col) * 16 + value),
+        }
+    }
+
+    pub(crate) fn turn_key(player: Player) -> u64 {
+        match player {
+            Player::Red => 0,
+            Player::Blue => key(TURN_KEYS, 0),
+        }
+    }
+
+    /// Zero for the standard rules, so their positions hash as they did
+    /// before there were variations.
+    pub(crate) fn rules_key(rules: Ruleset) -> u64 {
+        let misere = if rules.misere { key(RULES_KEYS, 0) } else { 0 };
+        let swedish = if rules.swedish { key(RULES_KEYS, 1) } else { 0 };
+        let alternate_turns = if rules.alternate_turns {
+            key(RULES_KEYS, 2)
+        } else {
+            0
+        };
+        let torus = if rules.torus { key(RULES_KEYS, 3) } else { 0 };
+        misere ^ swedish ^ alternate_turns ^ torus
+    }
+
+    /// Zero without a head start, so even games hash as they did before
+    /// there were handicaps. The bars of a handicap are hashed as bars.
+    pub(crate) fn handicap_key(handicap: Option<Handicap>) -> u64 {
+        match handicap {
+            Some(Handicap { player, boxes, .. }) if boxes > 0 => {
+                key(HANDICAP_KEYS, boxes << 1 | (player == Player::Blue) as u32)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Keys of `bar` under each symmetry, zero for the symmetries the board
+    /// lacks or is not hashed under, see `BoardState::symmetries`.
+    pub(crate) fn bar_keys(&self, bar: BarId) -> [u64; MAX_SYMMETRIES] {
+        let mut keys = [0; MAX_SYMMETRIES];
+        for symmetry in self.symmetries() {
+            keys[symmetry.index()] = self.bar_key(self.transform_bar(symmetry, bar));
+        }
+        keys
+    }
+
+    /// Keys of the box at `col`, `row` owned as `state` under each
+    /// symmetry, zero for the symmetries the board lacks.
+    pub(crate) fn cell_keys(&self, col: u32, row: u32, state: CellState) -> [u64; MAX_SYMMETRIES] {
+        let mut keys = [0; MAX_SYMMETRIES];
+        for symmetry in self.symmetries() {
+            let (col, row) = self.transform_cell(symmetry, col, row);
+            keys[symmetry.index()] = self.cell_key(col, row, state);
+        }
+        keys
+    }
+
+    /// Keys of the value of the box at `col`, `row` under each symmetry,
+    /// zero for the symmetries the board lacks.
+    pub(crate) fn value_keys(&self, col: u32, row: u32) -> [u64; MAX_SYMMETRIES] {
+        let value = self.cell_value(col, row);
+        let mut keys = [0; MAX_SYMMETRIES];
+        for symmetry in self.symmetries() {
+            let (col, row) = self.transform_cell(symmetry, col, row);
+            keys[symmetry.index()] = self.value_key(col, row, value);
+        }
+        keys
+    }
+
+    /// Hashes computed from scratch, for positions built without the
+    /// setters.
+    pub(crate) fn full_hashes(&self) -> [u64; MAX_SYMMETRIES] {
+        let key = Self::turn_key(self.cur_turn)
+            ^ Self::rules_key(self.rules)
+            ^ Self::handicap_key(self.handicap);
+        let mut hashes = [key; MAX_SYMMETRIES];
+        let bars = self
+            .bars()
+            .filter(|(_, state)| *state != CellState::Free)
+            .map(|(bar, _)| self.bar_keys(bar));
+        let cells = self
+            .cells()
+            .map(|(col, row, state)| self.cell_keys(col, row, state));
+        let values = self
+            .cells()
+            .map(|(col, row, _)| self.value_keys(col, row));
+        for keys in bars.chain(cells).chain(values) {
+            toggle_keys(&mut hashes, keys);
+        }
+        hashes
+    }
+}
+
+/// XORs each key into the hash under the same symmetry.
+pub(crate) fn toggle_keys(hashes: &mut [u64; MAX_SYMMETRIES], keys: [u64; MAX_SYMMETRIES]) {
+    for (hash, key) in hashes.iter_mut().zip(keys) {
+        *hash ^= key;
+    }
+}
+
+/// SplitMix64 of the feature kind and index, so no key table is needed.
+pub(crate) fn key(kind: u64, index: u32) -> u64 {
+    let mut z = ((kind << 32) | index as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
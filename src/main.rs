@@ -1,5 +1,3 @@
-mod lib;
-
 fn main() {
-    yew::start_app::<lib::AppComp>();
+    yew::start_app::<dots_and_boxes::AppComp>();
 }
@@ -0,0 +1,162 @@
+use dots_and_boxes::{
+    BarId, BoardState, Chain, ChainCell, ChainKind, ChainMistake, ChainParity, Player,
+};
+
+fn bar(notation: &str) -> BarId {
+    notation.parse().expect("valid notation")
+}
+
+fn board(width: u32, height: u32, moves: &[&str]) -> BoardState {
+    let moves = moves
+        .iter()
+        .map(|notation| bar(notation))
+        .collect::<Vec<_>>();
+    BoardState::from_moves(width, height, Player::Red, &moves).expect("legal moves")
+}
+
+/// A chain of `kind` through `cells`, given as column, row and free bars.
+fn chain(kind: ChainKind, cells: &[(u32, u32, u32)]) -> Chain {
+    let cells = cells
+        .iter()
+        .map(|&(col, row, open_edges)| ChainCell {
+            col,
+            row,
+            open_edges,
+        })
+        .collect();
+    Chain { kind, cells }
+}
+
+/// A row of three boxes open at both ends, listed from the far end. Red
+/// has to open it, so Blue takes it.
+#[test]
+fn one_long_chain() {
+    let row = board(4, 2, &["h0.0", "h1.0", "h2.0", "h0.1", "h1.1", "h2.1"]);
+    let expected = chain(ChainKind::Chain, &[(2, 0, 2), (1, 0, 2), (0, 0, 2)]);
+    assert_eq!(row.chains(), vec![expected]);
+    assert_eq!(
+        row.chain_parity(),
+        ChainParity {
+            long_chains: 1,
+            loops: 0,
+            double_crosses: 0,
+            controller: Some(Player::Blue),
+        }
+    );
+}
+
+/// Four boxes around the center dot, with only the bars between them
+/// free. Red has to open the loop, and Blue takes all of it, which counts
+/// as one double-cross: the last bar completes two boxes.
+#[test]
+fn loop_only() {
+    let square = board(
+        3,
+        3,
+        &[
+            "h0.0", "h1.0", "h0.2", "h1.2", "v0.0", "v0.1", "v2.0", "v2.1",
+        ],
+    );
+    let expected = chain(
+        ChainKind::Loop,
+        &[(0, 1, 2), (1, 1, 2), (1, 0, 2), (0, 0, 2)],
+    );
+    assert_eq!(square.chains(), vec![expected]);
+    assert_eq!(
+        square.chain_parity(),
+        ChainParity {
+            long_chains: 0,
+            loops: 1,
+            double_crosses: 1,
+            controller: Some(Player::Blue),
+        }
+    );
+}
+
+/// The loop of `loop_only` next to two rows of three boxes. Blue has to
+/// open something, and Red keeps control with one double-cross on the
+/// first chain and two on the loop.
+#[test]
+fn chains_and_loops() {
+    let position = board(
+        6,
+        3,
+        &[
+            "h0.0", "h1.0", "h0.2", "h1.2", "v0.0", "v0.1", "v2.0", "v2.1", "h3.0", "h4.0", "h2.1",
+            "h3.1", "h4.1", "h3.2", "h4.2",
+        ],
+    );
+    assert_eq!(
+        position.chains(),
+        vec![
+            chain(
+                ChainKind::Loop,
+                &[(0, 1, 2), (1, 1, 2), (1, 0, 2), (0, 0, 2)]
+            ),
+            chain(ChainKind::Chain, &[(4, 0, 2), (3, 0, 2), (2, 0, 2)]),
+            chain(ChainKind::Chain, &[(4, 1, 2), (3, 1, 2), (2, 1, 2)]),
+        ]
+    );
+    assert_eq!(
+        position.chain_parity(),
+        ChainParity {
+            long_chains: 2,
+            loops: 1,
+            double_crosses: 3,
+            controller: Some(Player::Red),
+        }
+    );
+}
+
+/// The top middle box has three free bars, so the columns below the top
+/// row end there: two long chains on the sides and a short one between
+/// them. Sixteen dots and two long chains favor Red, who moved first.
+#[test]
+fn junction_splits_chains() {
+    let position = board(
+        4,
+        4,
+        &[
+            "h0.0", "h1.0", "h2.0", "v0.0", "v0.1", "v0.2", "v1.1", "v1.2", "v2.1", "v2.2", "v3.0",
+            "v3.1", "v3.2",
+        ],
+    );
+    assert_eq!(position.open_edges(1, 0), 3);
+    assert_eq!(
+        position.chains(),
+        vec![
+            chain(ChainKind::Chain, &[(0, 2, 2), (0, 1, 2), (0, 0, 2)]),
+            chain(ChainKind::Chain, &[(2, 2, 2), (2, 1, 2), (2, 0, 2)]),
+            chain(ChainKind::Chain, &[(1, 2, 2), (1, 1, 2)]),
+        ]
+    );
+    assert_eq!(
+        position.chain_parity(),
+        ChainParity {
+            long_chains: 2,
+            loops: 0,
+            double_crosses: 1,
+            controller: Some(Player::Red),
+        }
+    );
+}
+
+/// A row of three boxes with only the bars between them and at its ends
+/// free: any of them opens the whole chain. Once it is open, taking a box
+/// is no mistake.
+#[test]
+fn opening_a_long_chain_is_a_mistake() {
+    let row = board(4, 2, &["h0.0", "h1.0", "h2.0", "h0.1", "h1.1", "h2.1"]);
+    let Some(ChainMistake::OpensLongChain(chain)) = row.chain_mistake(bar("v1.0")) else {
+        panic!("v1.0 opens the chain");
+    };
+    assert_eq!(chain.kind, ChainKind::Chain);
+    assert_eq!(chain.cells.len(), 3);
+    let opened = board(
+        4,
+        2,
+        &["h0.0", "h1.0", "h2.0", "h0.1", "h1.1", "h2.1", "v0.0"],
+    );
+    assert_eq!(opened.chain_mistake(bar("v1.0")), None);
+    assert_eq!(opened.chain_mistake(bar("v0.0")), None);
+}
@@ -0,0 +1,28 @@
+use dots_and_boxes::{BarId, BoardState, CoinString, Player, StringEnd};
+
+fn bar(notation: &str) -> BarId {
+    notation.parse().expect("valid notation")
+}
+
+/// Every free bar is a string, tied to the ground on the border, and every
+/// free box a coin hanging by as many strings as it has free bars.
+#[test]
+fn strings_and_coins() {
+    let empty = BoardState::from_moves(3, 2, Player::Red, &[]).expect("supported size");
+    assert_eq!(empty.strings().count(), 7);
+    let grounded = (empty.strings()).filter(|string| string.ends.contains(&StringEnd::Ground));
+    assert_eq!(grounded.count(), 6);
+    assert!(empty.strings().any(|string| string
+        == CoinString {
+            bar: bar("v1.0"),
+            ends: [StringEnd::Coin(0, 0), StringEnd::Coin(1, 0)],
+        }));
+    let bars = ["h0.0", "v0.0", "v1.0", "h0.1", "h1.0"].map(bar);
+    let board = BoardState::from_moves(3, 2, Player::Red, &bars).expect("legal moves");
+    assert_eq!(board.coins().collect::<Vec<_>>(), [(1, 0)]);
+    let strings = board.strings().collect::<Vec<_>>();
+    assert_eq!(strings.len() as u32, board.open_edges(1, 0));
+    for string in strings {
+        assert_eq!(string.ends, [StringEnd::Coin(1, 0), StringEnd::Ground]);
+    }
+}
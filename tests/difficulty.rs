@@ -0,0 +1,67 @@
+#![cfg(feature = "ai")]
+
+use dots_and_boxes::{BarId, BoardState, Difficulty, GameBuilder, GameTrait, Player};
+
+/// A position with 13 free bars, Blue to move, which the endgame solver
+/// can take on. Only v3.1 keeps Blue ahead with perfect play; every other
+/// move loses at least a box.
+fn endgame() -> BoardState {
+    let moves = [
+        "v1.0", "v3.0", "v1.1", "v0.2", "v2.2", "h1.0", "h2.0", "h0.1", "h2.2", "h0.3", "h1.2",
+    ]
+    .map(|bar| bar.parse().expect("valid notation"));
+    BoardState::from_moves(4, 4, Player::Red, &moves).expect("legal moves")
+}
+
+/// The moves the AI plays for Blue at `difficulty` from `endgame`, one per
+/// try, and whether it solved the position for each of them.
+fn replies(difficulty: Difficulty, tries: usize) -> Vec<(BarId, bool)> {
+    let mut game = GameBuilder::new(4, 4)
+        .difficulty(Player::Blue, difficulty)
+        .build()
+        .expect("supported size");
+    (0..tries)
+        .map(|_| {
+            game.set_board(endgame());
+            assert_eq!(game.current_turn(), Player::Blue);
+            game.play_ai_move().expect("the AI plays Blue");
+            let played = game.history().last().expect("a move was played").bar;
+            let solved = game.last_search_stats().is_some_and(|stats| stats.solved);
+            (played, solved)
+        })
+        .collect()
+}
+
+/// Hard plays the endgame perfectly, while Easy keeps searching with its
+/// heuristic and makes its mistakes to the end.
+#[test]
+fn easy_blunders_in_the_endgame() {
+    let best: BarId = "v3.1".parse().expect("valid notation");
+    assert!(replies(Difficulty::Hard, 3)
+        .into_iter()
+        .all(|reply| reply == (best, true)));
+    let easy = replies(Difficulty::Easy, 50);
+    assert!(easy.iter().all(|(_, solved)| !solved));
+    assert!(easy.iter().any(|(bar, _)| *bar != best));
+}
+
+/// Teaching reviews judge the human's moves the same whatever the
+/// difficulty of the AI they play against.
+#[test]
+fn reviews_do_not_depend_on_the_difficulty() {
+    for difficulty in Difficulty::ALL {
+        let mut game = GameBuilder::new(3, 4)
+            .difficulty(Player::Blue, difficulty)
+            .build()
+            .expect("supported size");
+        // Only v1.0 keeps Red level; every other move loses by four boxes.
+        let moves = ["v0.0", "v2.0", "v1.1", "v1.2", "h0.2", "h1.2"]
+            .map(|bar| bar.parse().expect("valid notation"));
+        let board = BoardState::from_moves(3, 4, Player::Red, &moves).expect("legal moves");
+        game.set_board(board);
+        let blunder = "h0.0".parse().expect("valid notation");
+        let best = "v1.0".parse().expect("valid notation");
+        assert_eq!(game.review_move(blunder), Some(best), "{difficulty:?}");
+        assert_eq!(game.review_move(best), None, "{difficulty:?}");
+    }
+}
@@ -0,0 +1,52 @@
+use dots_and_boxes::{decode_moves, encode_moves, BarId, BoardState, DecodeError, Player};
+
+fn moves(notation: &[&str]) -> Vec<BarId> {
+    (notation.iter())
+        .map(|bar| bar.parse().expect("valid notation"))
+        .collect()
+}
+
+/// The first bars take a byte each, the last horizontal bars of a large
+/// board two.
+#[test]
+fn moves_round_trip() {
+    let bars = moves(&["v0.0", "h1.0", "h48.49", "v49.48"]);
+    let bytes = encode_moves(50, 50, &bars);
+    assert_eq!(bytes.len(), 7);
+    assert_eq!(decode_moves(50, 50, &bytes), Ok(bars));
+    assert_eq!(
+        decode_moves(50, 50, &bytes[..6]),
+        Err(DecodeError::Truncated)
+    );
+    assert_eq!(decode_moves(3, 3, &bytes), Err(DecodeError::InvalidMove(1)));
+}
+
+/// A position with boxes of both players and Blue to move.
+#[test]
+fn position_round_trip() {
+    let bars = moves(&[
+        "h0.0", "v0.0", "v1.0", "h0.1", "h1.2", "v2.1", "h1.1", "v1.1", "h2.0",
+    ]);
+    let board = BoardState::from_moves(4, 3, Player::Red, &bars).expect("legal moves");
+    let bytes = board.to_bytes();
+    assert_eq!(bytes.len(), 6);
+    assert_eq!(BoardState::from_bytes(&bytes), Ok(board.clone()));
+    assert_eq!(
+        BoardState::from_bytes(&bytes).map(|board| board.hash()),
+        Ok(board.hash())
+    );
+    assert_eq!(
+        BoardState::from_bytes(&bytes[..5]),
+        Err(DecodeError::Truncated)
+    );
+    let mut padded = bytes.clone();
+    padded.push(0);
+    assert_eq!(
+        BoardState::from_bytes(&padded),
+        Err(DecodeError::TrailingBytes)
+    );
+    assert_eq!(
+        BoardState::from_bytes(&[1, 3, 0, 0, 0]),
+        Err(DecodeError::Size)
+    );
+}
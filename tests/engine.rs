@@ -0,0 +1,75 @@
+#![cfg(feature = "ai")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The lines the `engine` binary answers `commands` with.
+fn run_engine(commands: &[&str]) -> Vec<String> {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_engine"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("engine starts");
+    let mut stdin = engine.stdin.take().expect("piped stdin");
+    for command in commands {
+        writeln!(stdin, "{command}").expect("engine reads commands");
+    }
+    drop(stdin);
+    let output = engine.wait_with_output().expect("engine exits");
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).expect("utf-8 output");
+    output.lines().map(str::to_string).collect()
+}
+
+/// Blue can take the left box of two with `v1.0`, then the right one once
+/// Red has to draw its third bar.
+#[test]
+fn plays_the_capture() {
+    let lines = run_engine(&[
+        "setoption name Seed value 1",
+        "position startpos 3x2 moves h0.0 h0.1 v0.0",
+        "go movetime 50",
+        "position startpos 3x2 moves h0.0 h0.1 v0.0 v1.0 h1.0 h1.1 v2.0",
+        "go",
+    ]);
+    assert!(lines[0].starts_with("info "), "{}", lines[0]);
+    assert_eq!(lines[1], "bestmove v1.0");
+    assert_eq!(lines.last().map(String::as_str), Some("bestmove (none)"));
+}
+
+/// `depth` sets the depth of the search wherever `movetime` is given.
+#[test]
+fn searches_to_the_given_depth() {
+    let lines = run_engine(&[
+        "position startpos 5x5 moves h0.0",
+        "go movetime 1000 depth 1",
+        "go depth 1 movetime 1000",
+    ]);
+    assert!(lines[0].starts_with("info depth 1 "), "{}", lines[0]);
+    assert!(lines[1].starts_with("bestmove "), "{}", lines[1]);
+    assert!(lines[2].starts_with("info depth 1 "), "{}", lines[2]);
+}
+
+#[test]
+fn reports_invalid_commands() {
+    let lines = run_engine(&[
+        "position startpos 9",
+        "position startpos 3x2 moves h0.0 h0.0",
+        "position fen x",
+        "go depth 0",
+        "isready",
+    ]);
+    assert_eq!(lines[0], "info string invalid size 9");
+    assert!(
+        lines[1].starts_with("info string illegal move h0.0"),
+        "{}",
+        lines[1]
+    );
+    assert!(
+        lines[2].starts_with("info string invalid fen x"),
+        "{}",
+        lines[2]
+    );
+    assert_eq!(lines[3], "info string invalid go parameter depth");
+    assert_eq!(lines[4], "readyok");
+}
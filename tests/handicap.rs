@@ -0,0 +1,59 @@
+use dots_and_boxes::{
+    BoardState, CellState, GameConfig, GameError, GameTrait, Handicap, HotSeatGame, Player, Ruleset,
+};
+
+fn config(handicap: Handicap, rules: Ruleset) -> GameConfig {
+    GameConfig {
+        handicap: Some(handicap),
+        rules,
+        ..GameConfig::new(4, 4)
+    }
+}
+
+const BLUE: Handicap = Handicap {
+    player: Player::Blue,
+    boxes: 2,
+    bars: 3,
+};
+
+/// The weaker player starts with points and safe bars of their color, the
+/// same ones after a restart, and keeps them through FEN strings and
+/// packed bytes.
+#[test]
+fn head_start() {
+    let mut game = HotSeatGame::new(4, 4);
+    game.reconfigure(config(BLUE, Ruleset::default()))
+        .expect("handicap fits");
+    assert_eq!(game.score(Player::Blue), 2);
+    assert_eq!(game.score(Player::Red), 0);
+    assert_eq!(game.current_turn(), Player::Red);
+    let board = game.board().clone();
+    let blue_bars = (board.bars()).filter(|(_, state)| *state == CellState::Player(Player::Blue));
+    assert_eq!(blue_bars.count(), 3);
+    assert!((board.cells()).all(|(col, row, _)| board.open_edges(col, row) >= 2));
+    assert!(board.to_fen().ends_with("/b2+3"));
+    assert_eq!(BoardState::from_fen(&board.to_fen()), Ok(board.clone()));
+    assert_eq!(BoardState::from_bytes(&board.to_bytes()), Ok(board.clone()));
+    game.do_move("h0.0".parse().expect("valid notation"));
+    game.restart(Player::Red);
+    assert_eq!(game.board(), &board);
+}
+
+/// In misère play the head start goes to the opponent, and a handicap has
+/// to fit the board.
+#[test]
+fn misere_and_invalid_handicaps() {
+    let mut game = HotSeatGame::new(4, 4);
+    let misere = Ruleset {
+        misere: true,
+        ..Ruleset::default()
+    };
+    game.reconfigure(config(BLUE, misere))
+        .expect("handicap fits");
+    assert_eq!(game.score(Player::Red), 2);
+    let too_many = Handicap { boxes: 10, ..BLUE };
+    assert_eq!(
+        game.reconfigure(config(too_many, Ruleset::default())),
+        Err(GameError::InvalidHandicap)
+    );
+}
@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use dots_and_boxes::{BarDirection, BarId, CellState, GameTrait, HotSeatGame};
+
+const SIZE: u32 = 20;
+const TIME_BUDGET: Duration = Duration::from_secs(2);
+
+/// Plays a full 20x20 hot-seat game in a pseudo-random order and checks
+/// that it finishes within the time budget with every box taken.
+#[test]
+fn random_20x20_game() {
+    let mut bars = vec![];
+    for row in 0..SIZE {
+        for col in 0..SIZE {
+            if col + 1 < SIZE {
+                bars.push(BarId {
+                    direction: BarDirection::Horizontal,
+                    col,
+                    row,
+                });
+            }
+            if row + 1 < SIZE {
+                bars.push(BarId {
+                    direction: BarDirection::Vertical,
+                    col,
+                    row,
+                });
+            }
+        }
+    }
+    // Fisher-Yates with a fixed LCG, so the game is the same on every run.
+    let mut seed: u64 = 0x5eed;
+    for i in (1..bars.len()).rev() {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        bars.swap(i, (seed >> 33) as usize % (i + 1));
+    }
+
+    let start = Instant::now();
+    let mut game = HotSeatGame::new(SIZE, SIZE);
+    for bar in bars {
+        assert!(game.do_move(bar).is_legal(), "move {:?} rejected", bar);
+    }
+    let elapsed = start.elapsed();
+
+    for row in 0..SIZE - 1 {
+        for col in 0..SIZE - 1 {
+            assert_ne!(game.cell_get(col, row), CellState::Free);
+        }
+    }
+    assert!(elapsed < TIME_BUDGET, "game took {:?}", elapsed);
+}
@@ -0,0 +1,107 @@
+use dots_and_boxes::{
+    CellState, GameError, GameMode, LatticeBoard, LatticeGame, LatticeKind, Player,
+};
+
+/// The edge between the dots at `from` and `to`.
+fn edge(board: &LatticeBoard, from: u32, to: u32) -> u32 {
+    (0..board.edge_count())
+        .find(|edge| {
+            let dots = board.edge_dots(*edge);
+            dots == [from, to] || dots == [to, from]
+        })
+        .expect("dots are neighbors")
+}
+
+/// A 3x3 triangular board has 16 edges around its 8 triangles, and closing
+/// a triangle takes it and earns another move, which undo takes back.
+#[test]
+fn triangles() {
+    let mut game = LatticeGame::new(LatticeKind::Triangles, 3, 3).expect("valid size");
+    game.set_mode(GameMode::HotSeat);
+    let board = game.board();
+    assert_eq!(
+        (board.dot_count(), board.edge_count(), board.cell_count()),
+        (9, 16, 8)
+    );
+    assert!((0..board.cell_count()).all(|cell| board.cell_edges(cell).len() == 3));
+    let edges = [edge(board, 0, 1), edge(board, 1, 4), edge(board, 0, 4)];
+    let empty_hash = board.hash();
+    game.do_move(edges[0]);
+    game.do_move(edges[1]);
+    assert_eq!(game.board().current_turn(), Player::Red);
+    assert!(game.board().completes_cell(edges[2]));
+    game.do_move(edges[2]);
+    assert_eq!(game.board().score(Player::Red), 1);
+    assert_eq!(game.board().current_turn(), Player::Red);
+    assert!(!game.do_move(edges[2]));
+    assert!(game.undo());
+    assert_eq!(game.board().cell_get(0), CellState::Free);
+    assert_eq!(game.board().current_turn(), Player::Red);
+    assert!(game.undo() && game.undo() && !game.undo());
+    assert_eq!(game.board().hash(), empty_hash);
+    assert!(game.redo());
+    assert_eq!(
+        game.board().edge_get(edges[0]),
+        CellState::Player(Player::Red)
+    );
+}
+
+/// Two rows of two hexagons share 5 of their edges and 8 of their corners.
+#[test]
+fn hexagons() {
+    let board = LatticeBoard::new(LatticeKind::Hexagons, 3, 3).expect("valid size");
+    assert_eq!(
+        (board.dot_count(), board.edge_count(), board.cell_count()),
+        (16, 19, 4)
+    );
+    assert!((0..board.cell_count()).all(|cell| board.cell_edges(cell).len() == 6));
+}
+
+/// Two stacked 3x3 grids have 9 connectors between them, and 12 upright
+/// squares besides their 8 boxes. The middle connector lies on four of
+/// them, and closing an upright square takes it.
+#[test]
+fn layers() {
+    let mut game = LatticeGame::new(LatticeKind::Layers(2), 3, 3).expect("valid size");
+    game.set_mode(GameMode::HotSeat);
+    let board = game.board();
+    assert_eq!(
+        (board.dot_count(), board.edge_count(), board.cell_count()),
+        (18, 33, 20)
+    );
+    assert_eq!((board.layer_count(), board.dot_layer(13)), (2, 1));
+    assert_eq!(board.edge_cells(edge(board, 4, 13)).len(), 4);
+    assert_eq!(board.edge_cells(edge(board, 3, 4)).len(), 3);
+    let edges = [
+        edge(board, 0, 1),
+        edge(board, 1, 10),
+        edge(board, 9, 10),
+        edge(board, 0, 9),
+    ];
+    for edge in edges {
+        game.do_move(edge);
+    }
+    assert_eq!(game.board().score(Player::Blue), 1);
+    assert_eq!(game.board().current_turn(), Player::Blue);
+    assert_eq!(
+        LatticeBoard::new(LatticeKind::Layers(1), 3, 3),
+        Err(GameError::InvalidLayers(1))
+    );
+}
+
+/// The AI plays both sides of every lattice to the end.
+#[cfg(feature = "ai")]
+#[test]
+fn ai_plays_lattices() {
+    for kind in LatticeKind::ALL {
+        let mut game = LatticeGame::new(kind, 3, 3).expect("valid size");
+        game.set_mode(GameMode::AIVsAI);
+        game.play_ai_moves();
+        let board = game.board();
+        assert!(board.is_finished());
+        assert_eq!(
+            board.score(Player::Red) + board.score(Player::Blue),
+            board.cell_count()
+        );
+    }
+}
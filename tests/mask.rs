@@ -0,0 +1,93 @@
+use dots_and_boxes::{
+    BarError, BarId, BoardState, CellMask, CellState, GameConfig, GameError, GameTrait,
+    HotSeatGame, Player, Ruleset,
+};
+
+fn mask(rows: &str) -> CellMask {
+    rows.parse().expect("valid mask")
+}
+
+fn bar(notation: &str) -> BarId {
+    notation.parse().expect("valid notation")
+}
+
+/// An L leaves out the top right boxes of a 4x4 board, with the bars only
+/// they have, and keeps its shape through restarts, FEN strings and packed
+/// bytes.
+#[test]
+fn l_shape() {
+    let mut game = HotSeatGame::new(4, 4);
+    let config = GameConfig {
+        mask: Some(mask("##./##./###")),
+        ..GameConfig::new(4, 4)
+    };
+    game.reconfigure(config).expect("mask fits");
+    let board = game.board();
+    assert!(!board.has_box(2, 0));
+    assert!(!board.has_dot(3, 0));
+    assert!(board.has_dot(3, 2));
+    assert_eq!(board.validate(bar("v3.0")), Err(BarError::OutOfBounds));
+    assert_eq!(board.validate(bar("h2.1")), Err(BarError::OutOfBounds));
+    assert_eq!(board.validate(bar("h2.2")), Ok(()));
+    assert_eq!(board.perft(1), 20);
+    assert_eq!(board.mask().to_string(), "##./##./###");
+    for notation in ["h2.2", "h2.3", "v2.2", "v3.2"] {
+        game.do_move(bar(notation));
+    }
+    assert_eq!(game.score(Player::Blue), 1);
+    let board = game.board();
+    assert_eq!(BoardState::from_fen(&board.to_fen()).as_ref(), Ok(board));
+    assert_eq!(
+        BoardState::from_bytes(&board.to_bytes()).as_ref(),
+        Ok(board)
+    );
+    game.restart(Player::Red);
+    assert_eq!(game.board().mask(), mask("##./##./###"));
+    assert_eq!(game.cell_get(2, 2), CellState::Free);
+}
+
+/// The mask has to fit the board and leave boxes to play, and a Swedish
+/// border cannot close a box on its own.
+#[test]
+fn invalid_masks() {
+    let mut game = HotSeatGame::new(4, 2);
+    let config = |rows, rules| GameConfig {
+        mask: Some(mask(rows)),
+        rules,
+        ..GameConfig::new(4, 2)
+    };
+    let swedish = Ruleset {
+        swedish: true,
+        ..Ruleset::default()
+    };
+    assert_eq!(
+        game.reconfigure(config("##", Ruleset::default())),
+        Err(GameError::InvalidMask)
+    );
+    assert_eq!(
+        game.reconfigure(config("...", Ruleset::default())),
+        Err(GameError::InvalidMask)
+    );
+    assert_eq!(
+        game.reconfigure(config("#.#", swedish)),
+        Err(GameError::UnplayableRules(4, 2))
+    );
+    assert_eq!(game.reconfigure(config("##.", swedish)), Ok(()));
+    assert!("##/#".parse::<CellMask>().is_err());
+}
+
+/// The AI plays around a hole in the middle of the board to the end.
+#[cfg(feature = "ai")]
+#[test]
+fn ai_plays_around_a_hole() {
+    use dots_and_boxes::{GameBuilder, GameMode};
+
+    let mut game = GameBuilder::new(4, 4)
+        .mask(mask("###/#.#/###"))
+        .mode(GameMode::AIVsAI)
+        .build()
+        .expect("mask fits");
+    game.play_ai_moves();
+    assert!(game.board().is_finished());
+    assert_eq!(game.score(Player::Red) + game.score(Player::Blue), 8);
+}
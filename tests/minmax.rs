@@ -0,0 +1,392 @@
+#![cfg(feature = "ai")]
+
+use dots_and_boxes::{
+    MinMax, MinMaxInterface, MinMaxOptions, MinMaxState, PossibleMovesIterator, TABLE_CAPACITY,
+};
+
+/// Worth of the cells of `Row`, the same read from either end.
+const WEIGHTS: [i32; 6] = [3, 1, 4, 4, 1, 3];
+
+/// A toy game on a row of cells: the players take turns claiming a free
+/// cell, Red first, and score its weight. Mirrored positions share a hash,
+/// like symmetric boards do.
+#[derive(Clone, Default)]
+struct Row {
+    red: u32,
+    blue: u32,
+    played: Vec<u32>,
+}
+
+impl Row {
+    fn red_to_move(&self) -> bool {
+        self.played.len().is_multiple_of(2)
+    }
+
+    fn encoding(red: u32, blue: u32) -> u64 {
+        (red | blue << WEIGHTS.len()) as u64
+    }
+
+    fn mirror(mask: u32) -> u32 {
+        (0..WEIGHTS.len() as u32)
+            .filter(|cell| mask & 1 << cell != 0)
+            .fold(0, |mirrored, cell| {
+                mirrored | 1 << (WEIGHTS.len() as u32 - 1 - cell)
+            })
+    }
+
+    /// Whether the mirror image is the orientation the hash stands for.
+    fn is_mirrored(&self) -> bool {
+        Self::encoding(Self::mirror(self.red), Self::mirror(self.blue))
+            < Self::encoding(self.red, self.blue)
+    }
+
+    fn score(&self, mask: u32) -> i32 {
+        (0..WEIGHTS.len())
+            .filter(|cell| mask & 1 << cell != 0)
+            .map(|cell| WEIGHTS[cell])
+            .sum()
+    }
+}
+
+struct RowMoves {
+    next_cell: u32,
+}
+
+impl PossibleMovesIterator<Row, u32> for RowMoves {
+    fn new(_state: &Row) -> Self {
+        Self { next_cell: 0 }
+    }
+
+    fn next(&mut self, state: &Row) -> Option<u32> {
+        let cell = (self.next_cell..WEIGHTS.len() as u32)
+            .find(|cell| (state.red | state.blue) & 1 << cell == 0)?;
+        self.next_cell = cell + 1;
+        Some(cell)
+    }
+}
+
+impl MinMaxState for Row {
+    type Move = u32;
+    type PossibleMovesIterator = RowMoves;
+
+    fn _apply_move(&mut self, cell: u32) -> bool {
+        if self.red_to_move() {
+            self.red |= 1 << cell;
+        } else {
+            self.blue |= 1 << cell;
+        }
+        self.played.push(cell);
+        true
+    }
+
+    fn _undo_moves(&mut self, nr_moves: u32) -> bool {
+        (0..nr_moves).all(|_| match self.played.pop() {
+            Some(cell) => {
+                self.red &= !(1 << cell);
+                self.blue &= !(1 << cell);
+                true
+            }
+            None => false,
+        })
+    }
+
+    fn position_hash(&self) -> u64 {
+        let mirrored = Self::encoding(Self::mirror(self.red), Self::mirror(self.blue));
+        mirrored.min(Self::encoding(self.red, self.blue))
+    }
+
+    fn canonical_move(&self, cell: u32) -> u32 {
+        if self.is_mirrored() {
+            WEIGHTS.len() as u32 - 1 - cell
+        } else {
+            cell
+        }
+    }
+
+    fn move_from_canonical(&self, cell: u32) -> u32 {
+        // Mirroring undoes itself.
+        self.canonical_move(cell)
+    }
+}
+
+/// Scores `Row` for Red.
+#[derive(Clone)]
+struct RowScore;
+
+impl MinMaxInterface for RowScore {
+    type State = Row;
+
+    fn heuristic(&mut self, state: &mut Row) -> i32 {
+        state.score(state.red) - state.score(state.blue)
+    }
+
+    fn is_maximizing(&self, state: &Row) -> bool {
+        state.red_to_move()
+    }
+}
+
+/// The line after the last cell mirrors the line after the first one,
+/// although the table only holds the position searched first of the two.
+#[test]
+fn variation_through_mirrored_transposition() {
+    let mut minmax = MinMax::new(RowScore, Row::default());
+    minmax.set_options(MinMaxOptions {
+        depth: 4,
+        ..MinMaxOptions::default()
+    });
+    minmax.score_moves();
+    let line = minmax.variation(0);
+    assert!(line.len() >= 2, "line {line:?} too short");
+    let mirrored = line
+        .iter()
+        .map(|cell| WEIGHTS.len() as u32 - 1 - cell)
+        .collect::<Vec<_>>();
+    assert_eq!(minmax.variation(5), mirrored);
+}
+
+/// Scores `Row` like `RowScore`, and makes a mistake whenever it is asked
+/// whether to, picking the last of the moves it chooses from.
+#[derive(Clone)]
+struct Blunderer {
+    mistakes: bool,
+}
+
+impl MinMaxInterface for Blunderer {
+    type State = Row;
+
+    fn heuristic(&mut self, state: &mut Row) -> i32 {
+        RowScore.heuristic(state)
+    }
+
+    fn is_maximizing(&self, state: &Row) -> bool {
+        state.red_to_move()
+    }
+
+    fn choose_index(&mut self, count: usize) -> usize {
+        count - 1
+    }
+
+    fn chance(&mut self, _probability: f64) -> bool {
+        self.mistakes
+    }
+}
+
+/// A mistake plays the second or third best move, never a worse one, and
+/// is only made with a mistake probability.
+#[test]
+fn mistakes_pick_runners_up() {
+    let scored_moves = vec![(3, -2), (1, 5), (0, 10), (2, 1)];
+    let pick = |mistakes: bool, mistake_probability: f64| {
+        let mut minmax = MinMax::new(Blunderer { mistakes }, Row::default());
+        minmax.set_options(MinMaxOptions {
+            mistake_probability,
+            ..MinMaxOptions::default()
+        });
+        minmax.pick_move(scored_moves.clone())
+    };
+    assert_eq!(pick(false, 0.3), Some(0));
+    assert_eq!(pick(true, 0.3), Some(2));
+    assert_eq!(pick(true, 0.0), Some(0));
+}
+
+/// A toy game tree for the quiescence search: Red can grab a point with
+/// move 0, which lets Blue capture five with the forcing move 2, or play
+/// the quiet move 1. Move 3 is quiet too and worth nothing.
+#[derive(Clone, Default)]
+struct Trade {
+    played: Vec<u32>,
+}
+
+impl Trade {
+    fn moves(&self) -> &'static [u32] {
+        match self.played.as_slice() {
+            [] => &[0, 1],
+            [0] => &[2, 3],
+            [1] => &[3],
+            _ => &[],
+        }
+    }
+
+    /// Red's points minus Blue's, the sides taking turns.
+    fn balance(&self) -> i32 {
+        let gains = self.played.iter().map(|mv| match mv {
+            0 => 1,
+            2 => 5,
+            _ => 0,
+        });
+        (gains.enumerate())
+            .map(|(ply, gain)| if ply % 2 == 0 { gain } else { -gain })
+            .sum()
+    }
+}
+
+struct TradeMoves {
+    index: usize,
+}
+
+impl PossibleMovesIterator<Trade, u32> for TradeMoves {
+    fn new(_state: &Trade) -> Self {
+        Self { index: 0 }
+    }
+
+    fn next(&mut self, state: &Trade) -> Option<u32> {
+        let mv = state.moves().get(self.index)?;
+        self.index += 1;
+        Some(*mv)
+    }
+}
+
+impl MinMaxState for Trade {
+    type Move = u32;
+    type PossibleMovesIterator = TradeMoves;
+
+    fn _apply_move(&mut self, mv: u32) -> bool {
+        self.played.push(mv);
+        true
+    }
+
+    fn _undo_moves(&mut self, nr_moves: u32) -> bool {
+        (0..nr_moves).all(|_| self.played.pop().is_some())
+    }
+
+    fn position_hash(&self) -> u64 {
+        (self.played.iter()).fold(1, |hash, mv| hash * 4 + *mv as u64)
+    }
+}
+
+/// Scores `Trade` for Red, with the capture as its forcing move.
+#[derive(Clone)]
+struct TradeScore;
+
+impl MinMaxInterface for TradeScore {
+    type State = Trade;
+
+    fn heuristic(&mut self, state: &mut Trade) -> i32 {
+        state.balance()
+    }
+
+    fn is_maximizing(&self, state: &Trade) -> bool {
+        state.played.len().is_multiple_of(2)
+    }
+
+    fn is_forcing(&self, _state: &Trade, mv: u32) -> bool {
+        mv == 2
+    }
+}
+
+/// A search one ply deep plays out the capture that grabbing the point
+/// allows, so it prefers the quiet move.
+#[test]
+fn quiescence_sees_the_capture() {
+    let mut minmax = MinMax::new(TradeScore, Trade::default());
+    let scored_moves = minmax.score_moves();
+    assert_eq!(scored_moves, vec![(0, -4), (1, 0)]);
+    assert_eq!(minmax.pick_move(scored_moves), Some(1));
+    assert_eq!(minmax.last_stats().map(|stats| stats.max_depth), Some(2));
+}
+
+/// A toy game that ends after one move: each root, told apart by `id`,
+/// has `moves` moves, so a search stores one table entry per move.
+#[derive(Clone)]
+struct Fan {
+    id: u64,
+    moves: u32,
+    played: Option<u32>,
+}
+
+impl Fan {
+    fn new(id: u64, moves: usize) -> Self {
+        Self {
+            id,
+            moves: moves as u32,
+            played: None,
+        }
+    }
+}
+
+struct FanMoves {
+    next: u32,
+}
+
+impl PossibleMovesIterator<Fan, u32> for FanMoves {
+    fn new(_state: &Fan) -> Self {
+        Self { next: 0 }
+    }
+
+    fn next(&mut self, state: &Fan) -> Option<u32> {
+        if state.played.is_some() || self.next == state.moves {
+            return None;
+        }
+        self.next += 1;
+        Some(self.next - 1)
+    }
+}
+
+impl MinMaxState for Fan {
+    type Move = u32;
+    type PossibleMovesIterator = FanMoves;
+
+    fn _apply_move(&mut self, mv: u32) -> bool {
+        self.played.replace(mv).is_none()
+    }
+
+    fn _undo_moves(&mut self, nr_moves: u32) -> bool {
+        nr_moves == 1 && self.played.take().is_some()
+    }
+
+    fn position_hash(&self) -> u64 {
+        self.id << 32 | self.played.map_or(0, |mv| mv as u64 + 1)
+    }
+}
+
+/// Scores every `Fan` position the same.
+#[derive(Clone)]
+struct FanScore;
+
+impl MinMaxInterface for FanScore {
+    type State = Fan;
+
+    fn heuristic(&mut self, _state: &mut Fan) -> i32 {
+        0
+    }
+
+    fn is_maximizing(&self, state: &Fan) -> bool {
+        state.played.is_none()
+    }
+}
+
+/// Once the table outgrows its capacity, the positions of the last two
+/// searches stay and older ones are dropped.
+#[test]
+fn table_keeps_recent_searches() {
+    let mut minmax = MinMax::new(FanScore, Fan::new(0, 0));
+    let mut search = |root: Fan| {
+        minmax.set_root_state(root);
+        minmax.score_moves();
+        minmax.last_stats().expect("searched").table_hits
+    };
+    assert_eq!(search(Fan::new(1, 10)), 0);
+    assert_eq!(search(Fan::new(2, 10)), 0);
+    assert_eq!(search(Fan::new(3, TABLE_CAPACITY - 15)), 0);
+    // Starts with more than the capacity, and drops only the first search.
+    assert_eq!(search(Fan::new(4, 1)), 0);
+    assert_eq!(search(Fan::new(2, 10)), 10);
+    assert_eq!(search(Fan::new(1, 10)), 0);
+}
+
+/// Progress reports end with a best move, whether or not the root moves
+/// are split across threads.
+#[test]
+fn progress_ends_with_the_best_move() {
+    let mut minmax = MinMax::new(RowScore, Row::default());
+    minmax.set_options(MinMaxOptions {
+        depth: 3,
+        ..MinMaxOptions::default()
+    });
+    let mut reported = Vec::new();
+    let scored_moves = minmax.score_moves_with_progress(&mut |cell| reported.push(cell));
+    let score_of = |cell| scored_moves.iter().find(|(scored, _score)| *scored == cell);
+    let best = scored_moves.iter().map(|(_cell, score)| *score).max();
+    let last = reported.last().and_then(|cell| score_of(*cell));
+    assert_eq!(last.map(|(_cell, score)| *score), best);
+}
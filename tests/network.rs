@@ -0,0 +1,81 @@
+#![cfg(feature = "ai")]
+
+use dots_and_boxes::{BarId, BoardState, Network, Player};
+
+/// A network file for `inputs` inputs, with a layer of one output per bias
+/// for each `(weights, biases)` pair.
+fn network_bytes(inputs: u32, layers: &[(&[f32], &[f32])]) -> Vec<u8> {
+    let mut bytes = b"DABN".to_vec();
+    for number in [1, inputs, layers.len() as u32] {
+        bytes.extend(number.to_le_bytes());
+    }
+    for (weights, biases) in layers {
+        bytes.extend((biases.len() as u32).to_le_bytes());
+        for value in weights.iter().chain(*biases) {
+            bytes.extend(value.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// A network for boards of a single box, whose six inputs are the two
+/// vertical bars, the two horizontal bars, the box and the side to move.
+/// The second hidden output is negative for Blue to move, and cut to zero.
+fn tiny_network() -> Vec<u8> {
+    network_bytes(
+        6,
+        &[
+            (
+                &[1.0, 1.0, 2.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0],
+                &[0.5, 1.0],
+            ),
+            (&[2.0, 5.0], &[-1.0]),
+        ],
+    )
+}
+
+#[test]
+fn evaluates_a_tiny_network() {
+    let network = Network::from_bytes(&tiny_network()).expect("well formed");
+    let top: BarId = "h0.0".parse().expect("valid notation");
+    let board = BoardState::from_moves(2, 2, Player::Red, &[top]).expect("legal move");
+    assert!(network.accepts(&board));
+    // Hidden: 2 - 1 + 0.5 = 1.5 and max(-3 + 1, 0) = 0, then 3 + 0 - 1.
+    assert_eq!(network.evaluate(&board), 2.0);
+    let larger = BoardState::from_moves(3, 3, Player::Red, &[]).expect("no moves");
+    assert!(!network.accepts(&larger));
+}
+
+#[test]
+fn rejects_a_bad_header() {
+    let mut bad_magic = tiny_network();
+    bad_magic[0] = b'X';
+    assert!(Network::from_bytes(&bad_magic).is_none());
+    let mut bad_version = tiny_network();
+    bad_version[4] = 2;
+    assert!(Network::from_bytes(&bad_version).is_none());
+}
+
+#[test]
+fn rejects_truncated_data_and_trailing_bytes() {
+    let bytes = tiny_network();
+    for length in 0..bytes.len() {
+        assert!(
+            Network::from_bytes(&bytes[..length]).is_none(),
+            "length {length}"
+        );
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(Network::from_bytes(&trailing).is_none());
+}
+
+#[test]
+fn rejects_bad_layer_sizes() {
+    let no_outputs = network_bytes(2, &[(&[], &[]), (&[1.0, 1.0], &[0.0])]);
+    assert!(Network::from_bytes(&no_outputs).is_none());
+    let two_outputs = network_bytes(2, &[(&[1.0, 1.0, 1.0, 1.0], &[0.0, 0.0])]);
+    assert!(Network::from_bytes(&two_outputs).is_none());
+    let one_output = network_bytes(2, &[(&[1.0, 1.0], &[0.0])]);
+    assert!(Network::from_bytes(&one_output).is_some());
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dots_and_boxes::{BarId, GameConfig, GameError, GameObserver, GameTrait, HotSeatGame, Player};
+
+#[derive(Default)]
+struct Log(RefCell<Vec<String>>);
+
+impl GameObserver for Log {
+    fn on_move_played(&self, player: Player, bar: BarId) {
+        self.0.borrow_mut().push(format!("{:?} {}", player, bar));
+    }
+
+    fn on_box_completed(&self, player: Player, col: u32, row: u32) {
+        self.0
+            .borrow_mut()
+            .push(format!("{:?} box {},{}", player, col, row));
+    }
+
+    fn on_turn_changed(&self, player: Player) {
+        self.0.borrow_mut().push(format!("{:?} to move", player));
+    }
+
+    fn on_game_over(&self, winner: Option<Player>) {
+        self.0.borrow_mut().push(format!("won by {:?}", winner));
+    }
+}
+
+/// Blue takes the only box of a 2x2 board with the last bar.
+#[test]
+fn observer_sees_every_move() {
+    let log = Rc::new(Log::default());
+    let mut game = HotSeatGame::new(2, 2);
+    game.add_observer(log.clone());
+    for bar in ["h0.0", "v0.0", "v1.0", "h0.1", "h0.0"] {
+        game.do_move(bar.parse().expect("valid notation"));
+    }
+    assert_eq!(
+        *log.0.borrow(),
+        [
+            "Red h0.0",
+            "Blue to move",
+            "Blue v0.0",
+            "Red to move",
+            "Red v1.0",
+            "Blue to move",
+            "Blue h0.1",
+            "Blue box 0,0",
+            "won by Some(Blue)",
+        ]
+    );
+}
+
+/// A new game on another board keeps the observers, and a bad size keeps
+/// the game.
+#[test]
+fn reconfigure_keeps_observers() {
+    let log = Rc::new(Log::default());
+    let mut game = HotSeatGame::new(2, 2);
+    game.add_observer(log.clone());
+    game.do_move("h0.0".parse().expect("valid notation"));
+    let config = GameConfig {
+        starting_player: Player::Blue,
+        ..GameConfig::new(3, 4)
+    };
+    assert_eq!(game.reconfigure(config), Ok(()));
+    assert_eq!((game.get_width(), game.get_height()), (3, 4));
+    assert_eq!(game.current_turn(), Player::Blue);
+    assert!(game.history().is_empty());
+    assert_eq!(
+        game.reconfigure(GameConfig::new(1, 4)),
+        Err(GameError::InvalidSize(1, 4))
+    );
+    assert_eq!(game.get_width(), 3);
+    game.do_move("v2.2".parse().expect("valid notation"));
+    assert_eq!(
+        log.0.borrow().last().map(String::as_str),
+        Some("Red to move")
+    );
+}
@@ -0,0 +1,28 @@
+use dots_and_boxes::BoardState;
+
+/// Leaf counts by depth from the empty board, checked against an
+/// independent enumeration. Boxes are completed from depth 4 on, so the
+/// deeper counts cover the extra turns too.
+const TABLES: &[(&str, &[u64])] = &[
+    ("2x2/2/2/1/r", &[1, 4, 12, 24, 24]),
+    ("3x2/3/4/2/r", &[1, 7, 42, 210, 936, 3240, 5040, 720]),
+    ("3x3/6/6/4/r", &[1, 12, 132, 1320, 12552, 118080]),
+    ("4x4/12/12/9/r", &[1, 24, 552, 12144]),
+    ("5x5/20/20/16/r", &[1, 40, 1560, 59280]),
+];
+
+#[test]
+fn perft_from_empty_boards() {
+    for (fen, counts) in TABLES {
+        let board = BoardState::from_fen(fen).expect("valid fen");
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(
+                board.perft(depth as u32),
+                *count,
+                "perft({}) of {}",
+                depth,
+                fen
+            );
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use dots_and_boxes::{BarDirection, BarId, GameRecord, Player, RecordError};
+
+fn bar(direction: BarDirection, col: u32, row: u32) -> BarId {
+    BarId {
+        direction,
+        col,
+        row,
+    }
+}
+
+/// Writes a game where Blue takes a box and moves again, and reads it back.
+#[test]
+fn record_round_trip() {
+    let mut record = GameRecord::new(3, 3, Player::Red);
+    record.push(bar(BarDirection::Horizontal, 0, 0), None);
+    record.push(bar(BarDirection::Vertical, 0, 0), None);
+    record.push(bar(BarDirection::Vertical, 1, 0), None);
+    record.push(
+        bar(BarDirection::Horizontal, 0, 1),
+        Some("Takes the box [and moves again] \\o/".to_string()),
+    );
+    record.push(bar(BarDirection::Horizontal, 1, 2), None);
+    let text = record.to_string();
+    assert!(text.contains(";B[h0.1]C["), "{}", text);
+    assert!(text.contains(";B[h1.2]"), "{}", text);
+    assert_eq!(GameRecord::parse(&text), Ok(record.clone()));
+    let board = record.final_position().expect("legal moves");
+    assert_eq!(board.to_fen(), "3x3/br4/r1b2b/b3/r");
+}
+
+#[test]
+fn record_rejects_wrong_player() {
+    let text = "(;GM[dots-and-boxes]SZ[3x3]PL[R];R[h0.0];R[v0.0])";
+    assert_eq!(GameRecord::parse(text), Err(RecordError::WrongPlayer(1)));
+}
@@ -0,0 +1,184 @@
+use dots_and_boxes::{
+    BarError, BarId, BoardState, CellState, GameConfig, GameError, GameTrait, HotSeatGame, Player,
+    Ruleset,
+};
+
+fn moves(notation: &[&str]) -> Vec<BarId> {
+    (notation.iter())
+        .map(|bar| bar.parse().expect("valid notation"))
+        .collect()
+}
+
+const MISERE: Ruleset = Ruleset {
+    misere: true,
+    swedish: false,
+    alternate_turns: false,
+    torus: false,
+};
+
+const SWEDISH: Ruleset = Ruleset {
+    misere: false,
+    swedish: true,
+    alternate_turns: false,
+    torus: false,
+};
+
+const ALTERNATE_TURNS: Ruleset = Ruleset {
+    misere: false,
+    swedish: false,
+    alternate_turns: true,
+    torus: false,
+};
+
+const TORUS: Ruleset = Ruleset {
+    misere: false,
+    swedish: false,
+    alternate_turns: false,
+    torus: true,
+};
+
+/// Blue takes the only box of a 2x2 board, and loses in misère play.
+#[test]
+fn misere_winner() {
+    let mut game = HotSeatGame::new(2, 2);
+    game.reconfigure(GameConfig {
+        rules: MISERE,
+        ..GameConfig::new(2, 2)
+    })
+    .expect("supported size");
+    for bar in moves(&["h0.0", "v0.0", "v1.0", "h0.1"]) {
+        game.do_move(bar);
+    }
+    assert_eq!(game.score(Player::Blue), 1);
+    assert_eq!(game.winner(), Some(Some(Player::Red)));
+    assert!(game.evaluate().margin > 0);
+}
+
+/// The rules survive FEN strings and packed bytes, and tell positions
+/// apart.
+#[test]
+fn misere_round_trip() {
+    let mut board =
+        BoardState::from_moves(3, 3, Player::Red, &moves(&["h0.0", "v1.1"])).expect("legal moves");
+    let standard = board.clone();
+    board.set_rules(MISERE);
+    assert_ne!(board.hash(), standard.hash());
+    assert!(board.to_fen().ends_with("/r/m"));
+    assert_eq!(BoardState::from_fen(&board.to_fen()), Ok(board.clone()));
+    assert_eq!(BoardState::from_bytes(&board.to_bytes()), Ok(board.clone()));
+    board.set_rules(Ruleset::default());
+    assert_eq!(board, standard);
+    assert_eq!(board.hash(), standard.hash());
+}
+
+/// A Swedish board starts with its border drawn by neither player, again
+/// after a restart, and cannot be a single box.
+#[test]
+fn swedish_border() {
+    let mut game = HotSeatGame::new(3, 3);
+    let config = GameConfig {
+        rules: SWEDISH,
+        ..GameConfig::new(3, 3)
+    };
+    game.reconfigure(config).expect("supported size");
+    assert_eq!(game.horizontal_get(0, 0), CellState::Neutral);
+    assert_eq!(game.vertical_get(2, 1), CellState::Neutral);
+    assert_eq!(game.vertical_get(1, 0), CellState::Free);
+    let free = (game.board().bars()).filter(|(_, state)| *state == CellState::Free);
+    assert_eq!(free.count(), 4);
+    let border = "h0.0".parse().expect("valid notation");
+    assert_eq!(game.board().validate(border), Err(BarError::Taken));
+    for bar in moves(&["v1.0", "h0.1", "v1.1"]) {
+        game.do_move(bar);
+    }
+    assert_eq!(game.score(Player::Blue), 2);
+    let fen = game.board().to_fen();
+    assert!(fen.ends_with("/s"));
+    assert_eq!(BoardState::from_fen(&fen).as_ref(), Ok(game.board()));
+    let bytes = game.board().to_bytes();
+    assert_eq!(BoardState::from_bytes(&bytes).as_ref(), Ok(game.board()));
+    game.restart(Player::Red);
+    assert_eq!(game.horizontal_get(1, 2), CellState::Neutral);
+    assert_eq!(game.vertical_get(1, 0), CellState::Free);
+    assert_eq!(
+        game.reconfigure(GameConfig {
+            rules: SWEDISH,
+            ..GameConfig::new(2, 2)
+        }),
+        Err(GameError::UnplayableRules(2, 2))
+    );
+}
+
+/// Without extra turns, taking a box hands the move over, and undoing it
+/// gives the move back.
+#[test]
+fn alternate_turns() {
+    let mut game = HotSeatGame::new(3, 2);
+    let config = GameConfig {
+        rules: ALTERNATE_TURNS,
+        ..GameConfig::new(3, 2)
+    };
+    game.reconfigure(config).expect("supported size");
+    for bar in moves(&["h0.0", "v0.0", "h0.1", "v1.0"]) {
+        game.do_move(bar);
+    }
+    assert_eq!(game.score(Player::Blue), 1);
+    assert_eq!(game.current_turn(), Player::Red);
+    assert!(game.board().to_fen().ends_with("/a"));
+    let bytes = game.board().to_bytes();
+    assert_eq!(BoardState::from_bytes(&bytes).as_ref(), Ok(game.board()));
+    assert!(game.undo());
+    assert_eq!(game.current_turn(), Player::Blue);
+}
+
+/// On a torus the last column and row of bars are the first ones again, so
+/// the boxes on the right close with the bars on the left. It needs two
+/// boxes each way.
+#[test]
+fn torus_wraps() {
+    let mut game = HotSeatGame::new(3, 3);
+    let config = GameConfig {
+        rules: TORUS,
+        ..GameConfig::new(3, 3)
+    };
+    game.reconfigure(config).expect("supported size");
+    let free = (game.board().bars()).filter(|(_, state)| *state == CellState::Free);
+    assert_eq!(free.count(), 8);
+    let [last, first] = ["v2.0", "v0.0"].map(|bar| bar.parse().expect("valid notation"));
+    assert_eq!(game.board().validate(last), Err(BarError::OutOfBounds));
+    assert_eq!(game.board().wrap_bar(last), first);
+    for bar in moves(&["v0.0", "v1.0", "h1.0", "h1.1"]) {
+        game.do_move(bar);
+    }
+    assert_eq!(game.cell_get(1, 0), CellState::Player(Player::Blue));
+    let fen = game.board().to_fen();
+    assert!(fen.ends_with("/t"));
+    assert_eq!(BoardState::from_fen(&fen).as_ref(), Ok(game.board()));
+    let bytes = game.board().to_bytes();
+    assert_eq!(BoardState::from_bytes(&bytes).as_ref(), Ok(game.board()));
+    game.restart(Player::Red);
+    assert_eq!(game.vertical_get(2, 1), CellState::Neutral);
+    assert_eq!(
+        game.reconfigure(GameConfig {
+            rules: TORUS,
+            ..GameConfig::new(2, 4)
+        }),
+        Err(GameError::UnplayableRules(2, 4))
+    );
+}
+
+/// The AI plays a torus to the end.
+#[cfg(feature = "ai")]
+#[test]
+fn ai_plays_torus() {
+    use dots_and_boxes::{GameBuilder, GameMode};
+
+    let mut game = GameBuilder::new(4, 4)
+        .rules(TORUS)
+        .mode(GameMode::AIVsAI)
+        .build()
+        .expect("supported size");
+    game.play_ai_moves();
+    assert!(game.board().is_finished());
+    assert_eq!(game.score(Player::Red) + game.score(Player::Blue), 9);
+}
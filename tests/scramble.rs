@@ -0,0 +1,39 @@
+use dots_and_boxes::{BoardState, CellState, GameConfig, GameTrait, HotSeatGame, Player, Scramble};
+
+fn scrambled(bars: u32, seed: u64) -> BoardState {
+    let config = GameConfig {
+        scramble: Some(Scramble { bars, seed }),
+        ..GameConfig::new(5, 5)
+    };
+    let mut game = HotSeatGame::new(5, 5);
+    game.reconfigure(config).expect("supported size");
+    game.board().clone()
+}
+
+/// A scrambled board starts with the bars drawn and no box taken or left
+/// to take, the same for the same seed, with nothing to undo.
+#[test]
+fn scramble_start() {
+    let board = scrambled(6, 7);
+    let drawn = (board.bars()).filter(|(_, state)| *state != CellState::Free);
+    assert_eq!(drawn.count(), 6);
+    assert!((board.cells())
+        .all(|(col, row, state)| { state == CellState::Free && board.open_edges(col, row) >= 2 }));
+    assert_eq!(scrambled(6, 7), board);
+    assert_ne!(scrambled(6, 8), board);
+    let mut game = HotSeatGame::new(5, 5);
+    game.set_board(board);
+    assert_eq!(game.current_turn(), Player::Red);
+    assert!(!game.undo());
+}
+
+/// A board cannot take more bars than leave a safe one to play, so fewer
+/// are played.
+#[test]
+fn scramble_stops_while_fair() {
+    let mut board = BoardState::from_moves(3, 3, Player::Red, &[]).expect("supported size");
+    let played = board.scramble(Scramble { bars: 50, seed: 1 });
+    assert!(played < 50);
+    let drawn = (board.bars()).filter(|(_, state)| *state != CellState::Free);
+    assert_eq!(drawn.count() as u32, played);
+}
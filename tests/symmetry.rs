@@ -0,0 +1,128 @@
+use dots_and_boxes::{
+    BoardState, CellState, GameConfig, GameTrait, Handicap, HotSeatGame, Player, Ruleset,
+};
+
+/// A position on a board that is not square, with a box taken, so turning
+/// it swaps the width and height.
+fn position() -> BoardState {
+    let moves = ["h0.0", "v0.0", "v1.0", "h0.1", "h1.2", "v2.1"]
+        .map(|bar| bar.parse().expect("valid notation"));
+    BoardState::from_moves(4, 3, Player::Red, &moves).expect("legal moves")
+}
+
+#[test]
+fn rotations_compose() {
+    let board = position();
+    let turned = board.rotated(1);
+    assert_eq!((turned.width(), turned.height()), (3, 4));
+    assert_eq!(turned.rotated(3), board);
+    assert_eq!(turned.rotated(1), board.rotated(2));
+    assert_eq!(board.rotated(2), board.mirrored_h().mirrored_v());
+    assert_eq!(board.mirrored_h().mirrored_h(), board);
+}
+
+/// The transformed positions are consistent, and hash like the same
+/// positions read from scratch.
+#[test]
+fn transformed_positions_are_consistent() {
+    let board = position();
+    let transformed = [
+        board.rotated(1),
+        board.rotated(2),
+        board.rotated(3),
+        board.mirrored_h(),
+        board.mirrored_v(),
+    ];
+    for other in transformed {
+        let rebuilt = BoardState::from_fen(&other.to_fen()).expect("consistent position");
+        assert_eq!(rebuilt, other);
+        assert_eq!(rebuilt.hash(), other.hash());
+    }
+}
+
+/// The top bar of the first column ends up on the right side, in the top
+/// row, when the board turns clockwise.
+#[test]
+fn rotation_direction() {
+    let moves = ["h0.0".parse().expect("valid notation")];
+    let board = BoardState::from_moves(4, 3, Player::Red, &moves).expect("legal moves");
+    let expected = ["v2.0".parse().expect("valid notation")];
+    let expected = BoardState::from_moves(3, 4, Player::Red, &expected).expect("legal moves");
+    assert_eq!(board.rotated(1), expected);
+}
+
+/// The eight symmetries of a square board, as the public transformations
+/// compose them.
+fn symmetries(board: &BoardState) -> Vec<BoardState> {
+    (0..4)
+        .flat_map(|turns| {
+            let turned = board.rotated(turns);
+            [turned.mirrored_h(), turned]
+        })
+        .collect()
+}
+
+/// Boxes worth more than a point move with the boxes, the handicap stays,
+/// and every transformed position reads back from its FEN string.
+#[test]
+fn values_and_handicap_follow_the_symmetries() {
+    let board = BoardState::from_fen("3x3/6/6/4/r/1211/b2+0").expect("valid FEN");
+    assert_eq!(board.cell_value(1, 0), 2);
+    assert_eq!(board.mirrored_h().cell_value(0, 0), 2);
+    assert_eq!(board.rotated(1).cell_value(1, 1), 2);
+    for other in symmetries(&board) {
+        assert!(other.has_values());
+        assert_eq!(other.handicap(), board.handicap());
+        assert_eq!(other.points(Player::Blue), 2);
+        assert_eq!(BoardState::from_fen(&other.to_fen()), Ok(other.clone()));
+        assert_eq!(other.rotated(4), other);
+    }
+    assert_eq!(board.rotated(1).rotated(3), board);
+    assert_eq!(board.mirrored_v().mirrored_v().to_fen(), board.to_fen());
+    // Pre-drawn bars of the handicap are mapped like any other bar.
+    let config = GameConfig {
+        handicap: Some(Handicap {
+            player: Player::Red,
+            boxes: 1,
+            bars: 3,
+        }),
+        ..GameConfig::new(4, 4)
+    };
+    let mut game = HotSeatGame::new(4, 4);
+    game.reconfigure(config).expect("handicap fits");
+    for other in symmetries(game.board()) {
+        assert_eq!(BoardState::from_fen(&other.to_fen()), Ok(other.clone()));
+        assert_eq!(other.rotated(4), other);
+    }
+}
+
+/// On a torus, the bars on the edges where the board wraps around stay the
+/// first ones when mirrored, so the transformed positions are valid.
+#[test]
+fn torus_symmetries() {
+    let config = GameConfig {
+        rules: Ruleset {
+            torus: true,
+            ..Ruleset::default()
+        },
+        ..GameConfig::new(4, 4)
+    };
+    let mut game = HotSeatGame::new(4, 4);
+    game.reconfigure(config).expect("supported size");
+    for bar in ["v0.0", "h0.1", "h2.0", "v1.2"] {
+        game.do_move(bar.parse().expect("valid notation"));
+    }
+    let board = game.board();
+    let mirrored = board.mirrored_h();
+    let drawn = [("v0.0", Player::Red), ("h2.1", Player::Blue)];
+    for (bar, player) in drawn {
+        let bar = bar.parse().expect("valid notation");
+        let state = mirrored.bars().find(|(other, _)| *other == bar);
+        assert_eq!(state, Some((bar, CellState::Player(player))));
+    }
+    for other in symmetries(board) {
+        assert_eq!(BoardState::from_fen(&other.to_fen()), Ok(other.clone()));
+        assert_eq!(other.rotated(4), other);
+    }
+    assert_eq!(&mirrored.mirrored_h(), board);
+}
@@ -0,0 +1,65 @@
+use dots_and_boxes::{GameConfig, GameTrait, HotSeatGame, Participant, Player, Teams};
+
+fn participant(team: Player, member: u32) -> Option<Participant> {
+    Some(Participant { team, member })
+}
+
+/// The four participants play in a fixed order, a capture keeps the move
+/// with the participant who made it, and undoing gives it back.
+#[test]
+fn participants_take_turns() {
+    let mut game = HotSeatGame::new(3, 3);
+    assert_eq!(game.participant_to_move(), None);
+    let config = GameConfig {
+        teams: Some(Teams::default()),
+        ..GameConfig::new(3, 3)
+    };
+    game.reconfigure(config).expect("supported size");
+    assert_eq!(game.participant_to_move(), participant(Player::Red, 0));
+    for (notation, next) in [
+        ("h0.0", participant(Player::Blue, 0)),
+        ("v0.0", participant(Player::Red, 1)),
+        ("h0.1", participant(Player::Blue, 1)),
+        // Completes the top left box.
+        ("v1.0", participant(Player::Blue, 1)),
+        ("h1.2", participant(Player::Red, 0)),
+    ] {
+        game.do_move(notation.parse().expect("valid notation"));
+        assert_eq!(game.participant_to_move(), next);
+    }
+    assert_eq!(game.score(Player::Blue), 1);
+    game.undo();
+    assert_eq!(game.participant_to_move(), participant(Player::Blue, 1));
+    assert_eq!(
+        Participant::ALL.map(|participant| participant.index()),
+        [0, 1, 2, 3]
+    );
+    assert_eq!(Player::Red.members()[1].to_string(), "Red 2");
+}
+
+/// The AI plays its participants only, and stops at a human's turn.
+#[cfg(feature = "ai")]
+#[test]
+fn ai_plays_its_participants() {
+    use dots_and_boxes::GameBuilder;
+
+    let teams = Teams {
+        ai: [false, true, true, true],
+    };
+    let mut game = GameBuilder::new(4, 4)
+        .teams(teams)
+        .build()
+        .expect("supported size");
+    assert!(!game.ai_to_move());
+    game.do_move("h0.0".parse().expect("valid notation"));
+    assert!(
+        game.board().is_finished() || game.participant_to_move() == participant(Player::Red, 0)
+    );
+    let all_ai = Teams { ai: [true; 4] };
+    let mut game = GameBuilder::new(4, 4)
+        .teams(all_ai)
+        .build()
+        .expect("supported size");
+    game.play_ai_moves();
+    assert!(game.board().is_finished());
+}
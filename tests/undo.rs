@@ -0,0 +1,83 @@
+#![cfg(feature = "ai")]
+
+use dots_and_boxes::{
+    check_undo_exhaustive, fuzz_undo, Difficulty, GameBuilder, GameTrait, HotSeatGame, Player,
+};
+
+/// Undoes every sequence of moves on the boards small enough to enumerate.
+#[test]
+fn undo_exhaustive_tiny_boards() {
+    for (width, height) in [(2, 2), (3, 2), (2, 3)] {
+        check_undo_exhaustive(width, height);
+    }
+}
+
+/// Feeds the fuzz target inputs from a fixed LCG, so every run checks the
+/// same games.
+#[test]
+fn undo_random_sequences() {
+    let mut seed: u64 = 0x5eed;
+    for _ in 0..2000 {
+        let data = (0..64)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                // Mostly moves, with an undo now and then.
+                let byte = (seed >> 56) as u8;
+                if byte.is_multiple_of(8) {
+                    byte | 0x80
+                } else {
+                    byte & 0x7f
+                }
+            })
+            .collect::<Vec<_>>();
+        fuzz_undo(&data);
+    }
+}
+
+/// Takes back a capture and the move before it, and plays them again.
+#[test]
+fn game_undo_redo() {
+    let mut game = HotSeatGame::new(2, 2);
+    let mut positions = vec![game.board().to_fen()];
+    for bar in ["h0.0", "v0.0", "v1.0", "h0.1"] {
+        game.do_move(bar.parse().expect("valid notation"));
+        positions.push(game.board().to_fen());
+    }
+    assert!(!game.can_redo());
+    assert!(game.undo() && game.undo());
+    assert_eq!(game.board().to_fen(), positions[2]);
+    assert_eq!(game.current_turn(), Player::Red);
+    assert!(game.redo() && game.redo() && !game.redo());
+    assert_eq!(game.board().to_fen(), positions[4]);
+    let history = game.history();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[3].player, Player::Blue);
+    assert_eq!(history[3].completed, vec![(0, 0)]);
+    assert!(history[..3]
+        .iter()
+        .all(|played| played.completed.is_empty()));
+    assert!(game.undo());
+    game.do_move("h0.1".parse().expect("valid notation"));
+    assert!(!game.can_redo());
+    while game.undo() {}
+    assert_eq!(game.board().to_fen(), positions[0]);
+}
+
+/// Against the AI, undo takes back the AI's replies with the human's move.
+#[test]
+fn game_undo_against_ai() {
+    let mut game = GameBuilder::new(3, 3)
+        .difficulty(Player::Blue, Difficulty::Easy)
+        .build()
+        .expect("supported size");
+    let start = game.board().to_fen();
+    game.do_move("h0.0".parse().expect("valid notation"));
+    assert_eq!(game.current_turn(), Player::Red);
+    let after_reply = game.board().to_fen();
+    assert!(game.undo());
+    assert_eq!(game.board().to_fen(), start);
+    assert!(game.redo());
+    assert_eq!(game.board().to_fen(), after_reply);
+}
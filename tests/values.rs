@@ -0,0 +1,67 @@
+use dots_and_boxes::{
+    BoardState, CellValues, GameConfig, GameError, GameTrait, HotSeatGame, Player,
+};
+
+fn values(rows: &str) -> CellValues {
+    rows.parse().expect("valid values")
+}
+
+/// Scores and the winner count points rather than boxes: Red's single box
+/// worth 9 beats Blue's two worth 1.
+#[test]
+fn weighted_score() {
+    let board = BoardState::from_fen("4x2/rrrr/rrrrrr/brb/r/191").expect("valid FEN");
+    assert_eq!(board.cell_value(1, 0), 9);
+    let mut game = HotSeatGame::new(4, 2);
+    game.set_board(board);
+    assert_eq!(game.score(Player::Red), 9);
+    assert_eq!(game.score(Player::Blue), 2);
+    assert_eq!(game.winner(), Some(Some(Player::Red)));
+}
+
+/// Values have to fit the board, change the position's hash, and are kept
+/// through restarts, FEN strings and packed bytes.
+#[test]
+fn values_round_trip() {
+    let mut game = HotSeatGame::new(4, 4);
+    let config = |rows| GameConfig {
+        values: Some(values(rows)),
+        ..GameConfig::new(4, 4)
+    };
+    assert_eq!(
+        game.reconfigure(config("11/11")),
+        Err(GameError::InvalidValues)
+    );
+    let plain = game.board().hash();
+    game.reconfigure(config("111/121/111")).expect("values fit");
+    assert_ne!(game.board().hash(), plain);
+    game.do_move("h1.1".parse().expect("valid notation"));
+    let board = game.board();
+    assert!(board.to_fen().ends_with("/111121111"));
+    assert_eq!(BoardState::from_fen(&board.to_fen()).as_ref(), Ok(board));
+    assert_eq!(
+        BoardState::from_bytes(&board.to_bytes()).as_ref(),
+        Ok(board)
+    );
+    game.restart(Player::Red);
+    assert_eq!(game.board().values(), values("111/121/111"));
+    assert!(!CellValues::uniform(4, 4).to_string().contains('2'));
+    assert!("12/1".parse::<CellValues>().is_err());
+    assert!("10".parse::<CellValues>().is_err());
+}
+
+/// The AI plays a board with a valuable center to the end.
+#[cfg(feature = "ai")]
+#[test]
+fn ai_plays_weighted_boxes() {
+    use dots_and_boxes::{GameBuilder, GameMode};
+
+    let mut game = GameBuilder::new(4, 4)
+        .values(values("111/191/111"))
+        .mode(GameMode::AIVsAI)
+        .build()
+        .expect("values fit");
+    game.play_ai_moves();
+    assert!(game.board().is_finished());
+    assert_eq!(game.score(Player::Red) + game.score(Player::Blue), 17);
+}